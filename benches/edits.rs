@@ -0,0 +1,119 @@
+//! Times the edits of multi-kilobyte lines, run with `cargo bench --bench edits`
+//!
+//! the line used to be a `Vec<char>` cloned by every edit for its undo, the typing and deleting
+//! of keys in the middle of the line get timed on that storage against the [`Text`] gap buffer
+//! of the Input, which records the undo edits where they are made; the pastes and inserted
+//! strings get spliced into the line at once
+
+use std::time::{Duration, Instant};
+
+use ragout_assistant::{Edit, History, Input, InputOp, Text, UndoStack};
+
+const RUNS: u32 = 20;
+
+// the keys typed then deleted in the middle of the line
+const KEYS: usize = 1_000;
+
+// the average duration of f over the runs, each run on a fresh Input holding a line of len chars
+// with the cursor in its middle
+fn time(len: usize, f: impl Fn(&mut Input, &mut History)) -> Duration {
+    let line = text(len);
+    let mut total = Duration::ZERO;
+    for _ in 0..RUNS {
+        let mut i = Input::new("> ", false);
+        let mut h = History::new();
+        i.set_text(&line);
+        i.cursor = len / 2;
+        let start = Instant::now();
+        f(&mut i, &mut h);
+        total += start.elapsed();
+    }
+
+    total / RUNS
+}
+
+// the keys typed then deleted on the former storage: the line cloned before every edit and
+// compared with what the edit left for the undo
+fn time_vec(len: usize) -> Duration {
+    let line = text(len).chars().collect::<Vec<char>>();
+    let mut total = Duration::ZERO;
+    for _ in 0..RUNS {
+        let (mut values, mut cursor, mut undo) = (line.clone(), len / 2, UndoStack::default());
+        let start = Instant::now();
+        for key in 0..KEYS * 2 {
+            let before = values.clone();
+            match key < KEYS {
+                true => {
+                    values.insert(cursor, 'x');
+                    cursor += 1;
+                }
+                false => {
+                    cursor -= 1;
+                    values.remove(cursor);
+                }
+            }
+            if let Some(edit) = Edit::diff(&before, &values, cursor, cursor) {
+                undo.push(edit);
+            }
+        }
+        total += start.elapsed();
+    }
+
+    total / RUNS
+}
+
+// the same keys on a bare Text, without the undo
+fn time_text(len: usize) -> Duration {
+    let line = Text::from(text(len).as_str());
+    let mut total = Duration::ZERO;
+    for _ in 0..RUNS {
+        let (mut values, mut cursor) = (line.clone(), len / 2);
+        let start = Instant::now();
+        for key in 0..KEYS * 2 {
+            match key < KEYS {
+                true => {
+                    values.insert(cursor, 'x');
+                    cursor += 1;
+                }
+                false => {
+                    cursor -= 1;
+                    values.remove(cursor);
+                }
+            }
+        }
+        total += start.elapsed();
+    }
+
+    total / RUNS
+}
+
+fn text(len: usize) -> String {
+    "lorem ipsum dolor ".chars().cycle().take(len).collect()
+}
+
+fn main() {
+    println!(
+        "{:>8} {:>14} {:>14} {:>14} {:>14} {:>14}",
+        "chars", "vec keys", "text keys", "input keys", "paste", "insert str"
+    );
+    for len in [1_000, 10_000, 50_000] {
+        let pasted = text(len);
+        let keys = time(len, |i, h| {
+            (0..KEYS).for_each(|_| _ = i.apply(&InputOp::InsertChar('x'), h));
+            (0..KEYS).for_each(|_| _ = i.apply(&InputOp::Backspace, h));
+        });
+        let paste = time(len, |i, h| _ = i.apply(&InputOp::Paste(pasted.clone()), h));
+        let insert = time(len, |i, h| {
+            _ = i.apply(&InputOp::InsertStr(pasted.clone()), h)
+        });
+        println!(
+            "{:>8} {:>14.2?} {:>14.2?} {:>14.2?} {:>14.2?} {:>14.2?}",
+            len,
+            time_vec(len),
+            time_text(len),
+            keys,
+            paste,
+            insert
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use std::ffi::OsString;
+
+use ::clap::error::ErrorKind;
+use ::clap::{Arg, ArgMatches, Command};
+
+use crate::editor::Editor;
+
+/// Parses args with cmd, interactively prompting for every missing required argument first.
+///
+/// Each prompted value is checked with the value parser of its argument, and re-prompted for
+/// until it is accepted; the possible values of an argument are offered through Tab completion
+///
+/// # Errors
+/// Returns the clap error if args can't be parsed (even with all the required arguments
+/// prompted for), or an [`ErrorKind::Io`] error if reading the user input fails
+pub fn prompt_missing<I, T>(
+    editor: &mut Editor,
+    cmd: &Command,
+    args: I,
+) -> Result<ArgMatches, ::clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let mut argv = args.into_iter().map(Into::into).collect::<Vec<OsString>>();
+    let relaxed = cmd.clone().mut_args(|a| a.required(false));
+    let matches = relaxed.clone().try_get_matches_from(&argv)?;
+
+    let prompt = editor.input.prompt.clone();
+    let candidates = std::mem::take(&mut editor.candidates);
+    let mut result = Ok(());
+    for arg in cmd
+        .get_arguments()
+        .filter(|a| a.is_required_set() && !matches.contains_id(a.get_id().as_str()))
+    {
+        result =
+            prompt_arg(editor, &relaxed, &argv, arg).map(|value| push_arg(&mut argv, arg, value));
+        if result.is_err() {
+            break;
+        }
+    }
+    editor.input.overwrite_prompt(&prompt);
+    editor.candidates = candidates;
+    result?;
+
+    cmd.clone().try_get_matches_from(argv)
+}
+
+// reads values for arg until its value parser accepts one
+//
+// values are checked by parsing them along the other args with the relaxed command
+// (whose arguments are all optional), only value errors cause a re-prompt
+fn prompt_arg(
+    editor: &mut Editor,
+    relaxed: &Command,
+    argv: &[OsString],
+    arg: &Arg,
+) -> Result<String, ::clap::Error> {
+    let possible = arg
+        .get_possible_values()
+        .into_iter()
+        .map(|v| v.get_name().to_string())
+        .collect::<Vec<String>>();
+    let name = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map_or(arg.get_id().as_str(), |name| name.as_str());
+    editor.input.overwrite_prompt(&match possible.is_empty() {
+        true => format!("{}: ", name),
+        false => format!("{} [{}]: ", name, possible.join("|")),
+    });
+    editor.candidates = possible;
+
+    loop {
+        let value = editor
+            .read_line()
+            .map_err(|e| ::clap::Error::raw(ErrorKind::Io, e))?;
+        let mut trial = argv.to_vec();
+        push_arg(&mut trial, arg, value.clone());
+        match relaxed.clone().try_get_matches_from(trial) {
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::InvalidValue | ErrorKind::ValueValidation
+                ) =>
+            {
+                // clap renders its errors with bare line feeds, which don't return the cursor
+                // in raw mode
+                let msg = e.render().to_string().replace('\n', "\r\n");
+                _ = std::io::Write::write_all(&mut editor.sol, msg.as_bytes());
+            }
+            _ => return Ok(value),
+        }
+    }
+}
+
+// options go right after the binary name so that they stay in front of any `--`,
+// positionals are appended in their index order
+fn push_arg(argv: &mut Vec<OsString>, arg: &Arg, value: String) {
+    let flag = match (arg.get_long(), arg.get_short()) {
+        (Some(long), _) => Some(format!("--{}", long)),
+        (None, Some(short)) => Some(format!("-{}", short)),
+        (None, None) => None,
+    };
+    match flag {
+        Some(flag) => {
+            let at = argv.len().min(1);
+            argv.splice(at..at, [flag.into(), value.into()]);
+        }
+        None => argv.push(value.into()),
+    }
+}
+
+#[cfg(test)]
+mod test_args {
+    use super::push_arg;
+    use clap::Arg;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_push_arg() {
+        let mut argv = vec![OsString::from("bin"), "--".into(), "x".into()];
+
+        push_arg(&mut argv, &Arg::new("mode").long("mode"), "fast".into());
+        push_arg(&mut argv, &Arg::new("file"), "a.txt".into());
+
+        assert_eq!(argv, ["bin", "--mode", "fast", "--", "x", "a.txt"]);
+    }
+}
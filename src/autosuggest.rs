@@ -0,0 +1,111 @@
+use crate::history::History;
+use crate::input::Input;
+
+/// A fish style autosuggestion: the rest of the newest history entry starting with the line,
+/// previewed dimmed after the end of the line, see [`Input::suggest`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// the text completing the line into the history entry
+    pub text: String,
+    /// the 0 based index of the history entry
+    pub entry: usize,
+}
+
+/// Returns the suggestion completing line into the newest longer history entry starting with
+/// it, None for a blank line
+pub fn suggestion(line: &[char], h: &History) -> Option<Suggestion> {
+    if line.iter().all(|c| c.is_whitespace()) {
+        return None;
+    }
+
+    h.values
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, e)| e.len() > line.len() && e.starts_with(line))
+        .map(|(entry, e)| Suggestion {
+            text: e[line.len()..].iter().collect(),
+            entry,
+        })
+}
+
+impl Input {
+    /// Updates the autosuggestion of the line when [`Input::autosuggest`] is on: only a line
+    /// being typed (no history entry recalled) with the cursor at its end gets one, and never a
+    /// secret line
+    ///
+    /// returns true if the suggestion changed
+    pub fn suggest(&mut self, h: &History) -> bool {
+        let suggestion = match self.autosuggest
+            && self.cursor == self.values.len()
+            && h.cursor == h.len()
+            && !self.is_secret()
+        {
+            true => suggestion(&self.values, h),
+            false => None,
+        };
+
+        let changed = suggestion != self.suggestion;
+        self.suggestion = suggestion;
+
+        changed
+    }
+
+    /// Appends the text of the autosuggestion to the line, what Right and End do at the end of
+    /// the line
+    ///
+    /// returns false if there was no suggestion to accept
+    pub fn accept_suggestion(&mut self) -> bool {
+        match self.suggestion.take() {
+            Some(suggestion) if self.cursor == self.values.len() => {
+                self.put_str(suggestion.text.chars());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // the text previewed dimmed at the cursor: the ghost text, or else the suggestion when the
+    // cursor is at the end of the line
+    pub(crate) fn preview(&self) -> Option<&str> {
+        self.ghost.as_deref().or(self
+            .suggestion
+            .as_ref()
+            .filter(|_| self.cursor == self.values.len())
+            .map(|s| s.text.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod test_autosuggest {
+    use crate::history::History;
+    use crate::input::Input;
+    use crate::ops::InputOp;
+
+    #[test]
+    fn test_suggest() {
+        let mut i = Input::new("testing input> ", false);
+        let mut h = History::new();
+        ["git status", "ls", "git log"]
+            .iter()
+            .for_each(|e| h.push(e.chars().collect()));
+        i.autosuggest = true;
+
+        assert!(!i.suggest(&h));
+        i.apply(&InputOp::InsertStr("git s".into()), &mut h);
+        assert!(i.suggest(&h));
+        assert_eq!(i.preview(), Some("tatus"));
+        i.apply(&InputOp::MoveLeft, &mut h);
+        assert_eq!(i.preview(), None);
+        i.suggest(&h);
+        assert_eq!(i.suggestion, None);
+
+        i.apply(&InputOp::MoveEnd, &mut h);
+        i.apply(&InputOp::Backspace, &mut h);
+        i.suggest(&h);
+        assert_eq!(i.suggestion.as_ref().unwrap().entry, 2);
+        assert!(i.apply(&InputOp::MoveEnd, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "git log");
+        assert!(!i.apply(&InputOp::MoveRight, &mut h));
+    }
+}
@@ -0,0 +1,255 @@
+use std::path::PathBuf;
+
+use crate::completion::Completer;
+use crate::diagnostic::Linter;
+use crate::editor::Editor;
+use crate::error::RagoutError;
+use crate::highlight::Highlighter;
+use crate::history::HistoryPolicy;
+use crate::input::{enter, enter_lenient, show, Init};
+use crate::keymap::Keymap;
+use crate::term::{default_backend, TermBackend};
+use crate::validate::Validator;
+
+/// Configures then creates an [`Editor`], see [`Editor::builder`]
+///
+/// e.g. `Editor::builder().prompt("> ").alt_screen(true).history_file(path).build()?`
+#[derive(Debug, Default)]
+pub struct EditorBuilder {
+    prompt: String,
+    alt_screen: bool,
+    // enters the raw mode like Editor::new when set, failing like Editor::try_new otherwise
+    lenient: bool,
+    backend: Option<Box<dyn TermBackend>>,
+    history_file: Option<PathBuf>,
+    max_entries: Option<usize>,
+    policy: Option<HistoryPolicy>,
+    keymap: Option<Keymap>,
+    multiline: bool,
+    vi: bool,
+    autosuggest: bool,
+    completer: Option<Box<dyn Completer>>,
+    linter: Option<Box<dyn Linter>>,
+    highlighter: Option<Box<dyn Highlighter>>,
+    validator: Option<Box<dyn Validator>>,
+}
+
+impl EditorBuilder {
+    /// Creates a new EditorBuilder: no prompt, no alternate screen, the default backend
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the prompt
+    pub fn prompt(mut self, prompt: &str) -> Self {
+        self.prompt = prompt.to_string();
+        self
+    }
+
+    /// Runs the Editor in the alternate screen
+    pub fn alt_screen(mut self, on: bool) -> Self {
+        self.alt_screen = on;
+        self
+    }
+
+    /// Creates the Editor even if the raw mode could not be entered, see [`Editor::new`]
+    pub fn lenient(mut self, on: bool) -> Self {
+        self.lenient = on;
+        self
+    }
+
+    /// Drives the terminal through the given backend instead of [`default_backend`]
+    pub fn backend(mut self, backend: Box<dyn TermBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Loads the History from the file, which then gets saved after every submitted line, see
+    /// [`crate::History::autosave`]
+    pub fn history_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.history_file = Some(path.into());
+        self
+    }
+
+    /// Sets the most entries the History keeps, see [`crate::History::max_entries`]
+    pub fn max_history(mut self, max: usize) -> Self {
+        self.max_entries = Some(max);
+        self
+    }
+
+    /// Sets which of the submitted lines the History records
+    pub fn history_policy(mut self, policy: HistoryPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Replaces the default emacs style keymap
+    pub fn keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
+
+    /// Lets the lines span several lines, see [`crate::Input::multiline`]
+    pub fn multiline(mut self, on: bool) -> Self {
+        self.multiline = on;
+        self
+    }
+
+    /// Enables the vi bindings, see [`crate::Input::vi`]
+    pub fn vi(mut self, on: bool) -> Self {
+        self.vi = on;
+        self
+    }
+
+    /// Previews the history autosuggestions, see [`crate::Input::autosuggest`]
+    pub fn autosuggest(mut self, on: bool) -> Self {
+        self.autosuggest = on;
+        self
+    }
+
+    /// Sets the producer of the Tab completions
+    pub fn completer(mut self, completer: impl Completer + 'static) -> Self {
+        self.completer = Some(Box::new(completer));
+        self
+    }
+
+    /// Sets the linter checking the line after every key press
+    pub fn linter(mut self, linter: impl Linter + 'static) -> Self {
+        self.linter = Some(Box::new(linter));
+        self
+    }
+
+    /// Sets the highlighter styling the line after every key press
+    pub fn highlighter(mut self, highlighter: impl Highlighter + 'static) -> Self {
+        self.highlighter = Some(Box::new(highlighter));
+        self
+    }
+
+    /// Sets the validator checking the line when Enter is pressed
+    pub fn validator(mut self, validator: impl Validator + 'static) -> Self {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    /// Enters the raw mode and creates the configured Editor, the first prompt gets rendered
+    /// once the Editor is configured
+    ///
+    /// # Errors
+    /// Same as [`crate::init`] unless lenient, and a [`RagoutError::IoError`] if the history
+    /// file could not be read, the terminal then gets restored
+    pub fn build(mut self) -> Result<Editor, RagoutError> {
+        let mut backend = self.backend.take().unwrap_or_else(default_backend);
+        let init = match self.lenient {
+            true => enter_lenient(&self.prompt, self.alt_screen, &mut *backend),
+            false => enter(&self.prompt, self.alt_screen, &mut *backend)?,
+        };
+
+        self.finish(init, backend)
+    }
+
+    // configures the Editor of the entered terminal then renders its first prompt
+    fn finish(self, init: Init, backend: Box<dyn TermBackend>) -> Result<Editor, RagoutError> {
+        let mut editor = Editor::from_init(init, self.alt_screen, backend);
+
+        let h = &mut editor.history;
+        h.max_entries = self.max_entries;
+        if let Some(policy) = self.policy {
+            h.policy = policy;
+        }
+        if let Some(path) = self.history_file {
+            h.load(&path)?;
+            h.autosave = Some(path);
+        }
+        let i = &mut editor.input;
+        i.multiline = self.multiline;
+        i.vi = self.vi;
+        i.autosuggest = self.autosuggest;
+        if let Some(keymap) = self.keymap {
+            editor.keymap = keymap;
+        }
+        editor.completer = self.completer;
+        editor.linter = self.linter;
+        editor.highlighter = self.highlighter;
+        editor.validator = self.validator;
+
+        let shown = show(&mut editor.sol, &editor.input);
+        if !self.lenient {
+            shown?;
+        }
+
+        Ok(editor)
+    }
+}
+
+impl Editor {
+    /// Returns an [`EditorBuilder`] to configure a new Editor with
+    pub fn builder() -> EditorBuilder {
+        EditorBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod test_builder {
+    use super::EditorBuilder;
+    use crate::history::History;
+    use crate::input::{Input, Terminal};
+    use crate::term::TermBackend;
+    use std::io::Result;
+
+    // a backend that never touches the terminal
+    #[derive(Debug)]
+    struct MockBackend;
+
+    impl TermBackend for MockBackend {
+        fn enable_raw_mode(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn disable_raw_mode(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn size(&self) -> Result<(u16, u16)> {
+            Ok((20, 5))
+        }
+
+        fn watch_resize(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn take_resized(&mut self) -> bool {
+            false
+        }
+
+        fn watch_termination(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn take_termination(&mut self) -> Option<i32> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_first_render() {
+        let init = (
+            Terminal::headless(20, 5),
+            Input::new("> ", false),
+            History::new(),
+            String::new(),
+        );
+        let mut editor = EditorBuilder::new()
+            .prompt("> ")
+            .multiline(true)
+            .vi(true)
+            .finish(init, Box::new(MockBackend))
+            .unwrap();
+        assert!(editor.input.multiline && editor.input.vi);
+        assert!(format!("{:?}", editor.backend).contains("MockBackend"));
+
+        // the first prompt already got rendered as a multiline one
+        let mock = editor.sol.mock().unwrap();
+        assert_eq!(mock.take_output(), "\x1b[?2004h\r\x1b[J> ");
+        assert_eq!(mock.rows()[0], ">");
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A token other threads can trigger to make a blocking [`crate::Editor::read_line`] return
+/// promptly, e.g. on shutdown or when a network event makes the prompt obsolete
+///
+/// clones share the same state
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Creates a new untriggered CancelToken
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Triggers the token, the read waiting on it returns a [`Cancelled`] error
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if the token was triggered
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Untriggers the token so that it can be used by the next reads
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+
+    // returns true once per trigger
+    pub(crate) fn take(&self) -> bool {
+        self.0.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// The error carried by the [`std::io::Error`] returned by a read cancelled through a
+/// [`CancelToken`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the read was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+impl Cancelled {
+    /// Returns true if the error comes from a cancelled read
+    pub fn is(e: &std::io::Error) -> bool {
+        e.get_ref().is_some_and(|e| e.is::<Cancelled>())
+    }
+}
+
+impl From<Cancelled> for std::io::Error {
+    fn from(cancelled: Cancelled) -> Self {
+        std::io::Error::other(cancelled)
+    }
+}
+
+#[cfg(test)]
+mod test_cancel {
+    use super::{CancelToken, Cancelled};
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::new();
+        let other = token.clone();
+        std::thread::spawn(move || other.cancel()).join().unwrap();
+
+        assert!(token.is_cancelled());
+        assert!(token.take());
+        assert!(!token.take());
+        assert!(Cancelled::is(&Cancelled.into()));
+        assert!(!Cancelled::is(&std::io::ErrorKind::Interrupted.into()));
+    }
+}
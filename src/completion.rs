@@ -0,0 +1,137 @@
+use std::fmt::Debug;
+use std::path::Path;
+
+/// A candidate replacement of the text before the cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// the char index the replaced text starts at, the text up to the cursor gets replaced
+    pub start: usize,
+    /// the replacement
+    pub text: String,
+}
+
+impl Completion {
+    /// Creates a new Completion
+    pub fn new(start: usize, text: &str) -> Self {
+        Self {
+            start,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// Produces the Tab completions of the line being edited, see [`crate::Editor::completer`]
+///
+/// the completions of a single call are expected to share their start, the Editor completes
+/// the common prefix of the candidates then lets the user cycle through them in a menu
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait Completer: Debug {
+    /// Returns the completions of the line for a cursor at the given char index
+    fn complete(&self, line: &str, cursor: usize) -> Vec<Completion>;
+}
+
+/// Completes the whitespace delimited word before the cursor with the words of a list
+/// starting with it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WordCompleter(pub Vec<String>);
+
+impl Completer for WordCompleter {
+    fn complete(&self, line: &str, cursor: usize) -> Vec<Completion> {
+        let (start, word) = word_before(line, cursor);
+
+        self.0
+            .iter()
+            .filter(|w| w.starts_with(&word))
+            .map(|w| Completion::new(start, w))
+            .collect()
+    }
+}
+
+/// Completes the whitespace delimited word before the cursor as a file path, relative to the
+/// current directory unless absolute; the directories get a trailing `/` and the hidden
+/// files are only offered once the file name starts with a `.`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathCompleter;
+
+impl Completer for PathCompleter {
+    fn complete(&self, line: &str, cursor: usize) -> Vec<Completion> {
+        let (start, word) = word_before(line, cursor);
+        let (dir, name) = match word.rfind('/') {
+            Some(idx) => (&word[..idx + 1], &word[idx + 1..]),
+            None => ("", word.as_str()),
+        };
+        let Ok(entries) = std::fs::read_dir(match dir.is_empty() {
+            true => Path::new("."),
+            false => Path::new(dir),
+        }) else {
+            return Vec::new();
+        };
+        let mut completions = entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                let hidden = file_name.starts_with('.') && !name.starts_with('.');
+                if hidden || !file_name.starts_with(name) {
+                    return None;
+                }
+                let slash = match entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    true => "/",
+                    false => "",
+                };
+
+                Some(Completion::new(
+                    start,
+                    &format!("{}{}{}", dir, file_name, slash),
+                ))
+            })
+            .collect::<Vec<Completion>>();
+        completions.sort_by(|a, b| a.text.cmp(&b.text));
+
+        completions
+    }
+}
+
+// the char index and the text of the whitespace delimited word ending at cursor
+pub(crate) fn word_before(line: &str, cursor: usize) -> (usize, String) {
+    let chars = line.chars().take(cursor).collect::<Vec<char>>();
+    let start = chars
+        .iter()
+        .rposition(|c| c.is_whitespace())
+        .map_or(0, |idx| idx + 1);
+
+    (start, chars[start..].iter().collect())
+}
+
+#[cfg(test)]
+mod test_completion {
+    use super::{Completer, Completion, PathCompleter, WordCompleter};
+
+    #[test]
+    fn test_word_completer() {
+        let words = WordCompleter(["help", "hello", "exit"].map(String::from).to_vec());
+
+        assert_eq!(
+            words.complete("say hel there", 7),
+            [Completion::new(4, "help"), Completion::new(4, "hello")]
+        );
+        assert_eq!(words.complete("", 0).len(), 3);
+    }
+
+    #[test]
+    fn test_path_completer() {
+        let texts = |line: &str| {
+            PathCompleter
+                .complete(line, line.chars().count())
+                .into_iter()
+                .map(|c| c.text)
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(texts("cat sr"), ["src/"]);
+        assert_eq!(texts("cat src/lib"), ["src/lib.rs"]);
+        assert!(texts("cat ").iter().all(|t| !t.starts_with('.')));
+        assert!(texts("cat .").contains(&".git/".to_string()));
+    }
+}
@@ -0,0 +1,272 @@
+use std::io::Write;
+use std::sync::Mutex;
+
+// the DEC private modes the editor may toggle: cursor visibility, mouse reporting and
+// bracketed paste
+#[cfg(unix)]
+const MODES: [u16; 6] = [25, 1000, 1002, 1003, 1006, 2004];
+// how long the terminal gets to answer the queries
+#[cfg(unix)]
+const TIMEOUT_MS: i32 = 100;
+
+// the context captured by the last init, restored by deinit
+static CAPTURED: Mutex<Option<TermContext>> = Mutex::new(None);
+// the bytes typed ahead that got read along with the replies to the queries
+static TYPED_AHEAD: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+/// The terminal state found before the editor took over: the termios flags, the cursor
+/// visibility and style and the mouse and paste modes
+///
+/// the modes and the cursor style are asked to the terminal (DECRQM and DECRQSS), the ones
+/// it does not report are left alone on restore
+#[derive(Clone, Default)]
+pub struct TermContext {
+    #[cfg(unix)]
+    termios: Option<libc::termios>,
+    /// the DEC private modes reported by the terminal and whether they were set
+    pub modes: Vec<(u16, bool)>,
+    /// the DECSCUSR cursor style reported by the terminal
+    pub cursor_style: Option<u8>,
+}
+
+impl std::fmt::Debug for TermContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TermContext")
+            .field("modes", &self.modes)
+            .field("cursor_style", &self.cursor_style)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TermContext {
+    /// Captures the termios flags, must be called before entering the raw mode
+    pub fn capture() -> Self {
+        Self {
+            #[cfg(unix)]
+            termios: crate::state::termios().ok().flatten(),
+            ..Self::default()
+        }
+    }
+
+    /// Asks the terminal about its modes and cursor style, must be called in raw mode
+    ///
+    /// the queries end with a primary device attributes request, which every terminal answers,
+    /// so that terminals ignoring the others don't make it wait for the whole timeout
+    pub fn query(&mut self, sol: &mut impl Write) {
+        #[cfg(unix)]
+        if let Some(read) = ask(sol) {
+            let (replies, typed) = split_replies(&read);
+            (self.modes, self.cursor_style) = parse_replies(&replies);
+            if let Ok(mut typed_ahead) = TYPED_AHEAD.lock() {
+                typed_ahead.extend(typed);
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = sol;
+    }
+
+    /// Puts the terminal back in the captured state, the modes and the cursor style are
+    /// written to w, the termios flags are set once w is flushed
+    pub fn restore(&self, w: &mut impl Write) -> std::io::Result<()> {
+        w.write_all(self.escapes().as_bytes())?;
+        w.flush()?;
+        #[cfg(unix)]
+        if let Some(termios) = self.termios {
+            // SAFETY: termios was filled by tcgetattr
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSADRAIN, &termios) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+
+        Ok(())
+    }
+
+    // the escapes setting the captured modes and cursor style back
+    fn escapes(&self) -> String {
+        let mut escapes = self
+            .modes
+            .iter()
+            .map(|(mode, set)| format!("\x1b[?{}{}", mode, if *set { 'h' } else { 'l' }))
+            .collect::<String>();
+        if let Some(style) = self.cursor_style {
+            escapes.push_str(&format!("\x1b[{} q", style));
+        }
+
+        escapes
+    }
+}
+
+/// Stores the context to be restored by the next [`crate::deinit`]
+pub(crate) fn save(context: TermContext) {
+    if let Ok(mut captured) = CAPTURED.lock() {
+        *captured = Some(context);
+    }
+}
+
+/// Takes the context stored by the last [`crate::init`]
+pub(crate) fn take() -> Option<TermContext> {
+    CAPTURED.lock().ok()?.take()
+}
+
+// takes the bytes typed while the terminal was being queried, for the Editor to decode them
+// as if it had read them itself
+pub(crate) fn take_typed_ahead() -> Vec<u8> {
+    TYPED_AHEAD
+        .lock()
+        .map(|mut typed| std::mem::take(&mut *typed))
+        .unwrap_or_default()
+}
+
+// sends the queries then reads the replies, None if stdin or stdout is not a terminal
+#[cfg(unix)]
+fn ask(sol: &mut impl Write) -> Option<Vec<u8>> {
+    use std::io::IsTerminal;
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let mut queries = MODES
+        .iter()
+        .map(|mode| format!("\x1b[?{}$p", mode))
+        .collect::<String>();
+    queries.push_str("\x1bP$q q\x1b\\\x1b[c");
+    sol.write_all(queries.as_bytes()).ok()?;
+    sol.flush().ok()?;
+
+    Some(read_replies())
+}
+
+// reads stdin until the device attributes reply comes in or the terminal stops answering, the
+// keys typed meanwhile included
+#[cfg(unix)]
+fn read_replies() -> Vec<u8> {
+    let mut replies = Vec::new();
+    let mut buf = [0u8; 256];
+    let mut fd = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // SAFETY: poll watches the single pollfd, read fills at most buf.len() bytes of buf
+    while unsafe { libc::poll(&mut fd, 1, TIMEOUT_MS) } > 0 {
+        let n = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+        if n <= 0 {
+            break;
+        }
+        replies.extend_from_slice(&buf[..n as usize]);
+        // the user may have typed past the last reply
+        if device_attributes_end(&split_replies(&replies).0) {
+            break;
+        }
+    }
+
+    replies
+}
+
+// whether the replies end with the `CSI ? ... c` device attributes reply
+#[cfg(unix)]
+fn device_attributes_end(replies: &[u8]) -> bool {
+    replies.ends_with(b"c")
+        && replies
+            .windows(3)
+            .rposition(|w| w == b"\x1b[?")
+            .is_some_and(|start| {
+                replies[start + 3..replies.len() - 1]
+                    .iter()
+                    .all(|b| b.is_ascii_digit() || *b == b';')
+            })
+}
+
+// tells the replies to the queries (`CSI ? params $ y`, `CSI ? params c` and `DCS ... ST`)
+// apart from the bytes the user typed meanwhile, which come back second
+#[cfg(unix)]
+fn split_replies(read: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let (mut replies, mut typed) = (Vec::new(), Vec::new());
+    let mut idx = 0;
+    while idx < read.len() {
+        let rest = &read[idx..];
+        let len = if rest.starts_with(b"\x1b[?") {
+            let params = rest[3..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit() || **b == b';')
+                .count();
+            match &rest[3 + params..] {
+                [b'$', b'y', ..] => Some(3 + params + 2),
+                [b'c', ..] => Some(3 + params + 1),
+                _ => None,
+            }
+        } else if rest.starts_with(b"\x1bP") {
+            rest.windows(2)
+                .position(|w| w == b"\x1b\\")
+                .map(|end| end + 2)
+        } else {
+            None
+        };
+        match len {
+            Some(len) => replies.extend_from_slice(&rest[..len]),
+            None => typed.push(rest[0]),
+        }
+        idx += len.unwrap_or(1);
+    }
+
+    (replies, typed)
+}
+
+// picks the mode reports (`CSI ? mode ; status $ y`) and the cursor style report
+// (`DCS 1 $ r style SP q ST`) out of the replies
+#[cfg(unix)]
+fn parse_replies(replies: &[u8]) -> (Vec<(u16, bool)>, Option<u8>) {
+    let replies = String::from_utf8_lossy(replies);
+    let mut modes = Vec::new();
+    for report in replies.split("\x1b[?").skip(1) {
+        let Some((report, _)) = report.split_once("$y") else {
+            continue;
+        };
+        let Some((mode, status)) = report.split_once(';') else {
+            continue;
+        };
+        let (Ok(mode), Ok(status)) = (mode.parse::<u16>(), status.parse::<u8>()) else {
+            continue;
+        };
+        // 0 is an unknown mode, 3 and 4 are permanently set and reset
+        match status {
+            1 | 2 => modes.push((mode, status == 1)),
+            _ => (),
+        }
+    }
+    let cursor_style = replies
+        .split_once("\x1bP1$r")
+        .and_then(|(_, report)| report.split_once(" q"))
+        .and_then(|(style, _)| style.parse::<u8>().ok());
+
+    (modes, cursor_style)
+}
+
+#[cfg(all(test, unix))]
+mod test_context {
+    use super::{parse_replies, split_replies, TermContext};
+
+    #[test]
+    fn test_parse_replies() {
+        let replies =
+            b"\x1b[?25;1$y\x1b[?1000;2$y\x1b[?1003;0$y\x1b[?2004;2$y\x1bP1$r4 q\x1b\\\x1b[?62;22c";
+        let (modes, cursor_style) = parse_replies(replies);
+        assert_eq!(modes, [(25, true), (1000, false), (2004, false)]);
+        assert_eq!(cursor_style, Some(4));
+
+        let context = TermContext {
+            modes,
+            cursor_style,
+            ..TermContext::default()
+        };
+        assert_eq!(context.escapes(), "\x1b[?25h\x1b[?1000l\x1b[?2004l\x1b[4 q");
+        assert_eq!(parse_replies(b"\x1b[?62;22c"), (Vec::new(), None));
+    }
+
+    #[test]
+    fn test_split_replies() {
+        let (replies, typed) =
+            split_replies(b"l\x1b[?25;1$ys\x1bP1$r4 q\x1b\\\x1b[A\x1b[?62;22c\r");
+        assert_eq!(replies, b"\x1b[?25;1$y\x1bP1$r4 q\x1b\\\x1b[?62;22c");
+        assert_eq!(typed, b"ls\x1b[A\r");
+    }
+}
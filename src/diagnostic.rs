@@ -0,0 +1,209 @@
+use std::cmp::Reverse;
+use std::fmt::Debug;
+use std::ops::Range;
+
+use crate::style::{Color, Style};
+
+/// How serious a [`Diagnostic`] is, picks the color of its underline and message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    #[default]
+    Error,
+}
+
+impl Severity {
+    /// Returns the color of the severity: blue, yellow or red
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Info => Color::Blue,
+            Self::Warning => Color::Yellow,
+            Self::Error => Color::Red,
+        }
+    }
+
+    /// Returns the label prefixing the messages of the severity
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+
+    // the escape underlining text in the color of the severity, ended by `\x1b[24;59m`
+    fn underline(&self) -> &'static str {
+        match self {
+            Self::Info => "\x1b[4;58;5;4m",
+            Self::Warning => "\x1b[4;58;5;3m",
+            Self::Error => "\x1b[4;58;5;1m",
+        }
+    }
+}
+
+/// A message about a part of the line being edited, rendered as an underline of that part with
+/// the message on the status line, see [`crate::Input::diagnostics`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// the chars of the line the message is about, an empty span marks the char at its start
+    pub span: Range<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Creates a new Diagnostic
+    pub fn new(span: Range<usize>, severity: Severity, message: &str) -> Self {
+        Self {
+            span,
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    /// Creates a new error Diagnostic
+    pub fn error(span: Range<usize>, message: &str) -> Self {
+        Self::new(span, Severity::Error, message)
+    }
+
+    /// Creates a new warning Diagnostic
+    pub fn warning(span: Range<usize>, message: &str) -> Self {
+        Self::new(span, Severity::Warning, message)
+    }
+
+    // the chars underlined in a line of len chars, the empty spans widened to a single char
+    pub(crate) fn marked(&self, len: usize) -> Range<usize> {
+        let start = self.span.start.min(len);
+        let end = self.span.end.clamp(start, len);
+        match start == end && end < len {
+            true => start..end + 1,
+            false => start..end,
+        }
+    }
+
+    // the status line text of the diagnostic
+    pub(crate) fn status(&self) -> String {
+        format!(
+            "{}{}: {}\x1b[0m",
+            Style::new().fg(self.severity.color()).sgr(),
+            self.severity.label(),
+            self.message
+        )
+    }
+}
+
+/// Checks the line as it gets edited, the diagnostics it returns replace those of the Input
+/// after every key press, see [`crate::Editor::linter`]
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait Linter: Debug {
+    /// Returns the diagnostics of the line, in any order
+    fn lint(&mut self, values: &[char]) -> Vec<Diagnostic>;
+}
+
+/// A Linter calling a closure
+pub struct FnLinter<F>(pub F);
+
+impl<F> Debug for FnLinter<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnLinter").finish_non_exhaustive()
+    }
+}
+
+impl<F: FnMut(&[char]) -> Vec<Diagnostic>> Linter for FnLinter<F> {
+    fn lint(&mut self, values: &[char]) -> Vec<Diagnostic> {
+        (self.0)(values)
+    }
+}
+
+// the escapes switching the underline at idx, given the diagnostics of a line of len chars:
+// the most severe diagnostic covering idx wins
+pub(crate) fn underline_at(
+    diagnostics: &[Diagnostic],
+    idx: usize,
+    len: usize,
+) -> Option<&'static str> {
+    let covering = |idx: usize| {
+        diagnostics
+            .iter()
+            .filter(|d| d.marked(len).contains(&idx))
+            .map(|d| d.severity)
+            .max()
+    };
+    let (before, now) = (idx.checked_sub(1).and_then(covering), covering(idx));
+    match (before, now) {
+        (before, now) if before == now => None,
+        (_, Some(severity)) => Some(severity.underline()),
+        (_, None) => Some("\x1b[24;59m"),
+    }
+}
+
+// the escape of the underline at idx, given the diagnostics of a line of len chars, regardless
+// of the underline before it
+pub(crate) fn underline_of(
+    diagnostics: &[Diagnostic],
+    idx: usize,
+    len: usize,
+) -> Option<&'static str> {
+    diagnostics
+        .iter()
+        .filter(|d| d.marked(len).contains(&idx))
+        .map(|d| d.severity)
+        .max()
+        .map(|severity| severity.underline())
+}
+
+// the diagnostic shown on the status line: the most severe one at the cursor, else the most
+// severe one
+pub(crate) fn focused(
+    diagnostics: &[Diagnostic],
+    cursor: usize,
+    len: usize,
+) -> Option<&Diagnostic> {
+    let at_cursor = diagnostics.iter().filter(|d| {
+        let marked = d.marked(len);
+        marked.contains(&cursor) || marked.end == cursor
+    });
+
+    // min_by_key keeps the first of the equally severe ones
+    at_cursor
+        .min_by_key(|d| Reverse(d.severity))
+        .or_else(|| diagnostics.iter().min_by_key(|d| Reverse(d.severity)))
+}
+
+#[cfg(test)]
+mod test_diagnostic {
+    use super::{focused, underline_at, Diagnostic};
+
+    #[test]
+    fn test_marks() {
+        let diagnostics = [
+            Diagnostic::warning(0..3, "unused"),
+            Diagnostic::error(2..2, "missing"),
+            Diagnostic::error(9..12, "out of the line"),
+        ];
+        assert_eq!(diagnostics[1].marked(5), 2..3);
+        assert_eq!(diagnostics[2].marked(5), 5..5);
+
+        let escapes = (0..=5)
+            .map(|idx| underline_at(&diagnostics, idx, 5))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            escapes,
+            [
+                Some("\x1b[4;58;5;3m"),
+                None,
+                Some("\x1b[4;58;5;1m"),
+                Some("\x1b[24;59m"),
+                None,
+                None,
+            ]
+        );
+
+        assert_eq!(focused(&diagnostics, 1, 5), Some(&diagnostics[0]));
+        assert_eq!(focused(&diagnostics, 4, 5).unwrap().message, "missing");
+        assert_eq!(focused(&[], 0, 0), None);
+    }
+}
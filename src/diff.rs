@@ -0,0 +1,213 @@
+use crate::input::{clusters, Input, TerminalWriter};
+
+// a displayed cluster of a rendered line along with the escapes written before it, and the
+// cells it takes
+pub(crate) type Unit = (String, usize);
+
+// what a render drew on the single row of a line, for the next render to only redraw the cells
+// that changed, see Input::write_prompt
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Drawn {
+    pub(crate) units: Vec<Unit>,
+    pub(crate) right: String,
+    // the writes the terminal went through once the line was drawn
+    pub(crate) writes: u64,
+}
+
+// splits a rendered text into its displayed clusters, each with the escapes before it; the
+// escapes after the last cluster make a last unit taking no cells
+pub(crate) fn units(text: &str) -> Vec<Unit> {
+    let mut visible = Vec::new();
+    let mut before = Vec::new();
+    let mut escapes = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            visible.push(c);
+            before.push(std::mem::take(&mut escapes));
+            continue;
+        }
+        escapes.push(c);
+        match chars.next() {
+            // CSI: the parameters up to the final byte
+            Some('[') => {
+                escapes.push('[');
+                for c in chars.by_ref() {
+                    escapes.push(c);
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: up to BEL or ST
+            Some(']') => {
+                escapes.push(']');
+                while let Some(c) = chars.next() {
+                    escapes.push(c);
+                    match c {
+                        '\x07' => break,
+                        '\x1b' => {
+                            escapes.extend(chars.next());
+                            break;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            next => escapes.extend(next),
+        }
+    }
+
+    let mut units = clusters(&visible)
+        .map(|(range, cells)| {
+            let text = range
+                .map(|idx| format!("{}{}", before[idx], visible[idx]))
+                .collect::<String>();
+            (text, cells)
+        })
+        .collect::<Vec<Unit>>();
+    if !escapes.is_empty() {
+        units.push((escapes, 0));
+    }
+
+    units
+}
+
+// the style escapes in effect once the units are written, those before the last reset left out
+fn active_style(units: &[Unit]) -> String {
+    let mut active = String::new();
+    let text = units
+        .iter()
+        .map(|(text, _)| text.as_str())
+        .collect::<String>();
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("\x1b[") {
+        rest = &rest[start..];
+        let Some(end) = rest[2..].find(|c| ('\x40'..='\x7e').contains(&c)) else {
+            break;
+        };
+        let (escape, after) = rest.split_at(end + 3);
+        if escape.ends_with('m') {
+            let params = &escape[2..escape.len() - 1];
+            match params.is_empty() || params == "0" || params.starts_with("0;") {
+                true => active = escape.to_string(),
+                false => active.push_str(escape),
+            }
+        }
+        rest = after;
+    }
+
+    match active.as_str() {
+        "\x1b[m" | "\x1b[0m" => String::new(),
+        _ => active,
+    }
+}
+
+// the escapes turning the row old was drawn as into new: the cursor goes to the first unit
+// that changed, the style in effect there gets restored and the units from it get written,
+// then what is left of a shorter row gets cleared
+//
+// clear also clears the rest of a changed row, e.g. for the right prompt to be written again
+pub(crate) fn diff(old: &[Unit], new: &[Unit], clear: bool) -> String {
+    let same = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    if same == old.len() && same == new.len() {
+        return String::new();
+    }
+    let cells = |units: &[Unit]| units.iter().map(|(_, cells)| cells).sum::<usize>();
+
+    let mut escapes = format!("\x1b[{}G", cells(&new[..same]) + 1);
+    let active = active_style(&new[..same]);
+    if active != active_style(old) {
+        escapes.push_str("\x1b[0m");
+        escapes.push_str(&active);
+    }
+    new[same..]
+        .iter()
+        .for_each(|(text, _)| escapes.push_str(text));
+    if clear || cells(new) < cells(old) {
+        escapes.push_str("\x1b[K");
+    }
+
+    escapes
+}
+
+impl Input {
+    /// Makes the next render redraw the whole line, for when something got written to the
+    /// terminal without going through the [`TerminalWriter`] the Input renders to
+    pub fn invalidate(&self) {
+        self.drawn.replace(None);
+    }
+
+    // the drawn line when nothing else was written to the terminal since it was drawn
+    pub(crate) fn take_drawn(&self, sol: &dyn TerminalWriter) -> Option<Drawn> {
+        self.drawn
+            .take()
+            .filter(|drawn| Some(drawn.writes) == sol.written())
+    }
+
+    // keeps the drawn line valid after the writes of the render that drew it, written being
+    // what the terminal went through before them
+    pub(crate) fn keep_drawn(&self, written: Option<u64>, sol: &dyn TerminalWriter) {
+        let mut drawn = self.drawn.borrow_mut();
+        match (drawn.as_mut(), sol.written()) {
+            (Some(drawn), Some(writes)) if Some(drawn.writes) == written => drawn.writes = writes,
+            _ => *drawn = None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_diff {
+    use super::{diff, units};
+    use crate::history::History;
+    use crate::input::Input;
+    use crate::mock::MockTerminal;
+    use crate::ops::InputOp;
+    use std::io::Write;
+
+    #[test]
+    fn test_units_diff() {
+        let styled = units("> \x1b[1mab\x1b[0m");
+        assert_eq!(styled[2], ("\x1b[1ma".to_string(), 1));
+        assert_eq!(styled[4], ("\x1b[0m".to_string(), 0));
+
+        assert_eq!(diff(&units("> ab"), &units("> abc"), false), "\x1b[5Gc");
+        assert_eq!(
+            diff(&units("> abc"), &units("> ab"), false),
+            "\x1b[5G\x1b[K"
+        );
+        assert_eq!(diff(&units("> ab"), &units("> ab"), true), "");
+        assert_eq!(
+            diff(&styled, &units("> \x1b[1mac\x1b[0m"), false),
+            "\x1b[4G\x1b[0m\x1b[1mc\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_diff_render() {
+        let mut term = MockTerminal::new(20, 3);
+        let mut i = Input::new("> ", false);
+        let mut h = History::new();
+        i.set_text("echo");
+        i.render(&mut term).unwrap();
+        term.take_output();
+
+        i.apply(&InputOp::InsertStr(" hi".into()), &mut h);
+        i.render(&mut term).unwrap();
+        assert_eq!(term.take_output(), "\x1b[7G hi\x1b[10G");
+        i.apply(&InputOp::MoveLeft, &mut h);
+        i.render(&mut term).unwrap();
+        assert_eq!(term.take_output(), "\x1b[9G");
+        assert_eq!(term.rows()[0], "> echo hi");
+
+        // something else got written, the whole line gets redrawn
+        term.write_all(b"\r\x1b[2Kjunk").unwrap();
+        term.take_output();
+        i.render(&mut term).unwrap();
+        assert!(term.take_output().starts_with("\x1b[2K\r> echo hi"));
+        i.invalidate();
+        i.render(&mut term).unwrap();
+        assert!(term.take_output().starts_with("\x1b[2K\r"));
+        assert_eq!(term.rows()[0], "> echo hi");
+    }
+}
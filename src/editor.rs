@@ -0,0 +1,1669 @@
+use std::io::{ErrorKind, IsTerminal, Write};
+use std::ops::{ControlFlow, Range};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{poll, read, Event, KeyEventKind};
+
+use crate::cancel::{CancelToken, Cancelled};
+use crate::completion::{word_before, Completer};
+use crate::diagnostic::Linter;
+use crate::error::RagoutError;
+#[cfg(unix)]
+use crate::events::read_events;
+use crate::events::{mouse_from_crossterm, Decoder, KeyEvent, Mouse, MouseKind};
+use crate::expand::Expander;
+use crate::finder::FinderSource;
+use crate::highlight::Highlighter;
+use crate::history::{Dups, History};
+use crate::hooks::{poll_hooks, EventHook, Hooked};
+use crate::input::{
+    init_lenient, init_with, text_width, CharSearch, CursorShape, Init, Input, Mode, Terminal,
+};
+use crate::key::{from_crossterm, Code, Key};
+use crate::keymap::Keymap;
+use crate::kill::osc52;
+use crate::middleware::{intercept, KeyMiddleware};
+use crate::newline::LineEndings;
+use crate::ops::InputOp;
+use crate::replay::Recorder;
+use crate::segments::PromptSegments;
+use crate::state::{set_alt_screen, TermStack};
+use crate::store::HistoryStore;
+use crate::style::{Color, Style};
+use crate::suggest::did_you_mean;
+use crate::term::{default_backend, TermBackend};
+use crate::validate::{Validation, Validator};
+
+/// Bundles the terminal handle and the state returned by [`crate::init`], configured through
+/// [`Editor::builder`] or its pub fields
+#[derive(Debug)]
+pub struct Editor {
+    /// restores the terminal once the Editor is dropped
+    pub sol: Terminal,
+    pub input: Input,
+    pub history: History,
+    pub user_input: String,
+    pub backend: Box<dyn TermBackend>,
+    /// words offered by Tab completion
+    pub candidates: Vec<String>,
+    /// produces the Tab completions instead of the candidates when set
+    pub completer: Option<Box<dyn Completer>>,
+    /// the editing operations of the keys, see [`Keymap`]
+    pub keymap: Keymap,
+    /// reads and decodes the key presses, pastes and mouse reports from stdin itself, the
+    /// resizes being caught through SIGWINCH; set on unix when stdin is a terminal, and for
+    /// the terminals not using UTF-8 with the `encoding` feature, the reads go through
+    /// crossterm otherwise
+    pub decoder: Option<Decoder>,
+    /// the commands the corrections of [`Editor::suggest_correction`] are picked from,
+    /// along with the history entries
+    pub commands: Vec<String>,
+    /// whether the copied texts also get put on the system clipboard, through the OSC 52 escape
+    pub clipboard: bool,
+    /// the prompt segments computed in the background, which then make up the Input prompt
+    pub segments: Option<PromptSegments>,
+    /// the terminal modes and the saved terminal configurations
+    pub terminal: TermStack,
+    /// what the terminal sends for Enter and what the editor writes to end lines
+    pub line_endings: LineEndings,
+    /// the token cancelling the blocking reads when triggered from another thread
+    pub cancel: Option<CancelToken>,
+    /// where the submitted lines get persisted
+    pub store: Option<Box<dyn HistoryStore>>,
+    /// the items the Ctrl-T fuzzy finder picks from, Ctrl-T rings the bell when unset
+    pub finder: Option<FinderSource>,
+    /// what Ctrl-X e rewrites the word under the cursor (or the selection) with,
+    /// see [`Editor::expand`]
+    pub expander: Option<Box<dyn Expander>>,
+    /// the middlewares each key press goes through, in order, before the keymap
+    pub middlewares: Vec<Box<dyn KeyMiddleware>>,
+    /// the custom event sources polled while waiting for the key presses
+    pub hooks: Vec<Box<dyn EventHook>>,
+    /// records the key presses and pastes to a file, see [`crate::replay`]
+    pub recorder: Option<Recorder>,
+    /// checks the line after every key press, its diagnostics get rendered in and under the line
+    pub linter: Option<Box<dyn Linter>>,
+    /// styles the line after every key press, see [`Input::highlight`]
+    pub highlighter: Option<Box<dyn Highlighter>>,
+    /// checks the line when Enter is pressed: an incomplete line goes on and an invalid one
+    /// stays in place with its error under it, instead of getting submitted
+    pub validator: Option<Box<dyn Validator>>,
+    /// the comment string Alt-# prefixes the line with before storing it in the history
+    pub comment: String,
+    /// turns the History off: the submitted lines are neither recorded, stored nor autosaved and the
+    /// history navigation keys only ring the bell, see [`Editor::read_line_no_history`] for a
+    /// single read
+    pub no_history: bool,
+    /// whether the backslash-newline pairs of continued lines are removed from the submitted
+    /// line
+    pub strip_continuations: bool,
+    // the lines entered so far when the current line continues previous ones
+    pending: Vec<char>,
+    // the Input prompt to restore once the continued line is submitted
+    saved_prompt: Option<String>,
+    // the line ending the block being read by read_block
+    sentinel: Option<String>,
+    // whether the termination signals reported by the backend get looked for, see
+    // autosave_on_termination
+    termination_watched: bool,
+    // the correction offered by the last did you mean message
+    correction: Option<String>,
+    // whether the message is the error of a line the validator rejected
+    rejected: bool,
+    // when and where the last left click happened, to detect double clicks
+    last_click: Option<(Instant, usize)>,
+    // whether the last key was a carriage return, for the Enter translation
+    after_cr: bool,
+    // whether the next key gets inserted literally, set by Ctrl-V
+    quoted: bool,
+    // the direction of the char jump waiting for its char, set by Ctrl-] and Ctrl-Alt-]
+    find: Option<bool>,
+    // whether the last key was the Ctrl-X prefix
+    ctrl_x: bool,
+    // the hex digits of the codepoint being entered after Ctrl-X u
+    codepoint: Option<String>,
+    // the number of bottom rows reserved for the prompt by pin_bottom
+    pinned: Option<u16>,
+    // the index of the history entry loaded by the next read_line, set by Ctrl-O
+    preload: Option<usize>,
+    // how far back the last Alt-. went and the span of the word it inserted
+    last_arg: Option<(usize, Range<usize>)>,
+    // the completion menu opened by Tab when several candidates share no longer prefix
+    menu: Option<Menu>,
+    // whether Enter leaves the submission to prompt_parse, and whether it was pressed
+    defer_submit: bool,
+    entered: bool,
+    // whether the History is off for the current read only
+    private: bool,
+    // whether the resizes are watched for the reads of the decoder
+    #[cfg(unix)]
+    resize_watched: bool,
+    // the events decoded from the last read of the decoder, or replayed, and not yet handled
+    pub(crate) queued: std::collections::VecDeque<KeyEvent>,
+}
+
+// the candidates of an open completion menu, the selected one gets previewed as ghost text
+// and the candidates get listed on the status line
+#[derive(Debug)]
+struct Menu {
+    // the length in chars of the completed word
+    word_len: usize,
+    matches: Vec<String>,
+    selected: usize,
+    // the status line message the listing replaced
+    message: Option<String>,
+}
+
+impl Menu {
+    // the part of the selected candidate that would be inserted
+    fn preview(&self) -> String {
+        self.matches[self.selected]
+            .chars()
+            .skip(self.word_len)
+            .collect()
+    }
+
+    // the candidates fitting in width cells, the selected one in reverse video
+    fn listing(&self, width: usize) -> String {
+        let mut listing = String::new();
+        let mut cells = 0;
+        for (idx, candidate) in self.matches.iter().enumerate() {
+            let len = text_width(candidate) + 2;
+            if cells + len > width.saturating_sub(1) {
+                listing.push('…');
+                break;
+            }
+            cells += len;
+            match idx == self.selected {
+                true => listing.push_str(&format!("\x1b[7m{}\x1b[27m  ", candidate)),
+                false => listing.push_str(&format!("{}  ", candidate)),
+            }
+        }
+
+        listing
+    }
+}
+
+impl Editor {
+    /// Enables raw mode and creates a new Editor, see [`crate::init`]
+    ///
+    /// the Editor gets created even if the raw mode could not be entered, see
+    /// [`Editor::try_new`] to handle that
+    pub fn new(prompt: &str, alt_screen: bool) -> Self {
+        Self::with_backend(prompt, alt_screen, default_backend())
+    }
+
+    /// Same as [`Editor::new`] but drives the terminal through the given backend
+    pub fn with_backend(prompt: &str, alt_screen: bool, mut backend: Box<dyn TermBackend>) -> Self {
+        let init = init_lenient(prompt, alt_screen, &mut *backend);
+        Self::from_init(init, alt_screen, backend)
+    }
+
+    /// Same as [`Editor::new`] but fails like [`crate::init`] does
+    ///
+    /// # Errors
+    /// Same as [`crate::init`]
+    pub fn try_new(prompt: &str, alt_screen: bool) -> Result<Self, RagoutError> {
+        Self::try_with_backend(prompt, alt_screen, default_backend())
+    }
+
+    /// Same as [`Editor::with_backend`] but fails like [`crate::init`] does
+    ///
+    /// # Errors
+    /// Same as [`crate::init`]
+    pub fn try_with_backend(
+        prompt: &str,
+        alt_screen: bool,
+        mut backend: Box<dyn TermBackend>,
+    ) -> Result<Self, RagoutError> {
+        let init = init_with(prompt, alt_screen, &mut *backend)?;
+        Ok(Self::from_init(init, alt_screen, backend))
+    }
+
+    /// Creates an Editor that never touches the terminal: the Input renders to the
+    /// [`crate::MockTerminal`] of a [`Terminal::headless`] and the keys only come from
+    /// [`Editor::feed`], e.g. for testing an Editor configuration or driving it remotely (see
+    /// [`crate::Remote`])
+    ///
+    /// a nested prompt (e.g. the history finder) that runs out of fed keys fails with an
+    /// [`ErrorKind::WouldBlock`] error
+    pub fn headless(prompt: &str) -> Self {
+        let init = (
+            Terminal::headless(80, 24),
+            Input::new(prompt, false),
+            History::new(),
+            String::new(),
+        );
+        Self::from_init(init, false, default_backend())
+    }
+
+    // the Editor of an initialized terminal
+    pub(crate) fn from_init(
+        (sol, input, history, user_input): Init,
+        alt_screen: bool,
+        backend: Box<dyn TermBackend>,
+    ) -> Self {
+        let mut terminal = TermStack::new();
+        // the keys typed while init queried the terminal come first
+        let queued = match sol.is_headless() {
+            true => Vec::new(),
+            false => {
+                let mut decoder = Decoder::new();
+                let mut events = decoder.decode(&crate::context::take_typed_ahead());
+                events.extend(decoder.flush());
+                events
+            }
+        };
+        let mut modes = terminal.modes();
+        modes.alt_screen = alt_screen;
+        modes.bracketed_paste = !input.plain;
+        // init already switched to the alternate screen and the bracketed paste mode
+        _ = terminal.set_modes(&mut std::io::sink(), modes);
+
+        Self {
+            sol,
+            input,
+            history,
+            user_input,
+            backend,
+            candidates: Vec::new(),
+            completer: None,
+            keymap: Keymap::default(),
+            commands: Vec::new(),
+            clipboard: false,
+            segments: None,
+            terminal,
+            store: None,
+            cancel: None,
+            finder: None,
+            expander: None,
+            middlewares: Vec::new(),
+            hooks: Vec::new(),
+            recorder: None,
+            linter: None,
+            highlighter: None,
+            validator: None,
+            line_endings: LineEndings::default(),
+            after_cr: false,
+            last_click: None,
+            comment: "#".to_string(),
+            strip_continuations: true,
+            no_history: false,
+            private: false,
+            pending: Vec::new(),
+            saved_prompt: None,
+            sentinel: None,
+            termination_watched: false,
+            correction: None,
+            rejected: false,
+            menu: None,
+            quoted: false,
+            find: None,
+            ctrl_x: false,
+            codepoint: None,
+            pinned: None,
+            preload: None,
+            last_arg: None,
+            defer_submit: false,
+            entered: false,
+            // the terminal keys get read by the crate itself, crossterm only when stdin is not
+            // the terminal or on windows
+            decoder: (cfg!(unix) && std::io::stdin().is_terminal()).then(Decoder::new),
+            #[cfg(unix)]
+            resize_watched: false,
+            queued: queued.into(),
+        }
+    }
+
+    /// Opts in to saving the History to path, every recorded line getting appended to it as it
+    /// is submitted (see [`History::autosave`]), and to restoring the terminal when the process
+    /// receives SIGTERM or SIGHUP (e.g. when the terminal window is closed), be it while reading
+    /// a line, running a command or in between
+    ///
+    /// the process then dies of the signal as it would have without the watch, see
+    /// [`TermBackend::watch_termination`]
+    pub fn autosave_on_termination(&mut self, path: impl Into<PathBuf>) -> std::io::Result<()> {
+        self.backend.watch_termination()?;
+        self.history.autosave = Some(path.into());
+        self.termination_watched = true;
+
+        Ok(())
+    }
+
+    // re-renders the prompt once the segments computed in the background are ready
+    fn check_segments(&mut self) {
+        let Some(segments) = &self.segments else {
+            return;
+        };
+        if segments.take_ready() {
+            match self.saved_prompt.as_mut() {
+                // a continued line keeps its continuation prompt
+                Some(prompt) => *prompt = segments.render(),
+                None => self.input.overwrite_prompt(&segments.render()),
+            }
+            _ = self.input.render(&mut self.sol);
+        }
+    }
+
+    // polls the event hooks, a submission gets queued as an Enter press
+    fn check_hooks(&mut self) -> std::io::Result<()> {
+        let hooked = poll_hooks(&mut self.hooks, &mut self.input, &mut self.history);
+        if hooked.is_empty() {
+            return Ok(());
+        }
+        for hooked in hooked {
+            match hooked {
+                Hooked::Print(text) => self.print_above(&text)?,
+                Hooked::Submit => self.queued.push_back(KeyEvent::Key(Key::from(Code::Enter))),
+                Hooked::Redraw | Hooked::Idle => (),
+            }
+        }
+        self.lint();
+
+        self.input.render(&mut self.sol).map_err(Into::into)
+    }
+
+    // hands the terminal back and exits if a termination signal came in, the recorded lines
+    // being autosaved already
+    fn check_termination(&mut self) {
+        let Some(signal) = self.backend.take_termination() else {
+            return;
+        };
+        _ = self.suspend_raw();
+        // SAFETY: signal and raise take any signal number
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+
+        std::process::exit(128 + signal)
+    }
+
+    /// Reads a line of user input, handling the editing keys and rendering as it goes
+    ///
+    /// returns an [`ErrorKind::Interrupted`] error on Ctrl-C,
+    /// an [`ErrorKind::UnexpectedEof`] error on Ctrl-D in an empty line
+    /// and a [`Cancelled`] error when the Editor cancel token gets triggered
+    pub fn read_line(&mut self) -> std::io::Result<String> {
+        if let Some(idx) = self.preload.take().filter(|_| self.history_on()) {
+            if self.history.goto(idx + 1, &mut self.input.values) {
+                self.input.cursor = self.input.values.len();
+            }
+        }
+        if let Some(segments) = &self.segments {
+            segments.refresh();
+            self.input.overwrite_prompt(&segments.render());
+        }
+        self.lint();
+        self.input.render(&mut self.sol)?;
+        loop {
+            let key = self.next_key()?;
+            if let Some(line) = self.feed(key)? {
+                return Ok(line);
+            }
+        }
+    }
+
+    /// Owns the event loop: reads lines and hands each of them to on_line until it breaks or
+    /// Ctrl-D is pressed in an empty line, Ctrl-C only drops the line being edited
+    ///
+    /// the key presses, pastes, resizes and the events of the [`Editor::hooks`] get handled and
+    /// rendered along the way
+    ///
+    /// # Errors
+    /// Returns the errors of [`Editor::read_line`] other than Ctrl-C and Ctrl-D
+    pub fn run(
+        &mut self,
+        mut on_line: impl FnMut(&mut Self, String) -> ControlFlow<()>,
+    ) -> std::io::Result<()> {
+        loop {
+            let line = match self.read_line() {
+                Ok(line) => line,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {
+                    self.drop_line()?;
+                    continue;
+                }
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            if on_line(self, line).is_break() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Same as [`Editor::read_line`] but with the History off for this read, like with
+    /// [`Editor::no_history`], e.g. for a sensitive prompt or a one-off question
+    pub fn read_line_no_history(&mut self) -> std::io::Result<String> {
+        self.private = true;
+        let line = self.read_line();
+        self.private = false;
+
+        line
+    }
+
+    // replaces the diagnostics of the line with those of the linter, its highlights and its
+    // autosuggestion
+    fn lint(&mut self) {
+        if let Some(linter) = &mut self.linter {
+            self.input.diagnostics = linter.lint(&self.input.values);
+        }
+        if let Some(highlighter) = &self.highlighter {
+            self.input.highlight(highlighter.as_ref());
+        }
+        match self.history_on() {
+            true => _ = self.input.suggest(&self.history),
+            false => self.input.suggestion = None,
+        }
+    }
+
+    // whether the submitted lines get recorded and the history can be navigated
+    fn history_on(&self) -> bool {
+        !self.no_history && !self.private
+    }
+
+    // waits for the next key press, handling the resizes, termination signals and prompt
+    // segments that come in meanwhile
+    pub(crate) fn next_key(&mut self) -> std::io::Result<Key> {
+        loop {
+            match self.queued.pop_front() {
+                Some(KeyEvent::Key(key)) => return Ok(key),
+                Some(KeyEvent::Paste(text)) => self.paste(&text)?,
+                Some(KeyEvent::Mouse(mouse)) => {
+                    self.handle_mouse(mouse);
+                    self.input.render(&mut self.sol)?;
+                }
+                Some(KeyEvent::Unknown(_)) => (),
+                None => (),
+            }
+            if !self.queued.is_empty() {
+                continue;
+            }
+            if self.sol.is_headless() {
+                return Err(ErrorKind::WouldBlock.into());
+            }
+            let watching = self.termination_watched
+                || self.segments.is_some()
+                || self.cancel.is_some()
+                || !self.hooks.is_empty();
+            if watching {
+                self.check_termination();
+                self.check_segments();
+                if self.cancel.as_ref().is_some_and(CancelToken::take) {
+                    return Err(Cancelled.into());
+                }
+                self.check_hooks()?;
+                if !self.queued.is_empty() {
+                    continue;
+                }
+            }
+            // crossterm only reads UTF-8, the other encodings get decoded by the crate
+            #[cfg(all(unix, feature = "encoding"))]
+            if self.decoder.is_none()
+                && crate::encoding::current() != crate::encoding::Encoding::Utf8
+            {
+                self.decoder = Some(Decoder::new());
+            }
+            #[cfg(unix)]
+            if let Some(decoder) = self.decoder.as_mut() {
+                let timeout = if watching { 50 } else { -1 };
+                if !self.resize_watched {
+                    self.resize_watched = self.backend.watch_resize().is_ok();
+                }
+                let events = read_events(decoder, timeout)?;
+                // a SIGWINCH interrupts the read, the line gets re-wrapped before the keys
+                // read along with it are handled
+                if self.backend.take_resized() {
+                    let (_, rows) = self.backend.size()?;
+                    self.resized(rows)?;
+                }
+                self.queued.extend(events);
+                continue;
+            }
+            // neither termination signals, segments nor cancellations interrupt crossterm reads,
+            // look for them regularly
+            if watching && !poll(Duration::from_millis(50))? {
+                continue;
+            }
+            match read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    return Ok(from_crossterm(key))
+                }
+                Event::Resize(_, rows) => self.resized(rows)?,
+                Event::Paste(text) => self.paste(&text)?,
+                Event::Mouse(mouse) => {
+                    if let Some(mouse) = mouse_from_crossterm(mouse) {
+                        self.handle_mouse(mouse);
+                        self.input.render(&mut self.sol)?;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // inserts a bracketed paste in a single edit, its newlines never submit the line
+    pub(crate) fn paste(&mut self, text: &str) -> std::io::Result<()> {
+        self.record(KeyEvent::Paste(text.to_string()));
+        if self.menu.is_some() {
+            self.close_menu();
+        }
+        if !self
+            .input
+            .apply(&InputOp::Paste(text.to_string()), &mut self.history)
+        {
+            self.input.ring(&mut self.sol);
+        }
+        self.lint();
+
+        self.input.render(&mut self.sol).map_err(Into::into)
+    }
+
+    /// Turns the mouse reporting on or off, the input line can then be clicked to move the
+    /// cursor, dragged over to select text and double clicked to select a word
+    pub fn enable_mouse(&mut self, on: bool) -> std::io::Result<()> {
+        let mut modes = self.terminal.modes();
+        modes.mouse = on;
+
+        self.terminal.set_modes(&mut self.sol, modes)
+    }
+
+    // the clicks are assumed to be on the input line, only their column matters
+    fn handle_mouse(&mut self, mouse: Mouse) {
+        let i = &mut self.input;
+        let idx = i.index_at(mouse.column as usize);
+        match (mouse.kind, mouse.button) {
+            (MouseKind::Down, 0) => {
+                let double = self.last_click.is_some_and(|(at, last)| {
+                    last == idx && at.elapsed() < Duration::from_millis(400)
+                });
+                match double && i.select_word(idx) {
+                    true => self.last_click = None,
+                    false => {
+                        i.cursor = idx;
+                        // a drag selects from where it started
+                        i.mark = Some(idx);
+                        self.last_click = Some((Instant::now(), idx));
+                    }
+                }
+            }
+            (MouseKind::Drag, 0) if i.mark.is_some() => i.cursor = idx,
+            (MouseKind::Up, 0) if i.selection().is_none() => i.mark = None,
+            _ => (),
+        }
+    }
+
+    /// Opens a secondary prompt (e.g. a confirmation or a query) over the line being edited,
+    /// with its own minimal keymap: the editing keys, Enter to answer, Esc or Ctrl-C to cancel
+    ///
+    /// the line being edited is left untouched and repainted as it was once the secondary
+    /// prompt closes, its answer never gets recorded in the History
+    ///
+    /// returns None if the prompt was cancelled
+    pub fn nested_prompt(&mut self, prompt: &str) -> std::io::Result<Option<String>> {
+        let overlay = self.overlay(prompt);
+        let primary = std::mem::replace(&mut self.input, overlay);
+
+        let answer = self.read_overlay();
+        self.input = primary;
+        self.input.render(&mut self.sol)?;
+
+        answer
+    }
+
+    // an empty Input to read a secondary prompt with
+    pub(crate) fn overlay(&self, prompt: &str) -> Input {
+        // a clone keeps sharing the debug log instead of truncating it
+        let mut overlay = self.input.clone();
+        overlay.values.clear();
+        overlay.cursor = 0;
+        overlay.changes = 0;
+        overlay.overwrite_prompt(prompt);
+        overlay.set_mode(Mode::Insert);
+        overlay.sensitive = false;
+        overlay.unmask();
+        overlay.message = None;
+        overlay.ghost = None;
+        overlay.suggestion = None;
+        overlay.diagnostics.clear();
+        overlay.highlights.clear();
+        overlay.multiline = false;
+
+        overlay
+    }
+
+    // an empty History for the secondary prompts, which never record anything
+    pub(crate) fn scratch_history(&self) -> History {
+        // a clone keeps sharing the debug log instead of truncating it
+        let mut scratch = self.history.clone();
+        scratch.values.clear();
+        scratch.temp = None;
+        scratch.cursor = 0;
+
+        scratch
+    }
+
+    /// Asks a yes or no question through a [`Editor::nested_prompt`],
+    /// anything but an answer starting with y or Y (or a cancelled prompt) is a no
+    pub fn confirm(&mut self, question: &str) -> std::io::Result<bool> {
+        Ok(self
+            .nested_prompt(&format!("{} [y/N] ", question))?
+            .is_some_and(|answer| answer.trim_start().starts_with(['y', 'Y'])))
+    }
+
+    // the event loop of nested_prompt
+    fn read_overlay(&mut self) -> std::io::Result<Option<String>> {
+        let mut scratch = self.scratch_history();
+        loop {
+            self.input.render(&mut self.sol)?;
+            let key = self.next_key()?;
+            let i = &mut self.input;
+            match (key.code, key.mods.ctrl) {
+                (Code::Enter, _) => return Ok(Some(i.values.iter().collect())),
+                (Code::Esc, _) | (Code::Char('c'), true) => return Ok(None),
+                (Code::Up | Code::Down, _) => i.ring(&mut self.sol),
+                _ => {
+                    if let Some(op) = self.keymap.op(key) {
+                        if !i.apply(&op, &mut scratch) {
+                            i.ring(&mut self.sol);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // a recording that fails to be written is given up on rather than failing the editing; the
+    // events of the sensitive lines, the masked ones included, are left out
+    fn record(&mut self, event: KeyEvent) {
+        if self.input.sensitive {
+            return;
+        }
+        if let Some(recorder) = self.recorder.as_mut() {
+            if recorder.record(&event).is_err() {
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// Applies a key press to the Input and History then re-renders the Input,
+    /// lets applications running their own event loop drive the editor
+    ///
+    /// returns the submitted line once Enter completes it
+    /// # Errors
+    /// Same as [`Editor::read_line`]
+    pub fn feed(&mut self, key: Key) -> std::io::Result<Option<String>> {
+        self.record(KeyEvent::Key(key));
+        let Some(key) = intercept(&mut self.middlewares, key, &self.input) else {
+            return Ok(None);
+        };
+        let (cursor, generation) = (self.input.cursor, self.input.undo.generation);
+        let outer = self.input.values.track();
+        let line = self.handle_key(key);
+        let edit = self.input.values.untrack(outer, cursor, self.input.cursor);
+        let line = line?;
+        // catches the edits made without going through Input::apply, e.g. the completions
+        if let Some(edit) =
+            edit.filter(|_| line.is_none() && self.input.undo.generation == generation)
+        {
+            self.input.undo.push(edit);
+        }
+        if line.is_none() {
+            self.lint();
+            self.input.render(&mut self.sol)?;
+        }
+
+        Ok(line)
+    }
+
+    /// Reads lines of user input until parse accepts one, then returns what parse made of it
+    ///
+    /// a rejected line stays in place and editable, with the parse error rendered in red under it,
+    /// only the accepted line gets recorded in the History
+    ///
+    /// # Errors
+    /// Same as [`Editor::read_line`]
+    pub fn prompt_parse<T, E: std::fmt::Display>(
+        &mut self,
+        mut parse: impl FnMut(&str) -> Result<T, E>,
+    ) -> std::io::Result<T> {
+        self.defer_submit = true;
+        let parsed = self.parse_loop(&mut parse);
+        self.defer_submit = false;
+        self.entered = false;
+        self.input.message = None;
+
+        parsed
+    }
+
+    // the event loop of prompt_parse
+    fn parse_loop<T, E: std::fmt::Display>(
+        &mut self,
+        parse: &mut impl FnMut(&str) -> Result<T, E>,
+    ) -> std::io::Result<T> {
+        self.input.render(&mut self.sol)?;
+        loop {
+            let key = self.next_key()?;
+            self.feed(key)?;
+            if !std::mem::take(&mut self.entered) {
+                continue;
+            }
+            let mut line = self
+                .pending
+                .iter()
+                .chain(&self.input.values)
+                .collect::<String>();
+            if self.strip_continuations {
+                line = line.replace("\\\n", "");
+            }
+            match parse(&line) {
+                Ok(parsed) => {
+                    self.input.message = None;
+                    self.line_feed()?;
+                    self.submit();
+                    return Ok(parsed);
+                }
+                Err(e) => {
+                    let red = Style::new().fg(Color::Red).sgr();
+                    self.input.message = Some(format!("{}{}\x1b[0m", red, e));
+                    self.input.ring(&mut self.sol);
+                    self.input.render(&mut self.sol)?;
+                }
+            }
+        }
+    }
+
+    /// Reads lines of user input until the sentinel line (e.g. `EOF`) is entered alone,
+    /// then returns all the lines before it as a single block
+    ///
+    /// # Errors
+    /// Same as [`Editor::read_line`], the lines read so far are then discarded
+    pub fn read_block(&mut self, sentinel: &str) -> std::io::Result<String> {
+        self.sentinel = Some(sentinel.to_string());
+        let block = self.read_line();
+        self.sentinel = None;
+        if block.is_err() {
+            self.pending.clear();
+            if let Some(prompt) = self.saved_prompt.take() {
+                self.input.overwrite_prompt(&prompt);
+            }
+        }
+
+        block
+    }
+
+    /// Offers a correction of a submitted line that failed the application validation, by
+    /// rendering a "did you mean ...?" message on the status line, see [`crate::suggest::did_you_mean`]
+    ///
+    /// Alt-Enter replaces the next line being edited with the correction
+    ///
+    /// the history entries are left out with the History off, see [`Editor::no_history`]
+    ///
+    /// returns false if no correction was found
+    pub fn suggest_correction(&mut self, line: &str) -> bool {
+        let empty = History::new();
+        let history = match self.history_on() {
+            true => &self.history,
+            false => &empty,
+        };
+        self.correction = did_you_mean(line, &self.commands, history);
+        self.input.message = self
+            .correction
+            .as_ref()
+            .map(|c| format!("did you mean `{}`? (Alt-Enter to accept)", c));
+
+        self.correction.is_some()
+    }
+
+    // applies a key event to the Input and History, returns the line once it is submitted
+    fn handle_key(&mut self, key: Key) -> std::io::Result<Option<String>> {
+        let Some(key) = self.line_endings.key(key, &mut self.after_cr) else {
+            return Ok(None);
+        };
+        if self.quoted {
+            self.quoted = false;
+            match literal(key) {
+                Some(c) => self.input.put_char(c),
+                None => self.input.ring(&mut self.sol),
+            }
+            return Ok(None);
+        }
+        if let Some(forward) = self.find.take() {
+            let found = match key.code {
+                Code::Char(c) => self.input.apply(
+                    &InputOp::FindChar(CharSearch {
+                        c,
+                        forward,
+                        till: false,
+                    }),
+                    &mut self.history,
+                ),
+                _ => false,
+            };
+            if !found {
+                self.input.ring(&mut self.sol);
+            }
+            return Ok(None);
+        }
+        if self.codepoint.is_some() {
+            self.codepoint_key(key);
+            return Ok(None);
+        }
+        if std::mem::take(&mut self.ctrl_x) {
+            match key.code {
+                // expands a \u{HEX} escape typed before the cursor, or else starts reading
+                // the hex digits of the codepoint
+                Code::Char('u') if !self.input.expand_codepoint() => {
+                    self.codepoint = Some(String::new());
+                    self.input.message = Some("U+".to_string());
+                }
+                Code::Char('u') => (),
+                Code::Char('e') if self.expand() => (),
+                Code::Char('b') => self.copy(Input::copy_word_before)?,
+                Code::Char('f') => self.copy(Input::copy_word_after)?,
+                Code::Char('h') if self.history_on() => _ = self.browse_history()?,
+                _ => self.input.ring(&mut self.sol),
+            }
+            return Ok(None);
+        }
+        if self.menu.is_some() && self.menu_key(key) {
+            return Ok(None);
+        }
+        let copying = key.code == Code::Char('w') && key.mods.alt;
+        if !copying {
+            // the selection is gone once the line or the cursor moves
+            self.input.mark = None;
+        }
+        let yanking = key.code == Code::Char('.') && key.mods.alt;
+        if !yanking {
+            self.last_arg = None;
+        }
+        let history_on = self.history_on();
+        // the keys bound in the keymap override the editor commands
+        let bound = self.keymap.get(&key).is_some();
+        let op = self.keymap.op(key);
+        let apply = |op: Option<InputOp>, i: &mut Input, history: &mut History| match op {
+            Some(
+                InputOp::HistoryPrev
+                | InputOp::HistoryNext
+                | InputOp::HistoryPrevMatching
+                | InputOp::HistoryNextMatching,
+            ) if !history_on => false,
+            Some(op) => i.apply(&op, history),
+            // the keys bound to nothing ring the bell
+            None => false,
+        };
+        let i = &mut self.input;
+        let success = match (key.code, key.mods.ctrl) {
+            (Code::Char('c'), true) => return Err(ErrorKind::Interrupted.into()),
+            (Code::Char('d'), true) if i.values.is_empty() => {
+                return Err(ErrorKind::UnexpectedEof.into())
+            }
+            _ if bound => apply(op, i, &mut self.history),
+            (Code::Esc, _) if i.vi && i.mode != Mode::Normal => {
+                i.vi_normal();
+                true
+            }
+            (Code::Char('j' | 'k'), false) if i.mode == Mode::Normal && !history_on => false,
+            (Code::Char(c), false) if i.mode == Mode::Normal && !key.mods.alt => {
+                i.vi_command(c, &mut self.history)
+            }
+            (Code::Enter, false) if key.mods.alt && self.correction.is_some() => {
+                i.set_text(&self.correction.take().unwrap());
+                i.message = None;
+                true
+            }
+            (Code::Enter, _) if i.multiline && (key.mods.alt || i.continues()) => {
+                i.put_char('\n');
+                true
+            }
+            (Code::Enter, _) if self.defer_submit && self.sentinel.is_none() && !i.continues() => {
+                self.entered = true;
+                return Ok(None);
+            }
+            (Code::Enter, _) => {
+                if self.sentinel.is_none() && !i.continues() && !self.validated()? {
+                    return Ok(None);
+                }
+                self.line_feed()?;
+                let i = &mut self.input;
+                if let Some(sentinel) = &self.sentinel {
+                    if i.values.iter().copied().ne(sentinel.chars()) {
+                        self.continue_line();
+                        return Ok(None);
+                    }
+                    // the sentinel line and the line feed before it are not part of the block
+                    i.clear_line();
+                    self.pending.pop();
+                } else if i.continues() {
+                    self.continue_line();
+                    return Ok(None);
+                }
+
+                return Ok(Some(self.submit()));
+            }
+            // readline's character-search and character-search-backward
+            (Code::Char(']'), true) => {
+                self.find = Some(!key.mods.alt);
+                true
+            }
+            (Code::Char('x'), true) => {
+                self.ctrl_x = true;
+                true
+            }
+            (Code::Char('v'), true) => {
+                self.quoted = true;
+                true
+            }
+            // the history entries are not to be found with the History off
+            (Code::Char('t'), true)
+                if !history_on && matches!(self.finder, Some(FinderSource::History)) =>
+            {
+                false
+            }
+            (Code::Char('t'), true) => match self.finder.take() {
+                Some(source) => {
+                    let items = source.items(&self.history);
+                    self.finder = Some(source);
+                    if let Some(item) = self.fuzzy_find(&items)? {
+                        self.input
+                            .apply(&InputOp::InsertStr(item), &mut self.history);
+                    }
+                    true
+                }
+                None => false,
+            },
+            // operate-and-get-next: submits the line, the next read_line then starts from the
+            // history entry after the recalled one
+            (Code::Char('o'), true) if history_on => {
+                let h = &self.history;
+                let line = self
+                    .pending
+                    .iter()
+                    .chain(&i.values)
+                    .copied()
+                    .collect::<Vec<char>>();
+                let pushed = h.takes(&line);
+                // the entries after the recalled one stay the newest, but for the ones the
+                // submitted line erases
+                let after = h.values.get(h.cursor + 1..).unwrap_or_default();
+                let kept = match pushed && h.policy.dups == Dups::EraseOlder {
+                    true => after.iter().filter(|e| **e != line).count(),
+                    false => after.len(),
+                };
+                self.line_feed()?;
+                let line = self.submit();
+                let len = self.history.len() - usize::from(pushed);
+                if kept > 0 && kept <= len {
+                    self.preload = Some(len - kept);
+                }
+                return Ok(Some(line));
+            }
+            (Code::Char('.'), false) if yanking => history_on && self.yank_last_arg(),
+            (Code::Char('w'), false) if copying => {
+                match self.input.selection() {
+                    Some(_) => self.copy(Input::copy_selection)?,
+                    None => self.copy(Input::copy_line)?,
+                }
+                true
+            }
+            (Code::Char('#'), false) if key.mods.alt => {
+                self.comment_and_store()?;
+                return Ok(None);
+            }
+            (Code::Insert, _) if i.mode != Mode::Normal => {
+                i.set_mode(match i.mode {
+                    Mode::Overwrite => Mode::Insert,
+                    _ => Mode::Overwrite,
+                });
+                true
+            }
+            (Code::Tab, _) => self.complete(),
+            _ => apply(op, i, &mut self.history),
+        };
+        if !success {
+            self.input.ring(&mut self.sol);
+        }
+
+        Ok(None)
+    }
+
+    /// Replaces the selection, or else the word under the cursor, with what the Editor expander
+    /// makes of it, see [`Input::expand_with`]
+    ///
+    /// returns false if there is no expander or nothing was expanded
+    pub fn expand(&mut self) -> bool {
+        self.expander
+            .as_mut()
+            .is_some_and(|expander| self.input.expand_with(&mut **expander))
+    }
+
+    // reads the hex digits of a codepoint until Enter or space inserts its char, Esc cancels
+    fn codepoint_key(&mut self, key: Key) {
+        let digits = self.codepoint.as_mut().unwrap();
+        match key.code {
+            Code::Char(c) if c.is_ascii_hexdigit() && digits.len() < 6 => {
+                digits.push(c.to_ascii_uppercase());
+                self.input.message = Some(format!("U+{}", digits));
+                return;
+            }
+            Code::Backspace => {
+                digits.pop();
+                self.input.message = Some(format!("U+{}", digits));
+                return;
+            }
+            Code::Enter | Code::Char(' ') => {
+                match u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                {
+                    Some(c) => self.input.put_char(c),
+                    None => self.input.ring(&mut self.sol),
+                }
+            }
+            Code::Esc => (),
+            _ => self.input.ring(&mut self.sol),
+        }
+        self.codepoint = None;
+        self.input.message = None;
+    }
+
+    // cycles through, accepts or closes the completion menu, other keys close the menu
+    // before getting handled as usual
+    //
+    // returns true if the key was used up by the menu
+    fn menu_key(&mut self, key: Key) -> bool {
+        let menu = self.menu.as_mut().unwrap();
+        let len = menu.matches.len();
+        match key.code {
+            Code::Tab => menu.selected = (menu.selected + 1) % len,
+            Code::BackTab => menu.selected = (menu.selected + len - 1) % len,
+            Code::Enter => {
+                self.input.put_str(menu.preview().chars());
+                self.close_menu();
+                return true;
+            }
+            Code::Esc => {
+                self.close_menu();
+                return true;
+            }
+            _ => {
+                self.close_menu();
+                return false;
+            }
+        }
+        self.input.ghost = Some(menu.preview());
+        self.list_menu();
+
+        true
+    }
+
+    fn close_menu(&mut self) {
+        if let Some(menu) = self.menu.take() {
+            self.input.message = menu.message;
+        }
+        self.input.ghost = None;
+    }
+
+    // lists the candidates of the menu on the status line
+    fn list_menu(&mut self) {
+        let width = self.backend.size().map_or(80, |(w, _)| w as usize);
+        if let Some(menu) = &self.menu {
+            self.input.message = Some(menu.listing(width));
+        }
+    }
+
+    // puts the text picked from the line on the kill ring (and the clipboard), leaving the line
+    // untouched
+    fn copy(&mut self, pick: fn(&Input) -> Option<String>) -> std::io::Result<()> {
+        let Some(text) = pick(&self.input) else {
+            self.input.ring(&mut self.sol);
+            return Ok(());
+        };
+        if self.clipboard {
+            self.sol.write_all(osc52(&text).as_bytes())?;
+        }
+        self.input.kill_ring.push(text);
+
+        Ok(())
+    }
+
+    // inserts the last word of the previous history entry at the cursor, repeated presses
+    // replace it with the last word of the entries before
+    fn yank_last_arg(&mut self) -> bool {
+        let n = self.last_arg.as_ref().map_or(0, |(n, _)| n + 1);
+        // the oldest entry was reached, leave its word in place
+        let Some(arg) = self.history.last_arg(n) else {
+            return false;
+        };
+        let start = match self.last_arg.take() {
+            Some((_, span)) => {
+                self.input.changes += span.len();
+                self.input.values.drain(span.clone());
+                self.input.cursor = span.start;
+                span.start
+            }
+            None => self.input.cursor,
+        };
+        self.input.put_str(arg.iter().copied());
+        self.last_arg = Some((n, start..self.input.cursor));
+
+        true
+    }
+
+    // leaves the line interrupted by Ctrl-C on screen and starts a fresh one below it, the
+    // pending lines dropped along with it
+    pub(crate) fn drop_line(&mut self) -> std::io::Result<()> {
+        self.line_feed()?;
+        self.input.clear_line();
+        self.pending.clear();
+        if let Some(prompt) = self.saved_prompt.take() {
+            self.input.overwrite_prompt(&prompt);
+        }
+
+        Ok(())
+    }
+
+    // comments the line out and stores it in the history without handing it to the application,
+    // then starts a fresh line
+    fn comment_and_store(&mut self) -> std::io::Result<()> {
+        match self.pending.is_empty() {
+            true => self.input.values.splice(0..0, self.comment.chars()),
+            false => self
+                .pending
+                .splice(0..0, self.comment.chars())
+                .for_each(drop),
+        }
+        self.input.cursor = self.input.values.len();
+        self.line_feed()?;
+        self.submit();
+        self.user_input.clear();
+
+        Ok(())
+    }
+
+    // moves the current line to the pending lines and shows the continuation prompt
+    fn continue_line(&mut self) {
+        let i = &mut self.input;
+        self.pending.extend(i.values.drain(..));
+        self.pending.push('\n');
+        i.cursor = 0;
+        i.undo.clear();
+        if self.saved_prompt.is_none() {
+            self.saved_prompt = Some(i.prompt.clone());
+        }
+        let continuation = i.continuation_prompt.clone();
+        i.overwrite_prompt(&continuation);
+    }
+
+    // runs the validator on the line Enter was pressed on, returns false if the line is not to
+    // be submitted: then continued when incomplete, or kept with its error when invalid
+    fn validated(&mut self) -> std::io::Result<bool> {
+        let Some(validator) = self.validator.as_mut() else {
+            return Ok(true);
+        };
+        let line = self
+            .pending
+            .iter()
+            .chain(&self.input.values)
+            .collect::<String>();
+        if std::mem::take(&mut self.rejected) {
+            self.input.message = None;
+        }
+        match validator.validate(&line) {
+            Validation::Valid => return Ok(true),
+            Validation::Incomplete if self.input.multiline => self.input.put_char('\n'),
+            Validation::Incomplete => {
+                self.line_feed()?;
+                self.continue_line();
+            }
+            Validation::Invalid(e) => {
+                let red = Style::new().fg(Color::Red).sgr();
+                self.input.message = Some(format!("{}{}\x1b[0m", red, e));
+                self.rejected = true;
+                self.input.ring(&mut self.sol);
+            }
+        }
+
+        Ok(false)
+    }
+
+    // joins the pending lines with the current one then submits the whole thing
+    fn submit(&mut self) -> String {
+        let history_on = self.history_on();
+        let i = &mut self.input;
+        if !self.pending.is_empty() {
+            self.pending.extend(i.values.drain(..));
+            i.values.set(std::mem::take(&mut self.pending));
+        }
+        if let Some(prompt) = self.saved_prompt.take() {
+            i.overwrite_prompt(&prompt);
+        }
+        if history_on {
+            let recorded = self.history.len();
+            i.cr_lf(&mut self.history, &mut self.user_input);
+            if let Some(store) = self.store.as_mut() {
+                // only the lines the History recorded get stored, a failing store doesn't lose
+                // the line
+                if self.history.len() > recorded {
+                    _ = store.append(&self.history.values[recorded]);
+                }
+            }
+        } else {
+            self.user_input = i.values.drain(..).collect();
+            i.cursor = 0;
+            i.changes = 0;
+            i.undo.clear();
+        }
+        if self.correction.take().is_some() {
+            i.message = None;
+        }
+        if self.strip_continuations && self.sentinel.is_none() {
+            self.user_input = self.user_input.replace("\\\n", "");
+        }
+
+        self.user_input.clone()
+    }
+
+    // completes the word behind the cursor with the longest prefix shared by the completions
+    // of the completer, or by the candidates starting with it
+    fn complete(&mut self) -> bool {
+        let line = self.input.values.iter().collect::<String>();
+        let (start, completions) = match &self.completer {
+            Some(completer) => {
+                let completions = completer.complete(&line, self.input.cursor);
+                let start = completions.first().map_or(0, |c| c.start);
+                (start, completions.into_iter().map(|c| c.text).collect())
+            }
+            None => {
+                let (start, word) = word_before(&line, self.input.cursor);
+                let candidates = self
+                    .candidates
+                    .iter()
+                    .filter(|c| c.starts_with(&word))
+                    .cloned()
+                    .collect::<Vec<String>>();
+                (start, candidates)
+            }
+        };
+        let i = &mut self.input;
+        let start = start.min(i.cursor);
+        let word = i.values[start..i.cursor].iter().collect::<String>();
+        // a lone completion may rewrite the word instead of extending it, e.g. expand a `~`
+        if let [only] = completions.as_slice() {
+            if !only.starts_with(&word) {
+                i.values.drain(start..i.cursor);
+                i.cursor = start;
+                i.put_str(only.chars());
+                return true;
+            }
+        }
+
+        let mut matches = completions.iter().filter(|c| c.starts_with(&word));
+        let Some(first) = matches.next() else {
+            return false;
+        };
+        let common = matches.fold(first.as_str(), |common, c| {
+            let len = common
+                .char_indices()
+                .zip(c.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((idx, a), _)| idx + a.len_utf8());
+
+            &common[..len]
+        });
+        if common.len() == word.len() {
+            let matches = completions
+                .iter()
+                .filter(|c| c.starts_with(&word) && c.len() > word.len())
+                .cloned()
+                .collect::<Vec<String>>();
+            if matches.len() < 2 {
+                return false;
+            }
+            // the candidates are too different to be completed any further, let the user pick one
+            let menu = Menu {
+                word_len: word.chars().count(),
+                matches,
+                selected: 0,
+                message: i.message.take(),
+            };
+            i.ghost = Some(menu.preview());
+            self.menu = Some(menu);
+            self.list_menu();
+            return true;
+        }
+        i.put_str(common[word.len()..].chars());
+
+        true
+    }
+
+    /// Pins the prompt to the bottom rows of the screen: the rows above become a scroll region
+    /// (DECSTBM) that [`Editor::print_above`] output and submitted lines scroll through, while
+    /// the prompt stays in place
+    ///
+    /// the scroll region follows the terminal resizes seen by [`Editor::read_line`]
+    pub fn pin_bottom(&mut self, rows: u16) -> std::io::Result<()> {
+        self.pinned = Some(rows.max(1));
+        let (_, height) = self.backend.size()?;
+
+        self.repin(height)
+    }
+
+    /// Gives the whole screen back to the scroll region, see [`Editor::pin_bottom`]
+    pub fn unpin(&mut self) -> std::io::Result<()> {
+        if self.pinned.take().is_some() {
+            self.sol.write_all(b"\x1b[r")?;
+            self.sol.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes application output on the last row of the area above the prompt,
+    /// scrolling that area up, then puts the cursor back on the prompt
+    ///
+    /// outside of the pinned mode, the output is written on its own lines above the prompt
+    pub fn print_above(&mut self, text: &str) -> std::io::Result<()> {
+        let text = self.line_endings.translate(text);
+        match self.pinned {
+            Some(rows) => {
+                let (_, height) = self.backend.size()?;
+                let bottom = height.saturating_sub(rows).max(1);
+                self.sol
+                    .write_all(format!("\x1b7\x1b[{};1H\r\n{}\x1b8", bottom, text).as_bytes())?;
+            }
+            None => {
+                self.input.to_first_row(&mut self.sol)?;
+                self.sol.write_all(b"\r\x1b[J")?;
+                self.sol.write_all(text.as_bytes())?;
+                self.sol.write_all(self.line_endings.output.as_bytes())?;
+                self.input.render(&mut self.sol)?;
+            }
+        }
+
+        self.sol.flush()
+    }
+
+    // re-renders the line re-wrapped to the new width of the terminal, along with the pinned
+    // prompt rows
+    fn resized(&mut self, height: u16) -> std::io::Result<()> {
+        match self.pinned {
+            Some(_) => self.repin(height),
+            None => self.input.render(&mut self.sol).map_err(Into::into),
+        }
+    }
+
+    // resets the scroll region and the prompt rows for a screen of the given height
+    fn repin(&mut self, height: u16) -> std::io::Result<()> {
+        let Some(rows) = self.pinned else {
+            return Ok(());
+        };
+        self.sol.write_all(pin_escapes(height, rows).as_bytes())?;
+        self.input.render(&mut self.sol)?;
+
+        Ok(())
+    }
+
+    // ends the current prompt line, in pinned mode the line gets echoed in the scroll region
+    // instead since the prompt doesn't move
+    fn line_feed(&mut self) -> std::io::Result<()> {
+        if self.input.plain {
+            // the typed line never got rendered, it goes to the output once done unless secret
+            if !self.input.is_secret() {
+                let line = self.input.values.iter().collect::<String>();
+                self.sol.write_all(line.as_bytes())?;
+            }
+            self.sol.write_all(b"\n")?;
+            self.input.prompted.set(false);
+            return self.sol.flush();
+        }
+        // the submitted line goes without its suggestion
+        if self.input.suggestion.take().is_some() {
+            self.input.write_prompt(&mut self.sol)?;
+        }
+        match self.pinned {
+            Some(_) => {
+                let line = format!(
+                    "{}{}",
+                    self.input.prompt,
+                    self.input.values.iter().collect::<String>()
+                );
+                self.print_above(&line)
+            }
+            None => {
+                self.input.to_last_row(&mut self.sol)?;
+                self.sol.write_all(self.line_endings.output.as_bytes())?;
+                self.sol.flush()
+            }
+        }
+    }
+
+    /// Saves the whole terminal configuration (termios flags, alternate screen, mouse and
+    /// bracketed paste modes, cursor shape), see [`TermStack::push_state`]
+    pub fn push_state(&mut self) -> std::io::Result<()> {
+        self.terminal.push_state()
+    }
+
+    /// Restores the terminal configuration saved by the last [`Editor::push_state`] call then
+    /// repaints the prompt
+    ///
+    /// returns false if there was no saved configuration
+    pub fn pop_state(&mut self) -> std::io::Result<bool> {
+        let popped = self.terminal.pop_state(&mut self.sol)?;
+        self.input.alt_screen = self.terminal.modes().alt_screen;
+        self.sol.alt_screen = self.input.alt_screen;
+        self.input.render(&mut self.sol)?;
+
+        Ok(popped)
+    }
+
+    /// Hands the terminal back in cooked mode (leaving the alternate screen if it is used),
+    /// so that interactive child processes can be run
+    pub fn suspend_raw(&mut self) -> std::io::Result<()> {
+        if self.input.cursor_shapes {
+            self.sol
+                .write_all(CursorShape::Default.escape().as_bytes())?;
+        }
+        if self.pinned.is_some() {
+            self.sol.write_all(b"\x1b[r")?;
+        }
+        match self.input.alt_screen {
+            true => set_alt_screen(&mut self.sol, false)?,
+            // don't let the child output overwrite the prompt line
+            false => self.sol.write_all(self.line_endings.output.as_bytes())?,
+        }
+        self.sol.flush()?;
+
+        self.backend.disable_raw_mode()
+    }
+
+    /// Takes the terminal back in raw mode after a [`Editor::suspend_raw`] call,
+    /// then repaints the prompt and the pending input
+    pub fn resume_raw(&mut self) -> std::io::Result<()> {
+        self.backend.enable_raw_mode()?;
+        if self.input.alt_screen {
+            set_alt_screen(&mut self.sol, true)?;
+            self.sol.write_all(b"\x1b[1;1f")?;
+        }
+        match self.pinned {
+            Some(_) => {
+                let (_, height) = self.backend.size()?;
+                self.repin(height)?;
+            }
+            None => self.input.render(&mut self.sol)?,
+        }
+
+        Ok(())
+    }
+
+    /// Runs cmd with the terminal handed over to it, waits for it to exit then takes the terminal
+    /// back and repaints the prompt and the pending input
+    ///
+    /// returns the exit status of the child process
+    pub fn run_external(&mut self, cmd: &mut Command) -> std::io::Result<ExitStatus> {
+        self.suspend_raw()?;
+        let status = cmd.status();
+        // the terminal is taken back even if the child could not be spawned
+        self.resume_raw()?;
+
+        status
+    }
+}
+
+// the char a key stands for when inserted literally by quoted insert
+fn literal(key: Key) -> Option<char> {
+    Some(match key.code {
+        Code::Char(c) if key.mods.ctrl && c.is_ascii() => {
+            (c.to_ascii_uppercase() as u8 ^ 0x40) as char
+        }
+        Code::Char(c) => c,
+        Code::Enter => '\r',
+        Code::Tab => '\t',
+        Code::Esc => '\x1b',
+        Code::Backspace => '\x7f',
+        _ => return None,
+    })
+}
+
+// the escapes restricting the scroll region to the rows above the pinned ones, clearing the
+// pinned rows and moving the cursor to the first of them
+fn pin_escapes(height: u16, rows: u16) -> String {
+    let top = height.saturating_sub(rows).max(1);
+    let mut escapes = format!("\x1b[1;{}r", top);
+    (top + 1..=height).for_each(|row| escapes.push_str(&format!("\x1b[{};1H\x1b[2K", row)));
+    escapes.push_str(&format!("\x1b[{};1H", (top + 1).min(height)));
+
+    escapes
+}
+
+#[cfg(test)]
+mod test_editor {
+    use super::{literal, pin_escapes, Editor, Menu};
+    use crate::events::KeyEvent;
+    use crate::finder::FinderSource;
+    use crate::history::Dups;
+    use crate::key::{Code, Key};
+    use std::ops::ControlFlow;
+
+    #[test]
+    fn test_pin_escapes() {
+        assert_eq!(
+            pin_escapes(24, 2),
+            "\x1b[1;22r\x1b[23;1H\x1b[2K\x1b[24;1H\x1b[2K\x1b[23;1H"
+        );
+        // a screen too small to pin anything keeps a one row scroll region
+        assert_eq!(pin_escapes(1, 3), "\x1b[1;1r\x1b[1;1H");
+    }
+
+    #[test]
+    fn test_literal() {
+        assert_eq!(literal(Key::ctrl('v')), Some('\x16'));
+        assert_eq!(literal(Key::from(Code::Esc)), Some('\x1b'));
+        assert_eq!(literal(Key::char('x')), Some('x'));
+        assert_eq!(literal(Key::from(Code::Up)), None);
+    }
+
+    #[test]
+    fn test_menu_listing() {
+        let menu = Menu {
+            word_len: 2,
+            matches: ["help", "hello", "helm"].map(String::from).to_vec(),
+            selected: 1,
+            message: None,
+        };
+        assert_eq!(menu.preview(), "llo");
+        assert_eq!(menu.listing(80), "help  \x1b[7mhello\x1b[27m  helm  ");
+        assert_eq!(menu.listing(12), "help  …");
+    }
+
+    #[test]
+    fn test_no_history_lookups() {
+        let mut editor = Editor::headless("> ");
+        editor.history.push("git status".chars().collect());
+        editor.finder = Some(FinderSource::History);
+        editor.no_history = true;
+
+        assert!(!editor.suggest_correction("gti status"));
+        // the finder would wait for a key to pick an entry with, failing headless
+        assert_eq!(editor.feed(Key::ctrl('t')).unwrap(), None);
+        assert_eq!(editor.sol.mock().unwrap().bells, 1);
+
+        editor.no_history = false;
+        assert!(editor.suggest_correction("gti status"));
+    }
+
+    #[test]
+    fn test_yank_last_arg() {
+        let mut editor = Editor::headless("> ");
+        editor.history.push("cat notes.txt".chars().collect());
+        editor.history.push("ls x".chars().collect());
+
+        editor.feed(Key::alt('.')).unwrap();
+        assert_eq!(editor.input.values, ['x']);
+        editor.input.mark_clean();
+        // the previous arg getting replaced counts as well as the inserted one
+        editor.feed(Key::alt('.')).unwrap();
+        assert_eq!(editor.input.values.iter().collect::<String>(), "notes.txt");
+        assert_eq!(editor.input.changes, 10);
+    }
+
+    #[test]
+    fn test_operate_and_get_next() {
+        for (dups, next) in [
+            (Dups::Keep, 2),
+            (Dups::IgnoreConsecutive, 2),
+            (Dups::IgnoreAll, 2),
+            (Dups::EraseOlder, 1),
+        ] {
+            let mut editor = Editor::headless("> ");
+            editor.history.policy.dups = dups;
+            ["ls", "cd", "pwd"]
+                .iter()
+                .for_each(|e| editor.history.push(e.chars().collect()));
+            editor.feed(Key::from(Code::Up)).unwrap();
+            editor.feed(Key::from(Code::Up)).unwrap();
+
+            assert_eq!(editor.feed(Key::ctrl('o')).unwrap().as_deref(), Some("cd"));
+            assert_eq!(editor.preload, Some(next), "{:?}", dups);
+            assert_eq!(editor.history.values[next], ['p', 'w', 'd']);
+        }
+    }
+
+    #[test]
+    fn test_unbound_keys() {
+        let mut editor = Editor::headless("> ");
+        editor.input.set_text("ls -a");
+        editor.input.cursor = 2;
+        editor.feed(Key::from(Code::Delete)).unwrap();
+        assert_eq!(editor.input.values.iter().collect::<String>(), "ls-a");
+
+        editor.feed(Key::ctrl('q')).unwrap();
+        assert_eq!(editor.sol.mock().unwrap().bells, 1);
+    }
+
+    #[test]
+    fn test_run_interrupted() {
+        let mut editor = Editor::headless("> ");
+        let keys = [
+            Key::char('l'),
+            Key::char('s'),
+            Key::ctrl('c'),
+            Key::from(Code::Enter),
+        ];
+        editor.queued.extend(keys.map(KeyEvent::Key));
+        let mut lines = Vec::new();
+        editor
+            .run(|_, line| {
+                lines.push(line);
+                ControlFlow::Break(())
+            })
+            .unwrap();
+
+        assert_eq!(lines, [""]);
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::events::{Decoder, KeyEvent};
+use crate::key::{Code, Key};
+
+// the upper half of KOI8-R, from 0x80 to 0xff, latin-1 bytes are their own codepoints
+const KOI8_R: &str = "─│┌┐└┘├┤┬┴┼▀▄█▌▐░▒▓⌠■∙√≈≤≥\u{a0}⌡°²·÷═║╒ё╓╔╕╖╗╘╙╚╛╜╝╞╟╠╡Ё╢╣╤╥╦╧╨╩╪╫╬©\
+    юабцдефгхийклмнопярстужвьызшэщчъЮАБЦДЕФГХИЙКЛМНОПЯРСТУЖВЬЫЗШЭЩЧЪ";
+
+// the encoding of the terminal, set by init
+static CURRENT: AtomicU8 = AtomicU8::new(Encoding::Utf8 as u8);
+
+/// The character encodings a terminal may use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    /// ISO-8859-1
+    Latin1,
+    Koi8R,
+}
+
+impl Encoding {
+    /// Detects the encoding from the codeset of the locale (`LC_ALL`, `LC_CTYPE` then `LANG`),
+    /// unknown codesets and unset locales fall back to UTF-8
+    pub fn detect() -> Self {
+        ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|locale| !locale.is_empty())
+            .map_or(Self::Utf8, |locale| Self::from_locale(&locale))
+    }
+
+    /// Returns the encoding of a locale like `ru_RU.KOI8-R` or `de_DE.ISO-8859-1@euro`
+    pub fn from_locale(locale: &str) -> Self {
+        let codeset = locale
+            .split_once('.')
+            .map_or("", |(_, codeset)| codeset)
+            .split('@')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match codeset.as_str() {
+            "iso88591" | "latin1" => Self::Latin1,
+            "koi8r" => Self::Koi8R,
+            _ => Self::Utf8,
+        }
+    }
+
+    /// Returns the char of a byte above 0x7f in a single byte encoding,
+    /// None in UTF-8 where such a byte is only part of a char
+    pub fn decode_byte(&self, b: u8) -> Option<char> {
+        match self {
+            Self::Utf8 => None,
+            _ if b.is_ascii() => Some(b as char),
+            Self::Latin1 => Some(b as char),
+            Self::Koi8R => KOI8_R.chars().nth(b as usize - 0x80),
+        }
+    }
+
+    /// Appends the encoded char to bytes, the chars the encoding lacks become `?`
+    pub fn encode_char(&self, c: char, bytes: &mut Vec<u8>) {
+        match self {
+            Self::Utf8 => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            _ if c.is_ascii() => bytes.push(c as u8),
+            Self::Latin1 => bytes.push(u8::try_from(c).unwrap_or(b'?')),
+            Self::Koi8R => bytes.push(
+                KOI8_R
+                    .chars()
+                    .position(|k| k == c)
+                    .map_or(b'?', |idx| idx as u8 + 0x80),
+            ),
+        }
+    }
+
+    /// Encodes a whole text
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(text.len());
+        text.chars().for_each(|c| self.encode_char(c, &mut bytes));
+
+        bytes
+    }
+}
+
+/// Returns the encoding the input line gets rendered in, set by [`crate::init`]
+pub fn current() -> Encoding {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Encoding::Latin1,
+        2 => Encoding::Koi8R,
+        _ => Encoding::Utf8,
+    }
+}
+
+/// Overrides the encoding detected by [`crate::init`]
+pub fn set_current(encoding: Encoding) {
+    CURRENT.store(encoding as u8, Ordering::Relaxed);
+}
+
+/// Decodes the bytes read from a terminal using a single byte encoding into key presses,
+/// the escape sequences that are not keys become [`Code::Other`] keys, see [`Decoder`]
+///
+/// the raw bytes have to be decoded by the crate itself since crossterm only reads UTF-8
+pub fn decode_keys(bytes: &[u8], encoding: Encoding) -> Vec<Key> {
+    let mut decoder = Decoder::new();
+    decoder.encoding = encoding;
+    let mut events = decoder.decode(bytes);
+    events.extend(decoder.flush());
+
+    events
+        .into_iter()
+        .flat_map(|event| match event {
+            KeyEvent::Key(key) => vec![key],
+            KeyEvent::Unknown(_) | KeyEvent::Mouse(_) => vec![Key::new(Code::Other)],
+            KeyEvent::Paste(text) => text.chars().map(Key::char).collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_encoding {
+    use super::{decode_keys, Encoding};
+    use crate::key::{Code, Key, Mods};
+
+    #[test]
+    fn test_transcode() {
+        assert_eq!(Encoding::from_locale("ru_RU.KOI8-R"), Encoding::Koi8R);
+        assert_eq!(
+            Encoding::from_locale("de_DE.ISO-8859-1@euro"),
+            Encoding::Latin1
+        );
+        assert_eq!(Encoding::from_locale("en_US.UTF-8"), Encoding::Utf8);
+        assert_eq!(Encoding::from_locale("C"), Encoding::Utf8);
+
+        assert_eq!(Encoding::Koi8R.decode_byte(0xc1), Some('а'));
+        assert_eq!(Encoding::Koi8R.encode("Ёж €"), [0xb3, 0xd6, b' ', b'?']);
+        assert_eq!(Encoding::Latin1.encode("é"), [0xe9]);
+        assert_eq!(Encoding::Utf8.encode("é"), "é".as_bytes());
+    }
+
+    #[test]
+    fn test_decode_keys() {
+        assert_eq!(
+            decode_keys(b"\xe9a\x01\x1bb\x1b[1;5D\x1b[3~\r\x1b", Encoding::Latin1),
+            [
+                Key::char('é'),
+                Key::char('a'),
+                Key::ctrl('a'),
+                Key::alt('b'),
+                Key::with(Code::Left, Mods::CTRL),
+                Key::new(Code::Delete),
+                Key::new(Code::Enter),
+                Key::new(Code::Esc),
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+/// The errors of setting up and rendering to the terminal
+#[derive(Debug)]
+pub enum RagoutError {
+    /// writing to the terminal failed
+    IoError(std::io::Error),
+    /// there is no terminal to enter the raw mode of, e.g. stdin is redirected from a file with
+    /// no controlling terminal left
+    NotATty,
+    /// the terminal refused the raw mode configuration (tcgetattr or tcsetattr failed)
+    TermiosError(std::io::Error),
+}
+
+impl RagoutError {
+    // classifies an error of TermBackend::enable_raw_mode
+    pub(crate) fn raw_mode(e: std::io::Error) -> Self {
+        #[cfg(unix)]
+        if matches!(e.raw_os_error(), Some(libc::ENOTTY | libc::ENXIO)) {
+            return Self::NotATty;
+        }
+        match e.kind() {
+            std::io::ErrorKind::Unsupported | std::io::ErrorKind::NotFound => Self::NotATty,
+            _ => Self::TermiosError(e),
+        }
+    }
+}
+
+impl std::fmt::Display for RagoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "terminal i/o failed: {}", e),
+            Self::NotATty => write!(f, "not a terminal"),
+            Self::TermiosError(e) => write!(f, "could not enter the raw mode: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RagoutError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IoError(e) | Self::TermiosError(e) => Some(e),
+            Self::NotATty => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RagoutError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+impl From<RagoutError> for std::io::Error {
+    fn from(e: RagoutError) -> Self {
+        match e {
+            RagoutError::IoError(e) | RagoutError::TermiosError(e) => e,
+            RagoutError::NotATty => {
+                std::io::Error::new(std::io::ErrorKind::Unsupported, e.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_error {
+    use super::RagoutError;
+    use std::io::{Error, ErrorKind};
+
+    #[test]
+    fn test_conversions() {
+        let unsupported = Error::new(ErrorKind::Unsupported, "stdin is not a terminal");
+        assert!(matches!(
+            RagoutError::raw_mode(unsupported),
+            RagoutError::NotATty
+        ));
+        assert!(matches!(
+            RagoutError::raw_mode(Error::from(ErrorKind::PermissionDenied)),
+            RagoutError::TermiosError(_)
+        ));
+
+        let e: Error = RagoutError::NotATty.into();
+        assert_eq!(e.kind(), ErrorKind::Unsupported);
+        let e: Error = RagoutError::from(Error::from(ErrorKind::BrokenPipe)).into();
+        assert_eq!(e.kind(), ErrorKind::BrokenPipe);
+    }
+}
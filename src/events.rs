@@ -0,0 +1,561 @@
+#[cfg(feature = "encoding")]
+use crate::encoding::Encoding;
+use crate::key::{Code, Key, Mods};
+
+// how long the rest of an escape sequence may take to arrive after its ESC
+#[cfg(unix)]
+const ESC_TIMEOUT_MS: i32 = 25;
+
+// the sequences the terminal wraps the pasted text in, in the bracketed paste mode
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// What the bytes read from a terminal decode to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyEvent {
+    /// a key press
+    Key(Key),
+    /// an escape sequence the decoder has no key for, with its bytes
+    Unknown(Vec<u8>),
+    /// the text pasted in the bracketed paste mode, see [`Input::paste`](crate::Input::paste)
+    Paste(String),
+    /// a report of the SGR mouse mode, see [`Editor::enable_mouse`](crate::Editor::enable_mouse)
+    Mouse(Mouse),
+}
+
+/// What a [`Mouse`] report is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseKind {
+    /// a button got pressed
+    Down,
+    /// a button got released
+    Up,
+    /// the mouse moved with a button held
+    Drag,
+    /// the wheel turned up
+    ScrollUp,
+    /// the wheel turned down
+    ScrollDown,
+}
+
+/// A mouse report, with the button 0 being the left one, 1 the middle one, 2 the right one
+/// and 3 none (e.g. a release in the X10 compatible reports)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mouse {
+    pub kind: MouseKind,
+    pub button: u8,
+    /// the 0 based column of the cell the mouse is on
+    pub column: u16,
+    /// the 0 based row of the cell the mouse is on
+    pub row: u16,
+    pub mods: Mods,
+}
+
+/// Decodes the bytes read from a terminal in raw mode into [`KeyEvent`]s: the chars (UTF-8,
+/// or the encoding of the terminal with the `encoding` feature), the control chars as Ctrl
+/// keys, the ESC prefixed bytes as Alt keys and the CSI and SS3 sequences of the arrows,
+/// Home/End, PageUp/PageDown, Insert/Delete and the function keys, with their xterm modifiers,
+/// the bracketed pastes and the SGR mouse reports
+///
+/// the sequences split across reads are held back until the next read completes them, see
+/// [`Decoder::flush`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoder {
+    // the bytes of an incomplete sequence
+    pending: Vec<u8>,
+    /// the encoding of the bytes above 0x7f
+    #[cfg(feature = "encoding")]
+    pub encoding: Encoding,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder {
+    /// Creates a new Decoder, in the encoding detected by [`crate::init`] with the `encoding`
+    /// feature
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            #[cfg(feature = "encoding")]
+            encoding: crate::encoding::current(),
+        }
+    }
+
+    /// Decodes the events the bytes complete, the bytes of a sequence cut short stay pending
+    pub fn decode(&mut self, bytes: &[u8]) -> Vec<KeyEvent> {
+        self.pending.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        let mut idx = 0;
+        while let Some((event, len)) = self.next_event(&self.pending[idx..]) {
+            events.push(event);
+            idx += len;
+        }
+        self.pending.drain(..idx);
+
+        events
+    }
+
+    /// Decodes the pending bytes as they are, once no more bytes came in for a while: a lone
+    /// ESC is then the Esc key
+    ///
+    /// the bytes of a bracketed paste stay pending until its end comes in, however long the
+    /// pasted text takes to arrive
+    pub fn flush(&mut self) -> Vec<KeyEvent> {
+        if self.pending.starts_with(PASTE_START) {
+            return Vec::new();
+        }
+        let pending = std::mem::take(&mut self.pending);
+        match pending.as_slice() {
+            [] => Vec::new(),
+            [0x1b] => vec![KeyEvent::Key(Key::new(Code::Esc))],
+            [0x1b, b] if b.is_ascii() => vec![KeyEvent::Key(self.alt(*b))],
+            _ => vec![KeyEvent::Unknown(pending)],
+        }
+    }
+
+    /// Returns true if some bytes wait for the rest of their sequence
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    // the event the bytes start with and the number of bytes it took, None if the bytes end
+    // before the event does
+    fn next_event(&self, bytes: &[u8]) -> Option<(KeyEvent, usize)> {
+        let b = *bytes.first()?;
+        if b != 0x1b {
+            return self.char_event(bytes);
+        }
+        match bytes.get(1)? {
+            b'[' if bytes.starts_with(PASTE_START) => {
+                let end = bytes.windows(6).position(|w| w == PASTE_END)?;
+                Some((KeyEvent::Paste(self.text(&bytes[6..end])), end + 6))
+            }
+            b'[' => {
+                let end = bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b))? + 2;
+                let event = match (csi(&bytes[2..end], bytes[end]), bytes[2]) {
+                    (Some(key), _) => KeyEvent::Key(key),
+                    (None, b'<') => match sgr_mouse(&bytes[3..end], bytes[end]) {
+                        Some(mouse) => KeyEvent::Mouse(mouse),
+                        None => KeyEvent::Unknown(bytes[..=end].to_vec()),
+                    },
+                    (None, _) => KeyEvent::Unknown(bytes[..=end].to_vec()),
+                };
+                Some((event, end + 1))
+            }
+            b'O' => {
+                let event = match ss3(*bytes.get(2)?) {
+                    Some(key) => KeyEvent::Key(key),
+                    None => KeyEvent::Unknown(bytes[..3].to_vec()),
+                };
+                Some((event, 3))
+            }
+            // the second ESC may start a sequence of its own
+            0x1b => Some((KeyEvent::Key(Key::new(Code::Esc)), 1)),
+            _ => {
+                let (event, len) = self.char_event(&bytes[1..])?;
+                let event = match event {
+                    KeyEvent::Key(mut key) => {
+                        key.mods.alt = true;
+                        KeyEvent::Key(key)
+                    }
+                    unknown => unknown,
+                };
+                Some((event, len + 1))
+            }
+        }
+    }
+
+    // the key of the char the bytes start with
+    fn char_event(&self, bytes: &[u8]) -> Option<(KeyEvent, usize)> {
+        let b = bytes[0];
+        if b.is_ascii() {
+            return Some((KeyEvent::Key(byte_key(b)), 1));
+        }
+        #[cfg(feature = "encoding")]
+        if let Some(c) = self.encoding.decode_byte(b) {
+            return Some((KeyEvent::Key(Key::char(c)), 1));
+        }
+        // the length of the UTF-8 sequence from its leading byte
+        let len = match b {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Some((KeyEvent::Unknown(vec![b]), 1)),
+        };
+        let event = match std::str::from_utf8(bytes.get(..len)?) {
+            Ok(s) => KeyEvent::Key(Key::char(s.chars().next()?)),
+            Err(_) => KeyEvent::Unknown(bytes[..len].to_vec()),
+        };
+
+        Some((event, len))
+    }
+
+    // the text of the bytes of a paste, taken as they are
+    fn text(&self, bytes: &[u8]) -> String {
+        let mut text = String::new();
+        let mut idx = 0;
+        while idx < bytes.len() {
+            if bytes[idx].is_ascii() {
+                text.push(bytes[idx] as char);
+                idx += 1;
+                continue;
+            }
+            match self.char_event(&bytes[idx..]) {
+                Some((KeyEvent::Key(key), len)) => {
+                    if let Code::Char(c) = key.code {
+                        text.push(c);
+                    }
+                    idx += len;
+                }
+                Some((_, len)) => {
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    idx += len;
+                }
+                None => {
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    break;
+                }
+            }
+        }
+
+        text
+    }
+
+    // the Alt key of an ESC prefixed ascii byte
+    fn alt(&self, b: u8) -> Key {
+        let mut key = byte_key(b);
+        key.mods.alt = true;
+
+        key
+    }
+}
+
+// the key of an ascii byte, like crossterm decodes them in raw mode
+fn byte_key(b: u8) -> Key {
+    match b {
+        b'\r' => Key::new(Code::Enter),
+        b'\t' => Key::new(Code::Tab),
+        0x7f | 0x08 => Key::new(Code::Backspace),
+        0x00 => Key::ctrl(' '),
+        0x01..=0x1a => Key::ctrl((b - 0x01 + b'a') as char),
+        0x1b => Key::new(Code::Esc),
+        0x1c..=0x1f => Key::ctrl((b - 0x1c + b'4') as char),
+        _ => Key::char(b as char),
+    }
+}
+
+// the key of a `CSI params final` sequence, None for the sequences that are not keys
+fn csi(params: &[u8], last: u8) -> Option<Key> {
+    // the private parameters (e.g. `<` of the SGR mouse reports) are no keys
+    if params.first().is_some_and(|b| (0x3c..=0x3f).contains(b)) {
+        return None;
+    }
+    let params = String::from_utf8_lossy(params);
+    let mut params = params.split(';').map(|p| p.parse::<u8>().unwrap_or(1));
+    let first = params.next().unwrap_or(1);
+    let mods = xterm_mods(params.next().unwrap_or(1));
+    let code = match last {
+        b'A' => Code::Up,
+        b'B' => Code::Down,
+        b'C' => Code::Right,
+        b'D' => Code::Left,
+        b'H' => Code::Home,
+        b'F' => Code::End,
+        b'P' => Code::F(1),
+        b'Q' => Code::F(2),
+        b'R' => Code::F(3),
+        b'S' => Code::F(4),
+        b'Z' => return Some(Key::new(Code::BackTab)),
+        b'~' => match first {
+            1 | 7 => Code::Home,
+            2 => Code::Insert,
+            3 => Code::Delete,
+            4 | 8 => Code::End,
+            5 => Code::PageUp,
+            6 => Code::PageDown,
+            11..=15 => Code::F(first - 10),
+            17..=21 => Code::F(first - 11),
+            23..=24 => Code::F(first - 12),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(Key::with(code, mods))
+}
+
+// the mouse report of a `CSI < button ; column ; row M` (or `m` for a release) sequence, the
+// button carrying the modifiers along with the motion and wheel flags
+fn sgr_mouse(params: &[u8], last: u8) -> Option<Mouse> {
+    let params = std::str::from_utf8(params).ok()?;
+    let mut params = params.split(';').map(str::parse::<u16>);
+    let (Some(Ok(b)), Some(Ok(column)), Some(Ok(row)), None) =
+        (params.next(), params.next(), params.next(), params.next())
+    else {
+        return None;
+    };
+    let button = (b & 3) as u8;
+    let kind = match (b & 64 != 0, b & 32 != 0, last) {
+        (true, _, _) if button == 0 => MouseKind::ScrollUp,
+        (true, _, _) => MouseKind::ScrollDown,
+        (false, true, _) => MouseKind::Drag,
+        (false, false, b'M') => MouseKind::Down,
+        (false, false, b'm') => MouseKind::Up,
+        _ => return None,
+    };
+
+    Some(Mouse {
+        kind,
+        button,
+        column: column.saturating_sub(1),
+        row: row.saturating_sub(1),
+        mods: Mods {
+            shift: b & 4 != 0,
+            alt: b & 8 != 0,
+            ctrl: b & 16 != 0,
+        },
+    })
+}
+
+// the mouse report of a crossterm mouse event, for the reads going through crossterm; None for
+// the moves without a button held
+pub(crate) fn mouse_from_crossterm(event: crossterm::event::MouseEvent) -> Option<Mouse> {
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEventKind};
+
+    let button = |b: MouseButton| match b {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    };
+    let (kind, button) = match event.kind {
+        MouseEventKind::Down(b) => (MouseKind::Down, button(b)),
+        MouseEventKind::Up(b) => (MouseKind::Up, button(b)),
+        MouseEventKind::Drag(b) => (MouseKind::Drag, button(b)),
+        MouseEventKind::ScrollUp => (MouseKind::ScrollUp, 0),
+        MouseEventKind::ScrollDown => (MouseKind::ScrollDown, 1),
+        _ => return None,
+    };
+
+    Some(Mouse {
+        kind,
+        button,
+        column: event.column,
+        row: event.row,
+        mods: Mods {
+            shift: event.modifiers.contains(KeyModifiers::SHIFT),
+            alt: event.modifiers.contains(KeyModifiers::ALT),
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        },
+    })
+}
+
+// the key of an `SS3 final` sequence, sent by the keypads in application mode
+fn ss3(last: u8) -> Option<Key> {
+    let code = match last {
+        b'A' => Code::Up,
+        b'B' => Code::Down,
+        b'C' => Code::Right,
+        b'D' => Code::Left,
+        b'H' => Code::Home,
+        b'F' => Code::End,
+        b'M' => Code::Enter,
+        b'P'..=b'S' => Code::F(last - b'P' + 1),
+        _ => return None,
+    };
+
+    Some(Key::new(code))
+}
+
+// xterm modifiers are 1 + a bit mask of shift, alt and ctrl
+fn xterm_mods(param: u8) -> Mods {
+    let bits = param.saturating_sub(1);
+
+    Mods {
+        shift: bits & 1 != 0,
+        alt: bits & 2 != 0,
+        ctrl: bits & 4 != 0,
+    }
+}
+
+/// Waits up to timeout_ms (forever if negative) for stdin to become readable then decodes
+/// what got read, a sequence cut short gets a few more milliseconds to complete before the
+/// decoder gets flushed, while a bracketed paste gets read up to its end
+///
+/// returns no events if nothing was read in time or a signal came in
+///
+/// # Errors
+/// Returns an [`std::io::ErrorKind::UnexpectedEof`] error once stdin is closed
+#[cfg(unix)]
+pub fn read_events(decoder: &mut Decoder, timeout_ms: i32) -> std::io::Result<Vec<KeyEvent>> {
+    let mut events = Vec::new();
+    let mut buf = [0u8; 256];
+    let mut timeout = timeout_ms;
+    loop {
+        match poll_read(&mut buf, timeout)? {
+            Some(n) => events.extend(decoder.decode(&buf[..n])),
+            None => events.extend(decoder.flush()),
+        }
+        if !decoder.is_pending() {
+            return Ok(events);
+        }
+        timeout = ESC_TIMEOUT_MS;
+    }
+}
+
+// reads what stdin has once readable, None on a timeout or an interrupting signal
+#[cfg(unix)]
+fn poll_read(buf: &mut [u8], timeout_ms: i32) -> std::io::Result<Option<usize>> {
+    let mut fd = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // SAFETY: poll watches the single pollfd, read fills at most buf.len() bytes of buf
+    unsafe {
+        match libc::poll(&mut fd, 1, timeout_ms) {
+            0 => return Ok(None),
+            n if n < 0 => {
+                let e = std::io::Error::last_os_error();
+                // a signal (e.g. SIGWINCH) came in, which is not a Ctrl-C interruption
+                return match e.kind() == std::io::ErrorKind::Interrupted {
+                    true => Ok(None),
+                    false => Err(e),
+                };
+            }
+            _ => (),
+        }
+        match libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) {
+            n if n < 0 => {
+                let e = std::io::Error::last_os_error();
+                match e.kind() == std::io::ErrorKind::Interrupted {
+                    true => Ok(None),
+                    false => Err(e),
+                }
+            }
+            0 => Err(std::io::ErrorKind::UnexpectedEof.into()),
+            n => Ok(Some(n as usize)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_events {
+    use super::{Decoder, KeyEvent, Mouse, MouseKind};
+    use crate::key::{Code, Key, Mods};
+
+    #[test]
+    fn test_decode() {
+        let mut decoder = Decoder::new();
+        let keys = |events: Vec<KeyEvent>| {
+            events
+                .into_iter()
+                .map(|e| match e {
+                    KeyEvent::Key(key) => key,
+                    _ => Key::new(Code::Other),
+                })
+                .collect::<Vec<Key>>()
+        };
+
+        assert_eq!(
+            keys(decoder.decode(b"a\x01\x1bb\x1b[1;5D\x1b[3~\x1bOP\x1b[15;2~\r\x7f")),
+            [
+                Key::char('a'),
+                Key::ctrl('a'),
+                Key::alt('b'),
+                Key::with(Code::Left, Mods::CTRL),
+                Key::new(Code::Delete),
+                Key::new(Code::F(1)),
+                Key::with(Code::F(5), Mods::SHIFT),
+                Key::new(Code::Enter),
+                Key::new(Code::Backspace),
+            ]
+        );
+        assert_eq!(
+            decoder.decode(b"\x1b[<0;3;1M\x1b[<32;4;2M\x1b[<0;4;2m\x1b[<65;1;1M\x1b[<1;2M"),
+            [
+                KeyEvent::Mouse(Mouse {
+                    kind: MouseKind::Down,
+                    button: 0,
+                    column: 2,
+                    row: 0,
+                    mods: Mods::NONE,
+                }),
+                KeyEvent::Mouse(Mouse {
+                    kind: MouseKind::Drag,
+                    button: 0,
+                    column: 3,
+                    row: 1,
+                    mods: Mods::NONE,
+                }),
+                KeyEvent::Mouse(Mouse {
+                    kind: MouseKind::Up,
+                    button: 0,
+                    column: 3,
+                    row: 1,
+                    mods: Mods::NONE,
+                }),
+                KeyEvent::Mouse(Mouse {
+                    kind: MouseKind::ScrollDown,
+                    button: 1,
+                    column: 0,
+                    row: 0,
+                    mods: Mods::NONE,
+                }),
+                KeyEvent::Unknown(b"\x1b[<1;2M".to_vec()),
+            ]
+        );
+        assert_eq!(
+            keys(decoder.decode("é漢".as_bytes())),
+            [Key::char('é'), Key::char('漢')]
+        );
+    }
+
+    #[test]
+    fn test_split_reads() {
+        let mut decoder = Decoder::new();
+        let bytes = "ж".as_bytes();
+        assert!(decoder.decode(&bytes[..1]).is_empty());
+        assert_eq!(decoder.decode(&bytes[1..]), [KeyEvent::Key(Key::char('ж'))]);
+
+        assert!(decoder.decode(b"\x1b[1;").is_empty());
+        assert!(decoder.is_pending());
+        assert_eq!(
+            decoder.decode(b"3A"),
+            [KeyEvent::Key(Key::with(Code::Up, Mods::ALT))]
+        );
+
+        // a lone ESC only is the Esc key once nothing follows it
+        assert!(decoder.decode(b"\x1b").is_empty());
+        assert_eq!(decoder.flush(), [KeyEvent::Key(Key::new(Code::Esc))]);
+        assert!(!decoder.is_pending());
+    }
+
+    #[test]
+    fn test_paste() {
+        let mut decoder = Decoder::new();
+        assert_eq!(
+            decoder.decode(b"a\x1b[200~rm -rf \xc3"),
+            [KeyEvent::Key(Key::char('a'))]
+        );
+        assert!(decoder.is_pending());
+        assert_eq!(
+            decoder.decode(b"\xa9\r\x1b[A\x1b[201~b"),
+            [
+                KeyEvent::Paste("rm -rf é\r\x1b[A".into()),
+                KeyEvent::Key(Key::char('b'))
+            ]
+        );
+
+        // the rest of the paste got late, e.g. past the ESC timeout or a signal
+        assert!(decoder.decode(b"\x1b[200~echo ").is_empty());
+        assert!(decoder.flush().is_empty());
+        assert!(decoder.decode(b"hi\x1b[20").is_empty());
+        assert!(decoder.flush().is_empty());
+        assert_eq!(decoder.decode(b"1~"), [KeyEvent::Paste("echo hi".into())]);
+        assert!(!decoder.is_pending());
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt::Debug;
+use std::ops::Range;
+
+use crate::input::Input;
+
+/// Rewrites the word under the cursor on demand, e.g. to evaluate inline math, expand
+/// environment variables or snippets, see [`Input::expand_with`]
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait Expander: Debug {
+    /// Returns the replacement of the text, or None to leave it as it is
+    fn expand(&mut self, text: &str) -> Option<String>;
+}
+
+/// An Expander replacing the `$NAME` and `${NAME}` environment variables of the text with
+/// their values, unset variables are left as they are
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnvVars;
+
+impl Expander for EnvVars {
+    fn expand(&mut self, text: &str) -> Option<String> {
+        let mut expanded = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                expanded.push(c);
+                continue;
+            }
+            let braced = chars.next_if_eq(&'{').is_some();
+            let mut name = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                name.push(c);
+            }
+            let closed = braced && chars.next_if_eq(&'}').is_some();
+            match std::env::var(&name) {
+                Ok(value) if !name.is_empty() && braced == closed => expanded.push_str(&value),
+                _ => {
+                    expanded.push('$');
+                    if braced {
+                        expanded.push('{');
+                    }
+                    expanded.push_str(&name);
+                    if closed {
+                        expanded.push('}');
+                    }
+                }
+            }
+        }
+
+        (expanded != text).then_some(expanded)
+    }
+}
+
+/// An Expander calling a closure
+pub struct FnExpander<F>(pub F);
+
+impl<F> Debug for FnExpander<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnExpander").finish_non_exhaustive()
+    }
+}
+
+impl<F: FnMut(&str) -> Option<String>> Expander for FnExpander<F> {
+    fn expand(&mut self, text: &str) -> Option<String> {
+        (self.0)(text)
+    }
+}
+
+impl Input {
+    /// Replaces the selection, or else the whitespace delimited word touching the cursor, with
+    /// what the expander makes of it, then puts the cursor after the replacement
+    ///
+    /// returns false if there was nothing to expand or the expander left it as it was
+    pub fn expand_with(&mut self, expander: &mut dyn Expander) -> bool {
+        let range = self.selection().unwrap_or_else(|| self.spaced_word());
+        if range.is_empty() {
+            return false;
+        }
+        let text = self.values[range.clone()].iter().collect::<String>();
+        let Some(expanded) = expander.expand(&text).filter(|e| *e != text) else {
+            return false;
+        };
+
+        let len = expanded.chars().count();
+        self.values.splice(range.clone(), expanded.chars());
+        self.cursor = range.start + len;
+        self.mark = None;
+        self.changes += 1;
+
+        true
+    }
+
+    // the span of the non whitespace chars around the cursor
+    fn spaced_word(&self) -> Range<usize> {
+        let mut start = self.cursor;
+        while start > 0 && !self.values[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        let mut end = self.cursor;
+        while end < self.values.len() && !self.values[end].is_whitespace() {
+            end += 1;
+        }
+
+        start..end
+    }
+}
+
+#[cfg(test)]
+mod test_expand {
+    use super::{EnvVars, Expander, FnExpander};
+    use crate::input::Input;
+
+    #[test]
+    fn test_expand_with() {
+        let mut i = Input::new("testing input> ", false);
+        i.set_text("echo 1+2 done");
+        i.set_cursor(6);
+        let mut sum = FnExpander(|text: &str| {
+            let (a, b) = text.split_once('+')?;
+            Some((a.parse::<i64>().ok()? + b.parse::<i64>().ok()?).to_string())
+        });
+
+        assert!(i.expand_with(&mut sum));
+        assert_eq!(i.values.iter().collect::<String>(), "echo 3 done");
+        assert_eq!(i.cursor, 6);
+        assert!(!i.expand_with(&mut sum));
+    }
+
+    #[test]
+    fn test_env_vars() {
+        std::env::set_var("RAGOUT_TEST_EXPAND", "pika");
+
+        assert_eq!(
+            EnvVars
+                .expand("$RAGOUT_TEST_EXPAND/${RAGOUT_TEST_EXPAND}chu")
+                .as_deref(),
+            Some("pika/pikachu")
+        );
+        assert_eq!(EnvVars.expand("$RAGOUT_TEST_UNSET ${x"), None);
+    }
+}
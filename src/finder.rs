@@ -0,0 +1,272 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::editor::Editor;
+use crate::history::History;
+use crate::key::Code;
+
+// the most rows of matches rendered under the finder prompt
+const ROWS: usize = 10;
+// the walk of a FinderSource::Files directory stops after this many files
+const MAX_FILES: usize = 10_000;
+
+/// Where the items of the Ctrl-T fuzzy finder come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinderSource {
+    /// the History entries, newest first
+    History,
+    /// the paths of the files under a directory, relative to it and without the hidden ones
+    Files(PathBuf),
+    /// a custom list of items
+    List(Vec<String>),
+}
+
+impl FinderSource {
+    /// Returns the items to pick from
+    pub fn items(&self, h: &History) -> Vec<String> {
+        match self {
+            Self::History => h.values.iter().rev().map(|e| e.iter().collect()).collect(),
+            Self::Files(root) => {
+                let mut files = Vec::new();
+                walk(root, root, &mut files);
+                files.sort();
+
+                files
+            }
+            Self::List(items) => items.clone(),
+        }
+    }
+}
+
+// collects the non hidden file paths under dir, relative to root
+fn walk(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if files.len() >= MAX_FILES {
+            return;
+        }
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        match entry.file_type().is_ok_and(|t| t.is_dir()) {
+            true => walk(root, &path, files),
+            false => files.push(
+                path.strip_prefix(root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+        }
+    }
+}
+
+/// Fuzzy matches an item against a query whose chars must all appear in the item, in order
+///
+/// the match ignores case unless the query has uppercase chars; consecutive chars and chars
+/// starting a word score higher while the gaps between the matched chars score lower
+///
+/// returns the score of the match and the char indices of the matched chars in the item
+pub fn fuzzy_match(query: &str, item: &str) -> Option<(i64, Vec<usize>)> {
+    let smart_case = query.chars().any(char::is_uppercase);
+    let fold = |c: char| match smart_case {
+        true => c,
+        false => c.to_lowercase().next().unwrap_or(c),
+    };
+    let query = query.chars().map(fold).collect::<Vec<char>>();
+    let item = item.chars().collect::<Vec<char>>();
+    let folded = item.iter().copied().map(fold).collect::<Vec<char>>();
+
+    // the leftmost end of a match, then the shortest match ending there
+    let mut q = 0;
+    let mut end = 0;
+    for (idx, c) in folded.iter().enumerate() {
+        if q < query.len() && *c == query[q] {
+            q += 1;
+            end = idx + 1;
+        }
+    }
+    if q < query.len() {
+        return None;
+    }
+    let mut start = end;
+    for c in query.iter().rev() {
+        start -= 1;
+        while folded[start] != *c {
+            start -= 1;
+        }
+    }
+
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0;
+    let mut q = 0;
+    for idx in start..end {
+        if q == query.len() || folded[idx] != query[q] {
+            score -= 1;
+            continue;
+        }
+        score += 16;
+        if positions.last().is_some_and(|last| last + 1 == idx) {
+            score += 8;
+        }
+        let word_start = idx == 0
+            || !item[idx - 1].is_alphanumeric()
+            || (item[idx - 1].is_lowercase() && item[idx].is_uppercase());
+        if word_start {
+            score += 10;
+        }
+        positions.push(idx);
+        q += 1;
+    }
+
+    Some((score, positions))
+}
+
+// the indices of the items matching the query, best first then shortest then in order
+fn filter(query: &str, items: &[String]) -> Vec<(usize, Vec<usize>)> {
+    let mut matches = items
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, item)| fuzzy_match(query, item).map(|(score, pos)| (idx, score, pos)))
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(idx, score, _)| (std::cmp::Reverse(*score), items[*idx].len(), *idx));
+
+    matches
+        .into_iter()
+        .map(|(idx, _, positions)| (idx, positions))
+        .collect()
+}
+
+// the rows of the finder list: the match count then the matches around the selected one,
+// with their matched chars in bold and the selected match reversed
+fn list(
+    items: &[String],
+    matches: &[(usize, Vec<usize>)],
+    selected: usize,
+    rows: usize,
+    width: usize,
+) -> Vec<String> {
+    let mut list = vec![format!("  {}/{}", matches.len(), items.len())];
+    let first = (selected + 1).saturating_sub(rows);
+    for (n, (idx, positions)) in matches.iter().enumerate().skip(first).take(rows) {
+        let mut row = match n == selected {
+            true => "\x1b[7m> ".to_string(),
+            false => "  ".to_string(),
+        };
+        for (pos, c) in items[*idx]
+            .chars()
+            .take(width.saturating_sub(2))
+            .enumerate()
+        {
+            match positions.contains(&pos) {
+                true => row.push_str(&format!("\x1b[1m{}\x1b[22m", c)),
+                false => row.push(c),
+            }
+        }
+        row.push_str("\x1b[0m");
+        list.push(row);
+    }
+
+    list
+}
+
+impl Editor {
+    /// Opens an fzf-style fuzzy finder under the line being edited that filters the items
+    /// as the query gets typed, see [`fuzzy_match`]
+    ///
+    /// Up/Down (or Ctrl-P/Ctrl-N) move the selection, Enter picks the selected item,
+    /// Esc, Ctrl-C or Ctrl-G cancel; the line being edited is left untouched and repainted
+    /// as it was once the finder closes
+    ///
+    /// returns None if the finder was cancelled or nothing matched
+    pub fn fuzzy_find(&mut self, items: &[String]) -> std::io::Result<Option<String>> {
+        let overlay = self.overlay("> ");
+        let primary = std::mem::replace(&mut self.input, overlay);
+
+        let picked = self.read_finder(items);
+        self.input = primary;
+        _ = self.sol.write(b"\r\x1b[J");
+        self.input.render(&mut self.sol)?;
+
+        picked
+    }
+
+    // the event loop of fuzzy_find
+    fn read_finder(&mut self, items: &[String]) -> std::io::Result<Option<String>> {
+        let mut scratch = self.scratch_history();
+        let mut matches = filter("", items);
+        let mut selected = 0;
+        loop {
+            let (width, height) = self.backend.size().unwrap_or((80, 24));
+            let list = list(
+                items,
+                &matches,
+                selected,
+                ROWS.min((height as usize).saturating_sub(2)),
+                width as usize,
+            );
+            self.input.render(&mut self.sol)?;
+            // the list goes under the prompt, then the cursor goes back up to the query
+            _ = self.sol.write(b"\x1b[J");
+            list.iter().for_each(|row| {
+                _ = self.sol.write(b"\r\n\x1b[2K");
+                _ = self.sol.write(row.as_bytes());
+            });
+            _ = self.sol.write(
+                format!("\x1b[{}A\x1b[{}G", list.len(), self.input.cursor_column()).as_bytes(),
+            );
+            _ = self.sol.flush();
+
+            let key = self.next_key()?;
+            match (key.code, key.mods.ctrl) {
+                (Code::Enter, _) => {
+                    return Ok(matches.get(selected).map(|(idx, _)| items[*idx].clone()))
+                }
+                (Code::Esc, _) | (Code::Char('c' | 'g'), true) => return Ok(None),
+                (Code::Up, _) | (Code::Char('p'), true) => selected = selected.saturating_sub(1),
+                (Code::Down | Code::Tab, _) | (Code::Char('n'), true) => {
+                    selected = (selected + 1).min(matches.len().saturating_sub(1))
+                }
+                _ => match self.keymap.op(key) {
+                    Some(op) if self.input.apply(&op, &mut scratch) => {
+                        matches = filter(&self.input.values.iter().collect::<String>(), items);
+                        selected = 0;
+                    }
+                    _ => self.input.ring(&mut self.sol),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_finder {
+    use super::{filter, fuzzy_match};
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("crt", "src/cart.rs").unwrap().1, [4, 6, 7]);
+        assert_eq!(fuzzy_match("SR", "src").map(|m| m.1), None);
+        assert_eq!(fuzzy_match("", "src").unwrap().1, []);
+        assert!(
+            fuzzy_match("ed", "src/editor.rs").unwrap().0 > fuzzy_match("ed", "lined").unwrap().0
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let items = ["src/lib.rs", "README.md", "src/editor.rs", "Cargo.lock"]
+            .map(String::from)
+            .to_vec();
+
+        assert_eq!(
+            filter("rs", &items)
+                .into_iter()
+                .map(|(idx, _)| items[idx].as_str())
+                .collect::<Vec<_>>(),
+            ["src/lib.rs", "src/editor.rs"]
+        );
+    }
+}
@@ -0,0 +1,39 @@
+// minimal glob matching used for the history ignore patterns: `*` matches any run of chars,
+// `?` matches any single char and `\` escapes the next char
+
+/// Returns true if the whole of text matches the glob pattern
+pub(crate) fn matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|idx| matches(&pattern[1..], &text[idx..])),
+        Some('?') => !text.is_empty() && matches(&pattern[1..], &text[1..]),
+        Some('\\') if pattern.len() > 1 => {
+            text.first() == Some(&pattern[1]) && matches(&pattern[2..], &text[1..])
+        }
+        Some(c) => text.first() == Some(c) && matches(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod test_glob {
+    use super::matches;
+
+    fn m(pattern: &str, text: &str) -> bool {
+        matches(
+            &pattern.chars().collect::<Vec<char>>(),
+            &text.chars().collect::<Vec<char>>(),
+        )
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(m("ls", "ls"));
+        assert!(!m("ls", "ls -la"));
+        assert!(m("ls*", "ls -la"));
+        assert!(m("*--token*", "curl --token abc"));
+        assert!(m("c?", "cd"));
+        assert!(!m("c?", "c"));
+        assert!(m(r"\*", "*"));
+        assert!(!m(r"\*", "a"));
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt::Debug;
+use std::ops::Range;
+
+use crate::input::Input;
+use crate::style::Style;
+
+/// Styles the parts of the line as it gets edited, e.g. the keywords, strings and errors of a
+/// language, see [`crate::Editor::highlighter`]
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait Highlighter: Debug {
+    /// Returns the styles of the parts of the line, as byte ranges of it; where ranges overlap
+    /// the last one wins
+    fn highlight(&self, line: &str) -> Vec<(Range<usize>, Style)>;
+}
+
+/// A Highlighter calling a closure
+pub struct FnHighlighter<F>(pub F);
+
+impl<F> Debug for FnHighlighter<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnHighlighter").finish_non_exhaustive()
+    }
+}
+
+impl<F: Fn(&str) -> Vec<(Range<usize>, Style)>> Highlighter for FnHighlighter<F> {
+    fn highlight(&self, line: &str) -> Vec<(Range<usize>, Style)> {
+        (self.0)(line)
+    }
+}
+
+impl Input {
+    /// Replaces the highlights of the line with the styles of a Highlighter, their byte ranges
+    /// turned into char ranges; the ranges that don't fall on char boundaries are dropped
+    pub fn highlight(&mut self, highlighter: &dyn Highlighter) {
+        let line = self.values.iter().collect::<String>();
+        self.highlights = highlighter
+            .highlight(&line)
+            .into_iter()
+            .filter_map(|(bytes, style)| {
+                let start = line.get(..bytes.start)?.chars().count();
+                let len = line.get(bytes)?.chars().count();
+                Some((start..start + len, style))
+            })
+            .collect();
+    }
+
+    // the style of the char at idx, the last highlight covering it wins
+    pub(crate) fn highlight_at(&self, idx: usize) -> Option<Style> {
+        self.highlights
+            .iter()
+            .rev()
+            .find(|(chars, _)| chars.contains(&idx))
+            .map(|(_, style)| *style)
+    }
+}
+
+#[cfg(test)]
+mod test_highlight {
+    use super::FnHighlighter;
+    use crate::input::Input;
+    use crate::style::{Color, Style};
+
+    #[test]
+    fn test_highlight() {
+        let mut i = Input::new("testing input> ", false);
+        i.set_text("é if x");
+        let keyword = Style::new().fg(Color::Blue);
+        i.highlight(&FnHighlighter(|line: &str| {
+            let start = line.find("if").unwrap();
+            vec![(start..start + 2, keyword), (1..2, Style::new())]
+        }));
+
+        assert_eq!(i.highlights, [(2..4, keyword)]);
+        assert_eq!(i.highlight_at(3), Some(keyword));
+        assert_eq!(i.highlight_at(4), None);
+        assert!(i.styled_values().starts_with("é \x1b[0;34mif\x1b[0m x"));
+    }
+}
@@ -0,0 +1,648 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::glob;
+use crate::log::LogSink;
+use crate::regex::Regex;
+use crate::store::{escape, FileStore, HistoryStore};
+use crate::text::Text;
+use crate::tokens::tokenize;
+
+// NOTE: the cursor in both input and history does not point to the item it's on,
+// but is alawys pointing at the item to the left
+// basically cursor = 0 points at nothing and cursor = 4 points at eg. input[3]
+// this logic is implemented in the functionality
+
+#[derive(Debug, Clone)]
+pub struct History {
+    /// where the debug log records go, disabled by default
+    pub debug_log: LogSink,
+    pub values: Vec<Vec<char>>,
+    pub cursor: usize,
+    pub temp: Option<Vec<char>>,
+    pub usage: HashMap<Vec<char>, Usage>,
+    /// glob patterns (`*`, `?`, `\` escapes) of the lines that never get recorded,
+    /// like bash's HISTIGNORE; a pattern starting with `re:` is a regex matching anywhere in
+    /// the line instead (e.g. `re:--token\S*`), an invalid regex matches nothing
+    pub ignore: Vec<String>,
+    /// the most entries kept, the oldest ones get dropped past it, like bash's HISTSIZE
+    pub max_entries: Option<usize>,
+    /// the file every recorded entry gets appended to as it is pushed, see [`History::save`]
+    pub autosave: Option<std::path::PathBuf>,
+    /// which of the pushed lines get recorded, like bash's HISTCONTROL
+    pub policy: HistoryPolicy,
+    // the number of recorded pushes, used to order usages by recency
+    pushes: usize,
+    // the entries appended to the autosave file since it was last written whole, None when it
+    // is to be written whole on the next push
+    autosaved: Option<usize>,
+}
+
+/// Usage statistics of a history entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    /// how many times the entry was submitted
+    pub count: usize,
+    /// the rank of the last submission of the entry among all the History pushes
+    pub last: usize,
+    /// when the entry was last submitted
+    pub last_used: SystemTime,
+}
+
+type Filter = Arc<dyn Fn(&[char]) -> bool + Send + Sync>;
+
+/// How [`History::push`] treats the lines already in the History
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dups {
+    /// every line gets recorded
+    Keep,
+    /// a line same as the newest entry is not recorded, bash's `ignoredups`
+    IgnoreConsecutive,
+    /// a line already in the History is not recorded, the entry keeps its place
+    #[default]
+    IgnoreAll,
+    /// the older entries same as the line are removed before it gets recorded, so that it
+    /// moves to the end, bash's `erasedups`
+    EraseOlder,
+}
+
+/// The lines [`History::push`] records, the HISTCONTROL of a [`History`]
+#[derive(Clone, Default)]
+pub struct HistoryPolicy {
+    pub dups: Dups,
+    /// the lines starting with a space are not recorded, bash's `ignorespace`
+    pub ignore_space: bool,
+    // returns false for the lines that must not be recorded
+    filter: Option<Filter>,
+}
+
+impl std::fmt::Debug for HistoryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HistoryPolicy")
+            .field("dups", &self.dups)
+            .field("ignore_space", &self.ignore_space)
+            .field("filter", &self.filter.is_some())
+            .finish()
+    }
+}
+
+impl HistoryPolicy {
+    /// Creates the default HistoryPolicy: no duplicates, the space prefixed lines recorded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the HistoryPolicy of bash's `HISTCONTROL=ignoreboth`
+    pub fn ignore_both() -> Self {
+        Self {
+            dups: Dups::IgnoreConsecutive,
+            ignore_space: true,
+            filter: None,
+        }
+    }
+
+    /// Sets a filter returning false for the lines that must not be recorded, on top of the
+    /// other rules
+    pub fn filter(mut self, filter: impl Fn(&[char]) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Returns true if the policy lets a line get recorded, the duplicates aside
+    pub fn allows(&self, line: &[char]) -> bool {
+        !(self.ignore_space && line.first() == Some(&' '))
+            && self.filter.as_ref().is_none_or(|f| f(line))
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl History {
+    /// Creates a new History instance
+    pub fn new() -> Self {
+        Self {
+            debug_log: LogSink::default(),
+            values: Vec::new(),
+            cursor: 0,
+            temp: None,
+            usage: HashMap::new(),
+            ignore: Vec::new(),
+            max_entries: None,
+            autosave: None,
+            policy: HistoryPolicy::new(),
+            pushes: 0,
+            autosaved: None,
+        }
+    }
+
+    /// Binds the value of the previous history entry to the value variable and moves back the
+    /// History cursor by one
+    pub fn prev(&mut self, value: &mut Text) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+
+        if self.temp.is_none() || self.cursor == self.values.len() {
+            self.temp = Some(value.to_vec()); // temporarily keep input val
+        }
+
+        value.set(self.values[self.cursor - 1].iter().copied());
+        self.cursor -= 1;
+
+        true
+    }
+
+    /// Binds the value of the next history entry to the value variable and moves forward the
+    /// History cursor by one
+    pub fn next(&mut self, value: &mut Text) -> bool {
+        if self.cursor == self.values.len() {
+            return false;
+        }
+
+        if self.cursor + 1 == self.values.len() {
+            value.set(self.temp.iter().flatten().copied());
+        } else {
+            value.set(self.values[self.cursor + 1].iter().copied());
+        }
+        self.cursor += 1;
+
+        true
+    }
+
+    /// Binds the value of the closest older history entry starting with prefix to the value
+    /// variable and moves the History cursor to it, like zsh's history-beginning-search-backward
+    ///
+    /// the entries same as the value are skipped, see [`History::search_prefix`]
+    pub fn prev_matching(&mut self, prefix: &[char], value: &mut Text) -> bool {
+        let Some(idx) = self.values[..self.cursor]
+            .iter()
+            .rposition(|e| e.starts_with(prefix) && e[..] != value[..])
+        else {
+            return false;
+        };
+
+        if self.cursor == self.values.len() {
+            self.temp = Some(value.to_vec()); // temporarily keep input val
+        }
+        value.set(self.values[idx].iter().copied());
+        self.cursor = idx;
+
+        true
+    }
+
+    /// Binds the value of the closest newer history entry starting with prefix to the value
+    /// variable and moves the History cursor to it, or goes back to the line being typed when
+    /// no newer entry matches
+    pub fn next_matching(&mut self, prefix: &[char], value: &mut Text) -> bool {
+        if self.cursor == self.values.len() {
+            return false;
+        }
+
+        match self.values[self.cursor + 1..]
+            .iter()
+            .position(|e| e.starts_with(prefix) && e[..] != value[..])
+        {
+            Some(idx) => {
+                self.cursor += idx + 1;
+                value.set(self.values[self.cursor].iter().copied());
+            }
+            None => {
+                self.cursor = self.values.len();
+                value.set(self.temp.iter().flatten().copied());
+            }
+        }
+
+        true
+    }
+
+    /// Returns the prefix the matching navigation filters the entries with: the line that was
+    /// being typed before navigating the history, or the value when no entry is recalled
+    pub fn search_prefix(&self, value: &[char]) -> Vec<char> {
+        match (self.cursor == self.values.len(), &self.temp) {
+            (false, Some(temp)) => temp.clone(),
+            _ => value.to_vec(),
+        }
+    }
+
+    /// Returns the 1 based index of the current history entry,
+    /// which is `len() + 1` when no entry is recalled
+    pub fn index(&self) -> usize {
+        self.cursor + 1
+    }
+
+    /// Returns the number of history entries
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the History has no entries
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Removes the history entry at the 0 based index idx along with its usage statistics,
+    /// the History cursor keeps pointing at the same entry
+    pub fn remove(&mut self, idx: usize) -> Option<Vec<char>> {
+        if idx >= self.values.len() {
+            return None;
+        }
+        let entry = self.values.remove(idx);
+        self.usage.remove(&entry);
+        if self.cursor > idx {
+            self.cursor -= 1;
+        }
+
+        Some(entry)
+    }
+
+    /// Binds the value of the history entry at the 1 based index n to the value variable and
+    /// moves the History cursor to it, n = `len() + 1` goes back to the line being typed
+    pub fn goto(&mut self, n: usize, value: &mut Text) -> bool {
+        if n == 0 || n > self.values.len() + 1 || n == self.index() {
+            return false;
+        }
+
+        if self.cursor == self.values.len() {
+            self.temp = Some(value.to_vec());
+        }
+        self.cursor = n - 1;
+        match self.cursor == self.values.len() {
+            true => value.set(self.temp.iter().flatten().copied()),
+            false => value.set(self.values[self.cursor].iter().copied()),
+        }
+
+        true
+    }
+
+    /// Replaces the `{history_index}` and `{history_len}` placeholders of a prompt template
+    /// with the current History index and length
+    pub fn fill_prompt(&self, template: &str) -> String {
+        template
+            .replace("{history_index}", &self.index().to_string())
+            .replace("{history_len}", &self.len().to_string())
+    }
+
+    /// Pushs a new history entry into the History.values, unless it is blank, ignored or
+    /// refused by the [`History::policy`]
+    pub fn push(&mut self, value: Vec<char>) {
+        if value.iter().any(|c| *c != ' ') && !self.is_ignored(&value) {
+            self.record_use(&value);
+        }
+        if self.takes(&value) {
+            let len = self.values.len();
+            if self.policy.dups == Dups::EraseOlder {
+                self.values.retain(|e| *e != value);
+            }
+            let erased = self.values.len() < len;
+            self.values.push(value);
+            self.truncate();
+            self.autosave_newest(erased);
+        }
+        self.temp = None;
+        self.cursor = self.values.len();
+    }
+
+    // whether pushing value records it as the newest entry
+    pub(crate) fn takes(&self, value: &[char]) -> bool {
+        value.iter().any(|c| *c != ' ')
+            && !self.is_ignored(value)
+            && match self.policy.dups {
+                Dups::Keep | Dups::EraseOlder => true,
+                Dups::IgnoreConsecutive => self.values.last().is_none_or(|e| e != value),
+                Dups::IgnoreAll => self.values.iter().all(|e| e != value),
+            }
+    }
+
+    /// Writes the history entries to a file, one entry per line and oldest first,
+    /// with the backslashes and line feeds of the entries escaped
+    ///
+    /// only the newest [`History::max_entries`] entries get written
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let skipped = self
+            .max_entries
+            .map_or(0, |max| self.values.len().saturating_sub(max));
+        for entry in &self.values[skipped..] {
+            writeln!(file, "{}", escape(entry))?;
+        }
+
+        file.flush()
+    }
+
+    /// Loads the entries of a file written by [`History::save`] after the entries the History
+    /// already has, then drops the oldest ones past [`History::max_entries`]
+    ///
+    /// a missing file loads no entries
+    pub fn load(&mut self, path: impl Into<std::path::PathBuf>) -> std::io::Result<()> {
+        self.load_from(&mut FileStore::new(path))?;
+        self.truncate();
+        self.cursor = self.values.len();
+
+        Ok(())
+    }
+
+    // appends the newest entry to the autosave file; the file gets written whole instead when
+    // older entries were erased, when the appends since it last was outnumber the entries kept
+    // (so that the dropped ones don't pile up in it), and after a failure
+    fn autosave_newest(&mut self, erased: bool) {
+        let Some(path) = &self.autosave else {
+            return;
+        };
+        let whole = match self.autosaved {
+            Some(appended) => erased || self.max_entries.is_some_and(|max| appended >= max),
+            None => true,
+        };
+        // push can't fail, a failed autosave gets retried on the next push
+        self.autosaved = match whole {
+            true => self.save(path).ok().map(|_| 0),
+            false => match FileStore::new(path).append(self.values.last().unwrap()) {
+                Ok(_) => self.autosaved.map(|appended| appended + 1),
+                Err(_) => None,
+            },
+        };
+    }
+
+    // drops the oldest entries past max_entries along with their usages
+    fn truncate(&mut self) {
+        let Some(max) = self.max_entries else {
+            return;
+        };
+        let excess = self.values.len().saturating_sub(max);
+        for entry in self.values.drain(..excess) {
+            self.usage.remove(&entry);
+        }
+    }
+
+    /// Returns true if the line matches any of the History ignore patterns or is refused by the
+    /// History policy
+    pub fn is_ignored(&self, line: &[char]) -> bool {
+        !self.policy.allows(line)
+            || self.ignore.iter().any(|p| match p.strip_prefix("re:") {
+                Some(re) => Regex::new(re).is_ok_and(|re| re.is_match(line)),
+                None => glob::matches(&p.chars().collect::<Vec<char>>(), line),
+            })
+    }
+
+    fn record_use(&mut self, value: &[char]) {
+        self.pushes += 1;
+        let usage = self.usage.entry(value.to_vec()).or_insert(Usage {
+            count: 0,
+            last: 0,
+            last_used: SystemTime::now(),
+        });
+        usage.count += 1;
+        usage.last = self.pushes;
+        usage.last_used = SystemTime::now();
+    }
+
+    /// Returns the last word (as typed, quotes included) of the nth history entry counting back
+    /// from the newest one, like readline's yank-last-arg
+    pub fn last_arg(&self, n: usize) -> Option<&[char]> {
+        let entry = self.values.iter().rev().nth(n)?;
+
+        tokenize(entry).last().map(|t| &entry[t.chars.clone()])
+    }
+
+    /// Returns the usage statistics of a history entry
+    pub fn usage_of(&self, entry: &[char]) -> Option<&Usage> {
+        self.usage.get(entry)
+    }
+
+    /// Returns the n most frequently submitted entries with their submission counts,
+    /// ties are broken by recency
+    pub fn most_used(&self, n: usize) -> Vec<(&[char], usize)> {
+        let mut entries = self.usage.iter().collect::<Vec<_>>();
+        entries.sort_by(|(_, a), (_, b)| b.count.cmp(&a.count).then(b.last.cmp(&a.last)));
+
+        entries
+            .into_iter()
+            .take(n)
+            .map(|(entry, usage)| (entry.as_slice(), usage.count))
+            .collect()
+    }
+
+    /// Returns the n most recently submitted entries, most recent first
+    pub fn most_recent(&self, n: usize) -> Vec<&[char]> {
+        let mut entries = self.usage.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.last));
+
+        entries
+            .into_iter()
+            .take(n)
+            .map(|(entry, _)| entry.as_slice())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_history {
+    use super::{Dups, History, HistoryPolicy};
+    use crate::text::Text;
+
+    fn push(h: &mut History, entries: &[&str]) {
+        entries.iter().for_each(|e| h.push(e.chars().collect()));
+    }
+
+    #[test]
+    fn test_push() {
+        let mut h = History::new();
+        push(&mut h, &["ls", "  ", "ls", "cd"]);
+
+        assert_eq!(h.values.len(), 2);
+        assert_eq!(h.cursor, 2);
+    }
+
+    #[test]
+    fn test_goto() {
+        let mut h = History::new();
+        push(&mut h, &["ls", "cd", "git status"]);
+        let mut value = Text::from("typing");
+
+        assert_eq!(h.fill_prompt("[{history_index}/{history_len}] "), "[4/3] ");
+        assert!(h.goto(2, &mut value));
+        assert_eq!(value, ['c', 'd']);
+        assert_eq!(h.index(), 2);
+
+        assert!(!h.goto(9, &mut value));
+        assert!(h.goto(4, &mut value));
+        assert_eq!(value.iter().collect::<String>(), "typing");
+    }
+
+    #[test]
+    fn test_ignore() {
+        let mut h = History::new();
+        h.ignore = vec!["ls".into(), "exit".into(), "*--token*".into()];
+        push(
+            &mut h,
+            &["ls", "ls -la", "exit", "curl --token 123 x.y", "cd"],
+        );
+        let texts = |h: &History| {
+            h.values
+                .iter()
+                .map(|e| e.iter().collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&h), ["ls -la", "cd"]);
+
+        let mut h = History::new();
+        h.ignore = vec![r"re:--token\S*".into(), "re:^exit$".into(), "re:(".into()];
+        push(&mut h, &["gh --token=abc", "exit", "exit 1", "re:("]);
+        assert_eq!(texts(&h), ["exit 1", "re:("]);
+    }
+
+    #[test]
+    fn test_prefix_navigation() {
+        let mut h = History::new();
+        push(&mut h, &["git status", "ls", "git log", "cd", "git log"]);
+        let mut value = Text::from("git");
+        let text = |value: &[char]| value.iter().collect::<String>();
+
+        let prefix = h.search_prefix(&value);
+        assert!(h.prev_matching(&prefix, &mut value));
+        assert_eq!(text(&value), "git log");
+        assert_eq!(h.search_prefix(&value), prefix);
+        assert!(h.prev_matching(&prefix, &mut value));
+        assert_eq!(text(&value), "git status");
+        assert!(!h.prev_matching(&prefix, &mut value));
+
+        assert!(h.next_matching(&prefix, &mut value));
+        assert_eq!(text(&value), "git log");
+        assert!(h.next_matching(&prefix, &mut value));
+        assert_eq!(text(&value), "git");
+        assert_eq!(h.cursor, h.len());
+        assert!(!h.next_matching(&prefix, &mut value));
+    }
+
+    #[test]
+    fn test_policy() {
+        let entries = |h: &History| {
+            h.values
+                .iter()
+                .map(|e| e.iter().collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        let lines = ["ls", "ls", "cd", " secret", "ls"];
+
+        let mut h = History::new();
+        h.policy = HistoryPolicy::ignore_both();
+        push(&mut h, &lines);
+        assert_eq!(entries(&h), ["ls", "cd", "ls"]);
+
+        let mut h = History::new();
+        h.policy.dups = Dups::EraseOlder;
+        push(&mut h, &lines);
+        assert_eq!(entries(&h), ["cd", " secret", "ls"]);
+        assert_eq!(h.usage_of(&['l', 's']).unwrap().count, 3);
+
+        let mut h = History::new();
+        h.policy = HistoryPolicy::new().filter(|line| line.len() > 2);
+        h.policy.dups = Dups::Keep;
+        push(&mut h, &lines);
+        assert_eq!(entries(&h), [" secret"]);
+    }
+
+    #[test]
+    fn test_usage() {
+        let mut h = History::new();
+        push(&mut h, &["ls", "cd", "ls", "git status", "cd", "ls"]);
+
+        let ls = "ls".chars().collect::<Vec<char>>();
+        assert_eq!(h.usage_of(&ls).unwrap().count, 3);
+        assert_eq!(
+            h.most_used(2),
+            vec![(ls.as_slice(), 3), (&['c', 'd'][..], 2)]
+        );
+        assert_eq!(
+            h.most_recent(2)
+                .into_iter()
+                .map(|e| e.iter().collect::<String>())
+                .collect::<Vec<_>>(),
+            ["ls", "cd"]
+        );
+    }
+
+    #[test]
+    fn test_save() {
+        let mut h = History::new();
+        push(&mut h, &["ls", "echo a\\\nb"]);
+        let path = std::env::temp_dir().join("ragout_test_save");
+
+        h.save(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "ls\necho a\\\\\\nb\n"
+        );
+        _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_load_autosave() {
+        let path = std::env::temp_dir().join("ragout_test_load_autosave");
+        let mut h = History::new();
+        h.max_entries = Some(2);
+        h.autosave = Some(path.clone());
+        push(&mut h, &["ls", "cd", "pwd"]);
+        // the dropped entry stays in the appended file until it gets written whole
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "ls\ncd\npwd\n");
+        assert_eq!(h.usage_of(&['l', 's']), None);
+
+        let mut loaded = History::new();
+        push(&mut loaded, &["top"]);
+        loaded.load(&path).unwrap();
+        assert_eq!(loaded.len(), 4);
+        loaded.max_entries = Some(1);
+        loaded.load(&path).unwrap();
+        assert_eq!(loaded.values, [['p', 'w', 'd']]);
+        assert!(History::new().load("ragout_no_such_history").is_ok());
+        _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_autosave_appends() {
+        let path =
+            std::env::temp_dir().join(format!("ragout_test_autosave_{}", std::process::id()));
+        std::fs::write(&path, "stale\n").unwrap();
+        let mut h = History::new();
+        h.max_entries = Some(3);
+        h.autosave = Some(path.clone());
+        // the first push writes the file whole, the next ones append to it
+        push(&mut h, &["ls", "cd"]);
+        std::fs::write(&path, "ls\nkept\n").unwrap();
+        push(&mut h, &["pwd", "ls"]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "ls\nkept\npwd\n");
+
+        // erasing an older entry, or having appended more entries than are kept, writes it whole
+        h.policy.dups = Dups::EraseOlder;
+        push(&mut h, &["cd"]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "ls\npwd\ncd\n");
+        push(&mut h, &["top", "a", "b"]);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "ls\npwd\ncd\ntop\na\nb\n"
+        );
+        push(&mut h, &["c"]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\nb\nc\n");
+        _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_last_arg() {
+        let mut h = History::new();
+        push(&mut h, &["cp a.txt 'b c.txt'", "ls src"]);
+
+        assert_eq!(h.last_arg(0), Some(&['s', 'r', 'c'][..]));
+        assert_eq!(
+            h.last_arg(1)
+                .map(|a| a.iter().collect::<String>())
+                .as_deref(),
+            Some("'b c.txt'")
+        );
+        assert_eq!(h.last_arg(2), None);
+    }
+}
@@ -0,0 +1,89 @@
+use std::fmt::Debug;
+
+use crate::history::History;
+use crate::input::Input;
+
+/// What the Editor does after polling an [`EventHook`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hooked {
+    /// nothing happened
+    Idle,
+    /// the hook changed the line, it gets rendered again
+    Redraw,
+    /// the text gets printed above the prompt, e.g. a message that came in meanwhile
+    Print(String),
+    /// the line gets submitted as if Enter was pressed
+    Submit,
+}
+
+/// A source of custom events polled by the Editor event loop while it waits for the key
+/// presses, e.g. the messages of a channel, a timer or a file watcher, see
+/// [`crate::Editor::hooks`]
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait EventHook: Debug {
+    /// Handles the events that came in since the last poll, about every 50ms, never blocking;
+    /// the Input is the line being edited
+    fn poll(&mut self, i: &mut Input, h: &mut History) -> Hooked;
+}
+
+/// An EventHook calling a closure
+pub struct FnHook<F>(pub F);
+
+impl<F> Debug for FnHook<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnHook").finish_non_exhaustive()
+    }
+}
+
+impl<F: FnMut(&mut Input, &mut History) -> Hooked> EventHook for FnHook<F> {
+    fn poll(&mut self, i: &mut Input, h: &mut History) -> Hooked {
+        (self.0)(i, h)
+    }
+}
+
+/// Polls every hook in order, returns what they resulted in, the idle polls left out
+pub fn poll_hooks(hooks: &mut [Box<dyn EventHook>], i: &mut Input, h: &mut History) -> Vec<Hooked> {
+    hooks
+        .iter_mut()
+        .map(|hook| hook.poll(i, h))
+        .filter(|hooked| *hooked != Hooked::Idle)
+        .collect()
+}
+
+#[cfg(test)]
+mod test_hooks {
+    use super::{poll_hooks, EventHook, FnHook, Hooked};
+    use crate::history::History;
+    use crate::input::Input;
+
+    #[test]
+    fn test_poll_hooks() {
+        let mut i = Input::new("testing input> ", false);
+        let mut h = History::new();
+        let (tx, rx) = std::sync::mpsc::channel::<String>();
+        let mut hooks: Vec<Box<dyn EventHook>> = vec![
+            Box::new(FnHook(move |_: &mut Input, _: &mut History| {
+                match rx.try_recv() {
+                    Ok(message) => Hooked::Print(message),
+                    Err(_) => Hooked::Idle,
+                }
+            })),
+            Box::new(FnHook(|i: &mut Input, _: &mut History| {
+                match i.values.iter().collect::<String>() == "go" {
+                    true => Hooked::Submit,
+                    false => Hooked::Idle,
+                }
+            })),
+        ];
+
+        assert!(poll_hooks(&mut hooks, &mut i, &mut h).is_empty());
+        tx.send("build done".to_string()).unwrap();
+        i.set_text("go");
+        assert_eq!(
+            poll_hooks(&mut hooks, &mut i, &mut h),
+            [Hooked::Print("build done".to_string()), Hooked::Submit]
+        );
+    }
+}
@@ -1,6 +1,24 @@
-use std::io::{StdoutLock, Write};
-
-use crossterm::terminal::enable_raw_mode;
+use std::cell::{Cell, RefCell};
+use std::io::{IsTerminal, StdoutLock, Write};
+use std::ops::Range;
+
+use crate::autosuggest::Suggestion;
+pub use crate::context::{self, TermContext};
+use crate::diagnostic::{focused, underline_at, underline_of, Diagnostic};
+use crate::diff::{diff, units, Drawn};
+use crate::error::RagoutError;
+pub use crate::history::History;
+use crate::kill::KillRing;
+use crate::log::{LogSink, Redaction};
+use crate::mock::MockTerminal;
+use crate::state::set_alt_screen;
+use crate::style::Style;
+use crate::term::RawConfig;
+use crate::term::{default_backend, TermBackend};
+use crate::text::Text;
+use crate::undo::UndoStack;
+use crate::vi::ViPending;
+use crate::winsize::window_size;
 
 // raw mode:
 // you need to create exetrns for C functions from unistd.h
@@ -10,80 +28,574 @@ use crossterm::terminal::enable_raw_mode;
 /// Enables terminal raw mode and initializes the necessary variables for behaving in the raw mode.
 ///
 /// Takes a [`&str`] for the shell prompt (give "" for no prompt) and a bool for the option of running in the terminal alternate screen (give true to run your cli program in alternate screen)
+///
+/// the bracketed paste mode gets enabled as well, the pasted texts then come in as a whole,
+/// see [`Input::paste`]
 /// # Errors
-/// Does NOT return errors and never panics
+/// Returns [`RagoutError::NotATty`] if there is no terminal and a [`RagoutError::TermiosError`]
+/// if the terminal refused the raw mode, the terminal is then left untouched; never panics
 ///
 /// # Example
 ///
-/// Basic usage
+/// Basic usage, through the [`crate::Editor`] that calls init and runs the event loop
 ///
-/// ```
-/// use ragout::{init, run};
+/// ```no_run
+/// use std::ops::ControlFlow;
+/// use ragout_assistant::Editor;
 ///
-/// fn main() {
+/// fn main() -> Result<(), ragout_assistant::RagoutError> {
 ///     // enter raw mode and initialize necessary variables
 ///     // the string literal argument will be the value of the prompt
-///     let (mut sol, mut i, mut h, mut ui) = init("some prompt 🐱 ", true);
+///     // the terminal gets restored once the editor is dropped, even on panic
+///     let mut editor = Editor::try_new("some prompt 🐱 ", true)?;
 ///
-///     'main: loop {
-///         let input = run(&mut i, &mut h, &mut sol, &mut ui);
+///     editor.run(|editor, input| {
 ///         if !input.is_empty() {
 ///             // do some stuff with the user input
+///             _ = editor.print_above(&input);
 ///         }
-///     }
+///         ControlFlow::Continue(())
+///     })?;
+///
+///     Ok(())
 /// }
 /// ```
-pub fn init(
+pub fn init(prompt: &str, alt_screen: bool) -> Result<Init, RagoutError> {
+    init_with(prompt, alt_screen, &mut *default_backend())
+}
+
+/// Same as [`init`] but with the raw mode configuration of the given [`RawConfig`], which is
+/// ignored on windows
+pub fn init_with_config(
+    prompt: &str,
+    alt_screen: bool,
+    config: RawConfig,
+) -> Result<Init, RagoutError> {
+    #[cfg(unix)]
+    return init_with(prompt, alt_screen, &mut crate::term::Termios::new(config));
+    #[cfg(not(unix))]
+    {
+        _ = config;
+        init(prompt, alt_screen)
+    }
+}
+
+/// What [`init`] sets up: the guarded terminal, the Input, the History and the user input
+pub type Init = (Terminal, Input, History, String);
+
+/// Same as [`init`] but enters raw mode through the given terminal backend
+pub fn init_with(
+    prompt: &str,
+    alt_screen: bool,
+    backend: &mut dyn TermBackend,
+) -> Result<Init, RagoutError> {
+    let (mut term, i, h, user_input) = enter(prompt, alt_screen, backend)?;
+    show(&mut term, &i)?;
+
+    Ok((term, i, h, user_input))
+}
+
+// the init of the Editor constructors, which keep going in whatever state the terminal is in
+pub(crate) fn init_lenient(prompt: &str, alt_screen: bool, backend: &mut dyn TermBackend) -> Init {
+    let (mut term, i, h, user_input) = enter_lenient(prompt, alt_screen, backend);
+    _ = show(&mut term, &i);
+
+    (term, i, h, user_input)
+}
+
+// enters the raw mode and sets up the terminal like init_with, the first prompt left to show
+pub(crate) fn enter(
     prompt: &str,
     alt_screen: bool,
-) -> (std::io::StdoutLock<'static>, Input, History, String) {
-    _ = enable_raw_mode();
+    backend: &mut dyn TermBackend,
+) -> Result<Init, RagoutError> {
+    let context = TermContext::capture();
+    // the handlers keep the termios found before the raw mode
+    #[cfg(all(unix, feature = "signals"))]
+    {
+        _ = crate::signals::install_cleanup();
+    }
+    backend.enable_raw_mode().map_err(RagoutError::raw_mode)?;
+    // past this point, the Terminal guard restores the terminal on error
+
+    Ok(prepare(prompt, alt_screen, context))
+}
+
+// same as enter, whatever state the terminal is left in
+pub(crate) fn enter_lenient(prompt: &str, alt_screen: bool, backend: &mut dyn TermBackend) -> Init {
+    let context = TermContext::capture();
+    #[cfg(all(unix, feature = "signals"))]
+    {
+        _ = crate::signals::install_cleanup();
+    }
+    _ = backend.enable_raw_mode();
+
+    prepare(prompt, alt_screen, context)
+}
+
+// sets up the state of a terminal in raw mode
+fn prepare(prompt: &str, alt_screen: bool, mut context: TermContext) -> Init {
+    #[cfg(feature = "encoding")]
+    crate::encoding::set_current(crate::encoding::Encoding::detect());
+    // the console only understands the ANSI escapes once told to
+    #[cfg(windows)]
+    crate::windows::enable_vt_processing();
 
     let mut sol = std::io::stdout().lock();
+    // the output is redirected (e.g. `app | tee log`) while the user still types in a terminal
+    let plain = !sol.is_terminal() && std::io::stdin().is_terminal();
+    let alt_screen = alt_screen && !plain;
+    if !plain {
+        context.query(&mut sol);
+    }
+    context::save(context);
+
+    let mut i = Input::new(prompt, alt_screen);
+    i.plain = plain;
+    let term = Terminal {
+        sol,
+        alt_screen,
+        restored: false,
+        writes: 0,
+        mock: None,
+    };
+
+    (term, i, History::new(), String::new())
+}
+
+// enters the alternate screen if asked to then renders the first prompt
+pub(crate) fn show(term: &mut Terminal, i: &Input) -> Result<(), RagoutError> {
+    // the pasted texts get told apart from the typed keys, see Input::paste
+    if !i.plain {
+        term.write_all(b"\x1b[?2004h")?;
+    }
+    if term.alt_screen {
+        set_alt_screen(term, true)?;
+        term.write_all(b"\x1b[1;1f")?;
+    }
+
+    i.write_prompt(term)
+}
+
+/// The stdout of a terminal set up by [`init`], which gets restored (see [`deinit`]) when the
+/// guard is dropped, be it at the end of the program or while unwinding from a panic
+///
+/// derefs to the [`StdoutLock`] the rendering fns take
+#[derive(Debug)]
+pub struct Terminal {
+    sol: StdoutLock<'static>,
+    /// whether the alternate screen is to be left on restore
+    pub alt_screen: bool,
+    restored: bool,
+    // the writes gone through the guard, the accesses to the StdoutLock included
+    writes: u64,
+    // what a headless Terminal renders to instead of stdout
+    mock: Option<MockTerminal>,
+}
+
+impl Terminal {
+    /// Creates a Terminal rendering to a [`MockTerminal`] of the given size instead of stdout,
+    /// which never gets touched nor restored, see [`crate::Editor::headless`]
+    ///
+    /// the StdoutLock it derefs to is the one of stdout still
+    pub fn headless(columns: usize, rows: usize) -> Self {
+        Self {
+            sol: std::io::stdout().lock(),
+            alt_screen: false,
+            restored: true,
+            writes: 0,
+            mock: Some(MockTerminal::new(columns, rows)),
+        }
+    }
+
+    /// Returns true if the Terminal renders to a [`MockTerminal`], see [`Terminal::headless`]
+    pub fn is_headless(&self) -> bool {
+        self.mock.is_some()
+    }
+
+    /// Returns the MockTerminal a headless Terminal renders to
+    pub fn mock(&mut self) -> Option<&mut MockTerminal> {
+        self.mock.as_mut()
+    }
+
+    /// Restores the terminal right away instead of on drop, does nothing the second time
+    pub fn restore(&mut self) {
+        if !std::mem::replace(&mut self.restored, true) {
+            deinit(&mut self.sol, self.alt_screen);
+        }
+    }
+}
+
+impl std::ops::Deref for Terminal {
+    type Target = StdoutLock<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.sol
+    }
+}
+
+impl std::ops::DerefMut for Terminal {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.writes += 1;
+        &mut self.sol
+    }
+}
+
+impl Write for Terminal {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writes += 1;
+        match self.mock.as_mut() {
+            Some(mock) => mock.write(buf),
+            None => self.sol.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.mock.as_mut() {
+            Some(mock) => mock.flush(),
+            None => self.sol.flush(),
+        }
+    }
+}
+
+impl TerminalWriter for Terminal {
+    fn columns(&self) -> usize {
+        match &self.mock {
+            Some(mock) => mock.columns(),
+            None => term_width(),
+        }
+    }
 
-    if alt_screen {
-        _ = sol.write(b"\x1b[?1049h");
-        _ = sol.write(b"\x1b[1;1f");
+    fn written(&self) -> Option<u64> {
+        Some(self.writes)
     }
+}
+
+/// Where an [`Input`] gets rendered: the stdout of the terminal, or a
+/// [`crate::MockTerminal`] recording what would have been displayed
+///
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait TerminalWriter: Write {
+    /// Returns the number of columns the rendered line wraps at, the terminal width by default
+    fn columns(&self) -> usize {
+        term_width()
+    }
+
+    /// Returns how many writes went through the terminal, for a render to tell that nothing
+    /// else got drawn since the last one and only redraw what changed; None, the default,
+    /// makes every render redraw the whole line
+    fn written(&self) -> Option<u64> {
+        None
+    }
+}
 
-    let i = Input::new(prompt, alt_screen);
-    i.write_prompt(&mut sol);
+impl TerminalWriter for StdoutLock<'_> {}
 
-    (sol, i, History::new(), String::new())
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+/// Leaves the terminal raw mode and the alternate screen (if alt_screen is true) entered by
+/// [`init`], restoring the cursor shape, and the console output mode on windows
+///
+/// the termios flags, cursor visibility and style and the mouse and paste modes found by
+/// [`init`] are then put back, see [`crate::context::TermContext`]
+///
+/// the [`Terminal`] returned by [`init`] calls it on drop
+pub fn deinit(sol: &mut StdoutLock, alt_screen: bool) {
+    // gives the cursor back in whatever shape the user configured, with the bracketed paste
+    // off
+    if sol.is_terminal() {
+        _ = sol.write(CursorShape::Default.escape().as_bytes());
+        _ = sol.write(b"\x1b[?2004l");
+    }
+    if alt_screen && sol.is_terminal() {
+        _ = set_alt_screen(sol, false);
+    }
+    _ = sol.flush();
+    _ = default_backend().disable_raw_mode();
+    if let Some(context) = context::take() {
+        _ = context.restore(sol);
+    }
+    #[cfg(windows)]
+    crate::windows::restore_output_mode();
 }
 
 /// A struct that implements the user input movement and deletion logic inside the terminal raw
 /// mode
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Input {
-    pub values: Vec<char>,
+    pub values: Text,
     pub cursor: usize,
-    #[cfg(any(debug_assertions, feature = "debug_logs"))]
-    pub debug_log: std::fs::File,
+    /// where the debug log records go, disabled by default
+    pub debug_log: LogSink,
     pub prompt: String,
     pub alt_screen: bool,
+    pub indicator: Indicator,
+    pub mode: Mode,
+    pub mode_indicator: Indicator,
+    /// whether the cursor shape follows the editing mode, see [`Mode::cursor_shape`]
+    pub cursor_shapes: bool,
+    /// makes the jumps and the word deletions stop at the `camelCase` humps and the `snake_case`
+    /// underscores, see [`Input::subword_left`]
+    pub subwords: bool,
+    pub bell: Bell,
+    pub changes: usize,
+    /// marks the current line as secret, it then always gets redacted from the debug logs
+    pub sensitive: bool,
+    pub redaction: Redaction,
+    /// the prompt shown on the lines continuing a line that ended with a backslash
+    pub continuation_prompt: String,
+    /// the other end of the selection, which spans from the mark to the cursor
+    pub mark: Option<usize>,
+    /// the last jump to a char, repeated by [`Input::repeat_find`]
+    pub last_find: Option<CharSearch>,
+    /// renders without any escape sequence: the prompt gets printed once per line and the
+    /// cursor is never synced, set by [`init`] when stdout is not a terminal
+    pub plain: bool,
+    // whether the prompt of the current line was printed in plain mode
+    pub(crate) prompted: Cell<bool>,
+    /// a message rendered on the status line, after the status line indicators
+    pub message: Option<String>,
+    /// text previewed dimmed at the cursor without being part of the values,
+    /// e.g. the completion candidate selected in the menu
+    pub ghost: Option<String>,
+    /// previews the rest of the newest history entry starting with the line being typed,
+    /// accepted with Right or End, see [`Input::suggest`]
+    pub autosuggest: bool,
+    /// the autosuggestion of the line, previewed dimmed after its end
+    pub suggestion: Option<Suggestion>,
+    /// the diagnostics of the line, underlined in it with the one at the cursor explained on
+    /// the status line, dropped once the line is submitted
+    pub diagnostics: Vec<Diagnostic>,
+    /// the styles of the chars of the line, as char ranges, see [`Input::highlight`]
+    pub highlights: Vec<(Range<usize>, Style)>,
+    /// the zen layout of launcher style applications: in the alternate screen, the line is
+    /// rendered vertically centered after this many blank columns, with the status line
+    /// right under it
+    pub zen: Option<usize>,
+    /// the texts killed or copied from the line, yanked back by [`Input::yank`]
+    pub kill_ring: KillRing,
+    /// the edits of the line, reverted by [`Input::undo`]
+    pub undo: UndoStack,
+    /// lets the values span several lines: Alt-Enter, or Enter after a continuation backslash,
+    /// inserts a newline rendered as a line break, and Up/Down move between the lines before
+    /// going through the history
+    pub multiline: bool,
+    /// enables the vi bindings: Esc leaves the insert mode for the normal mode, where the typed
+    /// chars are commands, see [`Input::vi_command`]
+    pub vi: bool,
+    // the vi command being typed in the normal mode
+    pub(crate) vi_pending: ViPending,
+    // what every grapheme of a masked line is displayed as, None when it displays nothing,
+    // see set_mask
+    mask: Option<Option<char>>,
+    // the row the last render left the terminal cursor on, counted from the first row of the
+    // prompt
+    pub(crate) rendered_row: Cell<usize>,
+    // the terminal width of the last render, 0 before the first one
+    pub(crate) rendered_width: Cell<usize>,
+    // the last row the last render took, counted from the first row of the prompt
+    rendered_end: Cell<usize>,
+    // what the last render drew when the line took a single row
+    pub(crate) drawn: RefCell<Option<Drawn>>,
+}
+
+/// How the user gets notified of an invalid action
+/// (e.g. backspace at column 0, failed history search, rejected input)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Bell {
+    /// writes the terminal bell char `\a`
+    #[default]
+    Audible,
+    /// briefly flashes the screen in reverse video
+    Visual,
+    /// does nothing
+    None,
+}
+
+/// A jump to the next or previous occurrence of a char, vi's f/F/t/T motions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharSearch {
+    pub c: char,
+    pub forward: bool,
+    /// stops right before the char (after it when going backward) instead of on it
+    pub till: bool,
+}
+
+/// The editing mode of an Input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// typed chars get inserted at the cursor
+    #[default]
+    Insert,
+    /// typed chars are interpreted as commands (vi normal mode)
+    Normal,
+    /// typed chars replace the chars under the cursor
+    Overwrite,
+}
+
+impl Mode {
+    /// Returns the badge displayed by the mode indicator
+    pub fn badge(&self) -> &'static str {
+        match self {
+            Self::Insert => "[I]",
+            Self::Normal => "[N]",
+            Self::Overwrite => "[R]",
+        }
+    }
+
+    /// Returns the cursor shape displayed in the mode
+    pub fn cursor_shape(&self) -> CursorShape {
+        match self {
+            Self::Insert => CursorShape::Bar,
+            Self::Normal => CursorShape::Block,
+            Self::Overwrite => CursorShape::Underline,
+        }
+    }
+}
+
+/// The shape of the terminal cursor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CursorShape {
+    /// whatever shape the user configured their terminal with
+    #[default]
+    Default,
+    Block,
+    Underline,
+    Bar,
+}
+
+impl CursorShape {
+    /// Returns the DECSCUSR escape sequence setting the cursor to the shape (steady)
+    pub fn escape(&self) -> &'static str {
+        match self {
+            Self::Default => "\x1b[0 q",
+            Self::Block => "\x1b[2 q",
+            Self::Underline => "\x1b[4 q",
+            Self::Bar => "\x1b[6 q",
+        }
+    }
+}
+
+/// Where the cursor position indicator of an Input gets rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Indicator {
+    /// The indicator is not rendered
+    #[default]
+    Hidden,
+    /// The indicator is rendered right before the prompt
+    Prompt,
+    /// The indicator is rendered right aligned on the input line
+    RightPrompt,
+    /// The indicator is rendered on the line below the input line
+    StatusLine,
+}
+
+/// The position of the Input cursor as displayed by the position indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// 1 based line of the cursor, None outside of the multiline mode
+    pub line: Option<usize>,
+    /// 1 based column of the cursor in its line
+    pub column: usize,
+    /// number of chars in the Input values
+    pub len: usize,
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}/{}", line, self.column, self.len),
+            None => write!(f, "{}/{}", self.column, self.len),
+        }
+    }
+}
+
+/// A terminal cell relative to the first cell of the input line, which holds the start of the
+/// prompt, see [`Input::screen_cell`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ScreenCell {
+    /// 0 based row, counted from the row of the prompt
+    pub row: usize,
+    /// 0 based column
+    pub column: usize,
 }
 
 impl Input {
     /// Creates a new Input instance
     pub fn new(prompt: &str, alt_screen: bool) -> Self {
         Self {
-            #[cfg(any(debug_assertions, feature = "debug_logs"))]
-            debug_log: std::fs::File::create("resources/logs/terminal/input").unwrap_or_else(
-                |_| {
-                    std::fs::create_dir_all("resources/logs/terminal").unwrap();
-                    std::fs::File::create("resources/logs/terminal/input").unwrap()
-                },
-            ),
-            values: Vec::new(),
+            debug_log: LogSink::default(),
+            values: Text::new(),
             cursor: 0,
             prompt: prompt.to_owned(),
             alt_screen,
+            indicator: Indicator::Hidden,
+            mode: Mode::Insert,
+            mode_indicator: Indicator::Hidden,
+            cursor_shapes: false,
+            subwords: false,
+            bell: Bell::Audible,
+            changes: 0,
+            sensitive: false,
+            redaction: Redaction::default(),
+            continuation_prompt: "> ".to_string(),
+            mark: None,
+            last_find: None,
+            plain: false,
+            prompted: Cell::new(false),
+            message: None,
+            ghost: None,
+            autosuggest: false,
+            suggestion: None,
+            diagnostics: Vec::new(),
+            highlights: Vec::new(),
+            zen: None,
+            kill_ring: KillRing::default(),
+            undo: UndoStack::default(),
+            multiline: false,
+            vi: false,
+            vi_pending: ViPending::default(),
+            mask: None,
+            rendered_row: Cell::new(0),
+            rendered_end: Cell::new(0),
+            drawn: RefCell::new(None),
+            rendered_width: Cell::new(0),
         }
     }
 
+    /// Inserts a pasted text at the cursor: its line breaks become newlines in the multiline
+    /// mode and spaces otherwise, its tabs become 4 spaces and its other control chars get
+    /// dropped, so that a paste never submits the line nor runs the key bindings; a single
+    /// trailing line break is left out
+    ///
+    /// returns false if there was nothing left to insert
+    pub fn paste(&mut self, text: &str) -> bool {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let text = text.strip_suffix('\n').unwrap_or(&text);
+        let start = self.cursor;
+        let multiline = self.multiline;
+        self.put_str(text.chars().flat_map(|c| match c {
+            '\n' if multiline => std::iter::repeat_n('\n', 1),
+            '\n' => std::iter::repeat_n(' ', 1),
+            '\t' => std::iter::repeat_n(' ', 4),
+            c if c.is_control() => std::iter::repeat_n(c, 0),
+            c => std::iter::repeat_n(c, 1),
+        }));
+
+        self.cursor != start
+    }
+
     // NOTE: should input.values not be a byte vec instead of a char vec?
     /// Adds inputted char to Input values at cursor position then increments Input cursor
+    ///
+    /// a combining char joins the grapheme cluster before the cursor, which the cursor then
+    /// stays after
     pub fn put_char(&mut self, c: char) {
         match self.values.is_empty() {
             true => {
@@ -102,50 +614,197 @@ impl Input {
                 }
             },
         }
+        self.changes += 1;
+    }
+
+    // puts every char like put_char, in a single splice so that a long text doesn't move the
+    // rest of the line once per char; the chars get collected first for the splice to know
+    // how many there are
+    pub(crate) fn put_str(&mut self, chars: impl IntoIterator<Item = char>) {
+        let chars = chars.into_iter().collect::<Vec<char>>();
+        let inserted = chars.len();
+        self.values.splice(self.cursor..self.cursor, chars);
+        self.cursor += inserted;
+        self.changes += inserted;
+    }
+
+    /// Masks the line for the secrets: every grapheme gets displayed as the mask char, or not
+    /// at all without one, while the values keep the real text; the masked lines are sensitive
+    /// and never get pushed to the History, see [`Input::unmask`]
+    pub fn set_mask(&mut self, mask: Option<char>) {
+        self.mask = Some(mask);
+        self.sensitive = true;
+    }
+
+    /// Displays the line in clear again, the line stays sensitive
+    pub fn unmask(&mut self) {
+        self.mask = None;
     }
 
-    // TODO: multiline input
-    // WARN: do NOT touch this Input implementation
-    // the fns other than write are not to be touched
+    /// Returns true if the line is masked, see [`Input::set_mask`]
+    pub fn is_masked(&self) -> bool {
+        self.mask.is_some()
+    }
+
+    /// Returns true if the Input values were edited since the last submit or
+    /// [`Input::mark_clean`] call
+    pub fn is_dirty(&self) -> bool {
+        self.changes > 0
+    }
+
+    /// Resets the Input change counter, call it after recalling a history entry into the Input
+    /// values
+    pub fn mark_clean(&mut self) {
+        self.changes = 0;
+    }
 
     /// Pushs Input values to history, then binds a [`String`] of the Input values to user_input and resets both Input cursor and values
     pub fn cr_lf(&mut self, h: &mut History, user_input: &mut String) {
-        h.push(self.values.to_vec());
+        // a masked line is a secret
+        if self.mask.is_none() {
+            h.push(self.values.to_vec());
+        }
         *user_input = self.values.drain(..).collect::<String>();
         self.cursor = 0;
+        self.changes = 0;
+        self.diagnostics.clear();
+        self.highlights.clear();
+        self.undo.clear();
+        // every line starts in the insert mode
+        if self.vi {
+            self.set_mode(Mode::Insert);
+        }
     }
 
-    /// Deletes the char behind the cursor position in the Input values
-    pub fn backspace(&mut self) {
+    /// Returns true if the Input values end with an unescaped backslash,
+    /// meaning the line continues on the next one
+    pub fn continues(&self) -> bool {
+        self.values.iter().rev().take_while(|c| **c == '\\').count() % 2 == 1
+    }
+
+    /// Deletes the grapheme cluster behind the cursor position in the Input values, e.g. a
+    /// letter along with its combining accents or a whole emoji sequence
+    ///
+    /// returns false if there was nothing to delete
+    pub fn backspace(&mut self) -> bool {
         if self.values.is_empty() || self.cursor == 0 {
-            return;
+            return false;
         }
-        if self.cursor > 0 {
-            self.values.remove(self.cursor - 1);
-            self.cursor -= 1;
+        // the chars before the cursor are read without moving the gap the typing left there
+        let start = prev_boundary(self.values.head(self.cursor), self.cursor);
+        self.values.drain(start..self.cursor);
+        self.cursor = start;
+        self.changes += 1;
+
+        true
+    }
+
+    /// Deletes the grapheme cluster under the cursor position in the Input values, like the
+    /// Delete key
+    ///
+    /// returns false if there was nothing to delete
+    pub fn delete_char(&mut self) -> bool {
+        if self.cursor == self.values.len() {
+            return false;
         }
+        let end = next_boundary(&self.values, self.cursor);
+        self.values.drain(self.cursor..end);
+        self.changes += 1;
+
+        true
     }
 
-    /// Moves the Input cursor one cell to the right
+    /// Moves the Input cursor one grapheme cluster to the right
     pub fn to_the_right(&mut self) -> bool {
         if self.values.is_empty() || self.cursor == self.values.len() {
             return false;
         }
-        self.cursor += 1;
+        self.cursor = next_boundary(&self.values, self.cursor);
 
         true
     }
 
-    /// Moves the Input cursor one cell to the left
+    /// Moves the Input cursor one grapheme cluster to the left
     pub fn to_the_left(&mut self) -> bool {
         if self.values.is_empty() || self.cursor == 0 {
             return false;
         }
-        self.cursor -= 1;
+        self.cursor = prev_boundary(&self.values, self.cursor);
 
         true
     }
 
+    /// Returns the lines of the Input values, split at the newlines of the multiline mode
+    pub fn lines(&self) -> Vec<&[char]> {
+        match self.multiline {
+            true => self.values.split(|c| *c == '\n').collect(),
+            false => vec![&self.values],
+        }
+    }
+
+    /// Returns the 0 based line and column (in chars) of the Input cursor, see [`Input::lines`]
+    pub fn line_col(&self) -> (usize, usize) {
+        let start = self.line_start(self.cursor);
+        let line = match self.multiline {
+            true => self.values[..start].iter().filter(|c| **c == '\n').count(),
+            false => 0,
+        };
+
+        (line, self.cursor - start)
+    }
+
+    /// Moves the Input cursor to the same column of the line above, or the end of that line
+    /// if it is shorter
+    ///
+    /// returns false outside of the multiline mode or on the first line
+    pub fn line_up(&mut self) -> bool {
+        let start = self.line_start(self.cursor);
+        if start == 0 {
+            return false;
+        }
+        let column = self.cursor - start;
+        self.cursor = (self.line_start(start - 1) + column).min(start - 1);
+
+        true
+    }
+
+    /// Moves the Input cursor to the same column of the line below, or the end of that line
+    /// if it is shorter
+    ///
+    /// returns false outside of the multiline mode or on the last line
+    pub fn line_down(&mut self) -> bool {
+        let end = self.line_end(self.cursor);
+        if end == self.values.len() {
+            return false;
+        }
+        let column = self.cursor - self.line_start(self.cursor);
+        self.cursor = (end + 1 + column).min(self.line_end(end + 1));
+
+        true
+    }
+
+    // the index of the first char of the line holding idx
+    fn line_start(&self, idx: usize) -> usize {
+        match self.multiline {
+            true => self.values[..idx]
+                .iter()
+                .rposition(|c| *c == '\n')
+                .map_or(0, |nl| nl + 1),
+            false => 0,
+        }
+    }
+
+    // the index of the newline ending the line holding idx, or the values len on the last line
+    fn line_end(&self, idx: usize) -> usize {
+        match self.multiline {
+            true => self.values[idx..]
+                .iter()
+                .position(|c| *c == '\n')
+                .map_or(self.values.len(), |nl| idx + nl),
+            false => self.values.len(),
+        }
+    }
+
     /// Moves Input cursor to the position after the last in Input values (which is values.len())
     pub fn to_end(&mut self) -> usize {
         let diff = self.values.len() - self.cursor;
@@ -168,23 +827,341 @@ impl Input {
 
     /// Clears all the Input values
     pub fn clear_line(&mut self) {
+        if !self.values.is_empty() {
+            self.changes += 1;
+        }
         self.cursor = 0;
         self.values.clear();
     }
 
-    /// clears the values of Input to the right of Input cursor
+    /// clears the values of Input to the right of Input cursor, they go on the kill ring
     pub fn clear_right(&mut self) {
-        for _ in self.cursor..self.values.len() {
-            self.values.pop();
+        let before = (self.cursor, self.changes);
+        if self.cursor < self.values.len() {
+            self.changes += 1;
+        }
+        let killed = self.values.drain(self.cursor..).collect::<String>();
+        self.killed(killed, false, before);
+    }
+
+    /// Deletes the chars from the cursor back to where [`Input::to_left_jump`] would land,
+    /// they go on the kill ring
+    ///
+    /// returns false if there was nothing to delete
+    pub fn delete_jump_left(&mut self) -> bool {
+        let (end, before) = (self.cursor, (self.cursor, self.changes));
+        self.to_left_jump();
+        if self.cursor == end {
+            return false;
+        }
+        let killed = self.values.drain(self.cursor..end).collect::<String>();
+        self.changes += 1;
+        self.killed(killed, true, before);
+
+        true
+    }
+
+    /// Deletes the chars from the cursor up to where [`Input::to_right_jump`] would land,
+    /// they go on the kill ring
+    ///
+    /// returns false if there was nothing to delete
+    pub fn delete_jump_right(&mut self) -> bool {
+        let (start, before) = (self.cursor, (self.cursor, self.changes));
+        self.to_right_jump();
+        if self.cursor == start {
+            return false;
+        }
+        let killed = self.values.drain(start..self.cursor).collect::<String>();
+        self.cursor = start;
+        self.changes += 1;
+        self.killed(killed, false, before);
+
+        true
+    }
+
+    /// Deletes the chars from the cursor to the end of the next word, emacs's `kill-word`
+    /// (Alt-d), the words being delimited by the stopper chars; they go on the kill ring
+    ///
+    /// returns false if there was nothing to delete
+    pub fn delete_word_right(&mut self) -> bool {
+        let (start, before) = (self.cursor, (self.cursor, self.changes));
+        let end = self.word_end_right(start);
+        if end == start {
+            return false;
+        }
+        let killed = self.values.drain(start..end).collect::<String>();
+        self.changes += 1;
+        self.killed(killed, false, before);
+
+        true
+    }
+
+    /// Deletes the chars from the start of the word before the cursor up to the cursor,
+    /// emacs's `backward-kill-word`; they go on the kill ring
+    ///
+    /// returns false if there was nothing to delete
+    pub fn delete_word_left(&mut self) -> bool {
+        let (end, before) = (self.cursor, (self.cursor, self.changes));
+        let start = self.word_start_left(end);
+        if start == end {
+            return false;
+        }
+        let killed = self.values.drain(start..end).collect::<String>();
+        self.cursor = start;
+        self.changes += 1;
+        self.killed(killed, true, before);
+
+        true
+    }
+
+    /// Swaps the char before the cursor with the one under it then moves the cursor past
+    /// both, at the end of the line the last two chars get swapped instead, emacs's Ctrl-T
+    ///
+    /// returns false if there are not two chars to swap
+    pub fn transpose_chars(&mut self) -> bool {
+        let v = &self.values;
+        let mid = match self.cursor == v.len() {
+            true => prev_boundary(v, self.cursor),
+            false => self.cursor,
+        };
+        let (start, end) = (prev_boundary(v, mid), next_boundary(v, mid));
+        if start == mid || mid == end {
+            return false;
+        }
+        self.values[start..end].rotate_left(mid - start);
+        self.cursor = end;
+        self.changes += 1;
+
+        true
+    }
+
+    /// Swaps the word before the cursor with the one after it then moves the cursor to the
+    /// end of both, a cursor inside a word swaps that word with the next one and at the end of
+    /// the line the last two words get swapped, emacs's Alt-t
+    ///
+    /// returns false if there are not two words to swap
+    pub fn transpose_words(&mut self) -> bool {
+        let v = &self.values;
+        let mut idx = self.cursor;
+        let inside = idx > 0 && !Self::is_separator(&v[idx - 1]);
+        if inside && v.get(idx).is_some_and(|c| !Self::is_separator(c)) {
+            idx = self.word_end_right(idx);
+        }
+        let mut right_end = self.word_end_right(idx);
+        if right_end == idx || !v[idx..right_end].iter().any(|c| !Self::is_separator(c)) {
+            right_end = self.word_end_left(v.len());
+        }
+        let right_start = self.word_start_left(right_end);
+        let left_end = self.word_end_left(right_start);
+        let left_start = self.word_start_left(left_end);
+        if left_start == left_end || right_start == right_end {
+            return false;
+        }
+        let right = v[right_start..right_end].to_vec();
+        let left = v[left_start..left_end].to_vec();
+        let between = v[left_end..right_start].to_vec();
+        self.values.splice(
+            left_start..right_end,
+            right.into_iter().chain(between).chain(left),
+        );
+        self.cursor = right_end;
+        self.changes += 1;
+
+        true
+    }
+
+    fn is_separator(c: &char) -> bool {
+        Self::is_stopper(c) || c.is_whitespace()
+    }
+
+    // the end of the word after idx, the separators before it included
+    fn word_end_right(&self, idx: usize) -> usize {
+        let v = &self.values;
+        let mut idx = idx;
+        while idx < v.len() && Self::is_separator(&v[idx]) {
+            idx += 1;
+        }
+        while idx < v.len() && !Self::is_separator(&v[idx]) {
+            idx += 1;
+        }
+
+        idx
+    }
+
+    // the start of the word before idx, the separators after it included
+    fn word_start_left(&self, idx: usize) -> usize {
+        let v = &self.values;
+        let mut idx = idx;
+        while idx > 0 && Self::is_separator(&v[idx - 1]) {
+            idx -= 1;
+        }
+        while idx > 0 && !Self::is_separator(&v[idx - 1]) {
+            idx -= 1;
         }
+
+        idx
+    }
+
+    // the end of the word before idx, the separators after it excluded
+    fn word_end_left(&self, idx: usize) -> usize {
+        let v = &self.values;
+        let mut idx = idx;
+        while idx > 0 && Self::is_separator(&v[idx - 1]) {
+            idx -= 1;
+        }
+
+        idx
     }
 
-    /// clears the values of Input to the left of Input cursor
+    /// clears the values of Input to the left of Input cursor, they go on the kill ring
     pub fn clear_left(&mut self) {
-        for _ in 0..self.cursor {
-            self.values.remove(0);
+        let before = (self.cursor, self.changes);
+        if self.cursor > 0 {
+            self.changes += 1;
         }
+        let killed = self.values.drain(..self.cursor).collect::<String>();
         self.cursor = 0;
+        self.killed(killed, true, before);
+    }
+
+    /// Replaces the Input values with the given text and moves the cursor to its end
+    pub fn set_text(&mut self, text: &str) {
+        self.values = text.chars().collect();
+        self.cursor = self.values.len();
+        self.changes += 1;
+    }
+
+    /// Moves the Input cursor to the given position, clamped to the Input values length
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.values.len());
+    }
+
+    /// Replaces a `\u{HEX}` escape right before the cursor with the char of that codepoint
+    ///
+    /// returns false if there is no such escape or it is not a valid codepoint
+    pub fn expand_codepoint(&mut self) -> bool {
+        let before = &self.values[..self.cursor];
+        if before.last() != Some(&'}') {
+            return false;
+        }
+        let Some(start) = before.iter().rposition(|c| *c == '\\') else {
+            return false;
+        };
+        let escape = before[start..].iter().collect::<String>();
+        let Some(c) = escape
+            .strip_prefix("\\u{")
+            .and_then(|hex| hex.strip_suffix('}'))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+        else {
+            return false;
+        };
+        self.values.splice(start..self.cursor, [c]);
+        self.cursor = start + 1;
+        self.changes += 1;
+
+        true
+    }
+
+    /// Returns the range of the selected chars, between the mark and the cursor
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let mark = self.mark?.min(self.values.len());
+        let range = mark.min(self.cursor)..mark.max(self.cursor);
+
+        (!range.is_empty()).then_some(range)
+    }
+
+    /// Selects the word touching the char at idx, words being delimited by the stopper chars
+    ///
+    /// returns false if there is no word there
+    pub fn select_word(&mut self, idx: usize) -> bool {
+        let range = self.word_range(idx.min(self.values.len()));
+        if range.is_empty() {
+            return false;
+        }
+        self.mark = Some(range.start);
+        self.cursor = range.end;
+
+        true
+    }
+
+    /// Returns the index of the char displayed at the given 0 based terminal column of the input
+    /// line, clamped to the Input values
+    pub fn index_at(&self, column: usize) -> usize {
+        let mut cells = self.prompt_len();
+        clusters(&self.values)
+            .find(|(_, width)| {
+                cells += self.shown_cells(*width);
+                cells > column
+            })
+            .map_or(self.values.len(), |(range, _)| range.start)
+    }
+
+    /// Moves the cursor onto the next occurrence of c after the cursor (vi's `f`),
+    /// or right before it if till is true (vi's `t`)
+    ///
+    /// returns false if c doesn't occur after the cursor
+    pub fn find_char_forward(&mut self, c: char, till: bool) -> bool {
+        self.find_char(CharSearch {
+            c,
+            forward: true,
+            till,
+        })
+    }
+
+    /// Moves the cursor onto the previous occurrence of c before the cursor (vi's `F`),
+    /// or right after it if till is true (vi's `T`)
+    ///
+    /// returns false if c doesn't occur before the cursor
+    pub fn find_char_backward(&mut self, c: char, till: bool) -> bool {
+        self.find_char(CharSearch {
+            c,
+            forward: false,
+            till,
+        })
+    }
+
+    /// Repeats the last char jump, in the opposite direction if reverse is true
+    /// (vi's `;` and `,`)
+    pub fn repeat_find(&mut self, reverse: bool) -> bool {
+        let Some(mut search) = self.last_find else {
+            return false;
+        };
+        search.forward ^= reverse;
+        let found = self.find_char(search);
+        // the repeated jump is remembered in its original direction
+        if let Some(last) = self.last_find.as_mut() {
+            last.forward ^= reverse;
+        }
+
+        found
+    }
+
+    // the cursor is on the char at values[cursor], a till jump skips the char right next to
+    // the cursor so that repeating it moves on to the next occurrence
+    pub(crate) fn find_char(&mut self, search: CharSearch) -> bool {
+        self.last_find = Some(search);
+        let skip = search.till as usize;
+        let found = match search.forward {
+            true => self
+                .values
+                .iter()
+                .enumerate()
+                .skip(self.cursor + 1 + skip)
+                .find(|(_, c)| **c == search.c)
+                .map(|(idx, _)| idx - skip),
+            false => self.values[..self.cursor.saturating_sub(skip)]
+                .iter()
+                .rposition(|c| *c == search.c)
+                .map(|idx| idx + skip),
+        };
+        match found {
+            Some(idx) => {
+                self.cursor = idx;
+                true
+            }
+            None => false,
+        }
     }
 
     const STOPPERS: [char; 11] = ['/', ' ', '-', '_', ',', '"', '\'', ';', ':', '.', ','];
@@ -194,6 +1171,10 @@ impl Input {
         if self.cursor == self.values.len() {
             return;
         }
+        if self.subwords {
+            self.cursor = self.subword_right();
+            return;
+        }
 
         match self.values[if self.cursor + 1 < self.values.len() {
             self.cursor + 1
@@ -217,106 +1198,892 @@ impl Input {
         }
     }
 
+    fn is_stopper(c: &char) -> bool {
+        Self::STOPPERS.contains(c)
+    }
+
+    // whether c is inside a subword without starting an uppercase hump
+    fn is_subword_tail(c: &char) -> bool {
+        !c.is_whitespace() && !Self::is_stopper(c) && !c.is_uppercase()
+    }
+
+    /// Returns the index of the start of the subword before the cursor, the subwords being
+    /// delimited by the whitespaces, the stopper chars and the uppercase humps, so that
+    /// `HTTPServer_conf` is made of `HTTP`, `Server` and `conf`
+    pub fn subword_left(&self) -> usize {
+        let v = &self.values;
+        let mut idx = self.cursor;
+        while idx > 0 && (v[idx - 1].is_whitespace() || Self::is_stopper(&v[idx - 1])) {
+            idx -= 1;
+        }
+        let tail_end = idx;
+        while idx > 0 && Self::is_subword_tail(&v[idx - 1]) {
+            idx -= 1;
+        }
+        match idx < tail_end {
+            // the hump of a camel case subword
+            true if idx > 0 && v[idx - 1].is_uppercase() => idx -= 1,
+            true => (),
+            // a run of uppercase chars
+            false => {
+                while idx > 0 && v[idx - 1].is_uppercase() {
+                    idx -= 1;
+                }
+            }
+        }
+
+        idx
+    }
+
+    /// Returns the index of the end of the subword after the cursor, see [`Input::subword_left`]
+    pub fn subword_right(&self) -> usize {
+        let v = &self.values;
+        let mut idx = self.cursor;
+        while idx < v.len() && (v[idx].is_whitespace() || Self::is_stopper(&v[idx])) {
+            idx += 1;
+        }
+        let start = idx;
+        while idx < v.len() && v[idx].is_uppercase() {
+            idx += 1;
+        }
+        match idx - start > 1 && v.get(idx).is_some_and(Self::is_subword_tail) {
+            // the last uppercase char is the hump of the next subword
+            true => idx -= 1,
+            false => {
+                while idx < v.len() && Self::is_subword_tail(&v[idx]) {
+                    idx += 1;
+                }
+            }
+        }
+
+        idx
+    }
+
+    // expands idx into the range of the run of non stopper chars touching it
+    fn word_range(&self, idx: usize) -> Range<usize> {
+        let mut start = idx;
+        while start > 0 && !Self::is_stopper(&self.values[start - 1]) {
+            start -= 1;
+        }
+        let mut end = idx;
+        while end < self.values.len() && !Self::is_stopper(&self.values[end]) {
+            end += 1;
+        }
+
+        start..end
+    }
+
+    fn word(&self, range: Range<usize>) -> Option<(Range<usize>, String)> {
+        match range.is_empty() {
+            true => None,
+            false => Some((range.clone(), self.values[range].iter().collect())),
+        }
+    }
+
+    /// Returns the char range and the text of the word under (or right behind) the Input
+    /// cursor, words being delimited by the stopper chars
+    pub fn word_at_cursor(&self) -> Option<(Range<usize>, String)> {
+        self.word(self.word_range(self.cursor))
+    }
+
+    /// Returns the char range and the text of the word before the one at the Input cursor
+    pub fn prev_word(&self) -> Option<(Range<usize>, String)> {
+        let current = self.word_range(self.cursor);
+        let mut idx = current.start;
+        while idx > 0 && Self::is_stopper(&self.values[idx - 1]) {
+            idx -= 1;
+        }
+
+        self.word(self.word_range(idx))
+            .filter(|(r, _)| *r != current)
+    }
+
+    /// Returns the char range and the text of the word after the one at the Input cursor
+    pub fn next_word(&self) -> Option<(Range<usize>, String)> {
+        let current = self.word_range(self.cursor);
+        let mut idx = current.end;
+        while idx < self.values.len() && Self::is_stopper(&self.values[idx]) {
+            idx += 1;
+        }
+
+        self.word(self.word_range(idx))
+            .filter(|(r, _)| *r != current)
+    }
+
     /// Syncs Input's internal state to a movement of the user input cursor to the left, stops at the first stopper char
     pub fn to_left_jump(&mut self) {
         if self.cursor == 0 {
             return;
         }
+        if self.subwords {
+            self.cursor = self.subword_left();
+            return;
+        }
+
+        match self.values[self.cursor - 1] == ' ' {
+            true => {
+                while self.cursor > 0 && self.values[self.cursor - 1] == ' ' {
+                    self.cursor -= 1;
+                }
+            }
+            false => {
+                while self.cursor > 1 && !Self::STOPPERS.contains(&self.values[self.cursor - 1]) {
+                    self.cursor -= 1;
+                }
+                self.cursor -= 1;
+            }
+        }
+    }
+}
+
+impl Input {
+    /// Changes the Input prompt value to the provided string
+    pub fn overwrite_prompt(&mut self, new_prompt: &str) {
+        self.prompt.clear();
+        self.prompt.push_str(new_prompt);
+    }
+
+    /// Renders the Input prompt followed by the Input values on a clean line,
+    /// in plain mode only the prompt gets printed, once per line
+    ///
+    /// a line taking a single row that was rendered last, with nothing else written to the
+    /// terminal since (see [`TerminalWriter::written`]), only gets the cells that changed
+    /// redrawn
+    ///
+    /// # Errors
+    /// Returns a [`RagoutError::IoError`] if writing to the terminal failed
+    pub fn write_prompt(&self, sol: &mut dyn TerminalWriter) -> Result<(), RagoutError> {
+        if self.plain {
+            if !self.prompted.replace(true) {
+                sol.write_all(&str_to_bytes(&self.prompt))?;
+                sol.flush()?;
+            }
+            return Ok(());
+        }
+        let drawn = self.take_drawn(sol);
+        let [prefix, right, status] = self.indicators();
+        let width = sol.columns();
+        let previous = self.rendered_width.replace(width);
+        let resized = previous != 0 && previous != width;
+        let line = format!("{}{}{}", prefix, self.prompt, self.styled_values());
+        let end = self.end_cell(width);
+        let single = !resized && end.row == 0 && self.zen_row().is_none() && status.is_empty();
+        if let Some(drawn) = drawn.filter(|_| single && self.rendered_end.get() == 0) {
+            return self.write_changed(sol, drawn, units(&line), right);
+        }
+
+        // the terminal re-wrapped the rows of the line to its new width, the terminal cursor now
+        // is on the row of the Input cursor at that width
+        if resized {
+            self.rendered_row
+                .set(self.screen_cell(self.cursor, width).row);
+        }
+        match self.zen_row() {
+            Some(row) => sol.write_all(format!("\x1b[{};1H", row).as_bytes())?,
+            None => self.to_first_row(sol)?,
+        }
+        // the rows of a line that got shorter (or re-wrapped) go too
+        match self.multiline || resized || self.rendered_end.get() > 0 {
+            true => sol.write_all(b"\r\x1b[J")?,
+            false => sol.write_all(b"\x1b[2K\r")?,
+        }
+        self.write_margin(sol)?;
+        sol.write_all(&str_to_bytes(&line))?;
+        // a line filling its last row leaves the terminal cursor pending at the last column
+        // instead of wrapping it
+        if end.row > 0 && end.column == 0 && self.values.last() != Some(&'\n') {
+            sol.write_all(b" \r")?;
+        }
+        self.rendered_row.set(end.row);
+        self.rendered_end.set(end.row);
+        // the right prompt only goes on a line taking a single row
+        if !right.is_empty() && end.row == self.screen_cell(0, width).row {
+            self.write_right(sol, &right)?;
+        }
+        if !status.is_empty() {
+            self.write_status(sol, &status)?;
+        }
+        sol.flush()?;
+        if let Some(writes) = sol.written().filter(|_| single) {
+            self.drawn.replace(Some(Drawn {
+                units: units(&line),
+                right,
+                writes,
+            }));
+        }
+
+        Ok(())
+    }
+
+    // redraws the cells of the single row line that changed since it was drawn, and the right
+    // prompt along with them
+    fn write_changed(
+        &self,
+        sol: &mut dyn TerminalWriter,
+        drawn: Drawn,
+        units: Vec<(String, usize)>,
+        right: String,
+    ) -> Result<(), RagoutError> {
+        self.to_first_row(sol)?;
+        let prompted = !right.is_empty() || !drawn.right.is_empty();
+        let mut escapes = diff(&drawn.units, &units, prompted);
+        if escapes.is_empty() && right != drawn.right {
+            let cells = units.iter().map(|(_, cells)| cells).sum::<usize>();
+            escapes = format!("\x1b[{}G\x1b[K", cells + 1);
+        }
+        if escapes.is_empty() {
+            self.drawn.replace(Some(drawn));
+            return Ok(());
+        }
+
+        sol.write_all(&str_to_bytes(&escapes))?;
+        self.rendered_row.set(0);
+        if !right.is_empty() {
+            self.write_right(sol, &right)?;
+        }
+        sol.flush()?;
+        if let Some(writes) = sol.written() {
+            self.drawn.replace(Some(Drawn {
+                units,
+                right,
+                writes,
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Re-renders the whole Input line then syncs the terminal cursor to the Input cursor
+    ///
+    /// # Errors
+    /// Same as [`Input::write_prompt`]
+    pub fn render(&self, sol: &mut dyn TerminalWriter) -> Result<(), RagoutError> {
+        self.write_prompt(sol)?;
+        if self.plain {
+            return Ok(());
+        }
+        let written = sol.written();
+        if self.cursor_shapes {
+            sol.write_all(self.mode.cursor_shape().escape().as_bytes())?;
+        }
+        let cell = self.screen_cell(self.cursor, sol.columns());
+        self.move_to(sol, cell)?;
+        sol.flush()?;
+        self.keep_drawn(written, sol);
+
+        Ok(())
+    }
+
+    // moves the terminal cursor from the row the last render left it on to the given cell
+    fn move_to(&self, sol: &mut dyn TerminalWriter, cell: ScreenCell) -> Result<(), RagoutError> {
+        let row = self.rendered_row.replace(cell.row);
+        match row.cmp(&cell.row) {
+            std::cmp::Ordering::Greater => {
+                sol.write_all(format!("\x1b[{}A", row - cell.row).as_bytes())?
+            }
+            std::cmp::Ordering::Less => {
+                sol.write_all(format!("\x1b[{}B", cell.row - row).as_bytes())?
+            }
+            std::cmp::Ordering::Equal => (),
+        }
+        sol.write_all(format!("\x1b[{}G", cell.column + 1).as_bytes())?;
+
+        Ok(())
+    }
+
+    // moves the terminal cursor up to the first row of the prompt
+    pub(crate) fn to_first_row(&self, sol: &mut dyn TerminalWriter) -> Result<(), RagoutError> {
+        let row = self.rendered_row.replace(0);
+        if row > 0 {
+            sol.write_all(format!("\x1b[{}A", row).as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    // moves the terminal cursor down to the last row of the line, where the line feeds go
+    pub(crate) fn to_last_row(&self, sol: &mut dyn TerminalWriter) -> Result<(), RagoutError> {
+        let end = self.end_cell(sol.columns());
+        let row = self.rendered_row.replace(end.row);
+        if end.row > row {
+            sol.write_all(format!("\x1b[{}B", end.row - row).as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    // the cell after the last displayed char, ghost text and suggestion included
+    fn end_cell(&self, width: usize) -> ScreenCell {
+        let end = self.screen_cell(self.values.len(), width);
+        let ghost = self
+            .preview()
+            .map_or(Vec::new(), |g| g.chars().collect::<Vec<char>>());
+
+        clusters(&ghost).fold(end, |cell, (_, cells)| {
+            Self::advance(cell, cells, width.max(2))
+        })
+    }
+
+    /// Returns the number of terminal cells the Input values take once displayed on a single
+    /// row, the control chars being shown in caret notation, see [`text_width`]
+    pub fn display_width(&self) -> usize {
+        self.shown_width(&self.values)
+    }
+
+    // the 1 based terminal column of the Input cursor
+    pub(crate) fn cursor_column(&self) -> usize {
+        self.prompt_len() + self.shown_width(&self.values[..self.cursor]) + 1
+    }
+
+    // the cells a grapheme taking cells cells is displayed in, once masked
+    fn shown_cells(&self, cells: usize) -> usize {
+        match self.mask {
+            Some(Some(mask)) => text_width(&mask.to_string()),
+            Some(None) => 0,
+            None => cells,
+        }
+    }
+
+    // the cells some chars of the values are displayed in
+    fn shown_width(&self, chars: &[char]) -> usize {
+        clusters(chars)
+            .map(|(_, cells)| self.shown_cells(cells))
+            .sum()
+    }
+
+    // the chars of the values in range as displayed: in caret notation for the control chars,
+    // a mask char per grapheme when masked
+    pub(crate) fn shown(&self, range: Range<usize>) -> String {
+        match self.mask {
+            None => visible(&self.values[range]),
+            Some(mask) => range
+                .filter(|idx| prev_boundary(&self.values, idx + 1) == *idx)
+                .filter_map(|_| mask)
+                .collect(),
+        }
+    }
+
+    /// Returns the cell where the char at idx of the Input values is displayed (idx being clamped
+    /// to the values), in a terminal of the given width, accounting for the prompt, the wide
+    /// chars and the line wrapping
+    ///
+    /// idx may be the values len, for the cell the cursor sits on at the end of the line
+    pub fn screen_cell(&self, idx: usize, width: usize) -> ScreenCell {
+        let width = width.max(2);
+        let mut cell = ScreenCell {
+            row: self.prompt_len() / width,
+            column: self.prompt_len() % width,
+        };
+        for (range, cells) in clusters(&self.values) {
+            let newline = self.multiline && self.mask.is_none() && self.values[range.start] == '\n';
+            let cells = self.shown_cells(cells);
+            if range.start >= idx {
+                // a wide char that does not fit at the end of a row gets wrapped whole
+                if range.start == idx && !newline && cell.column + cells > width {
+                    cell = ScreenCell {
+                        row: cell.row + 1,
+                        column: 0,
+                    };
+                }
+                break;
+            }
+            cell = match newline {
+                true => ScreenCell {
+                    row: cell.row + 1,
+                    column: self.margin() + text_width(&self.continuation_prompt),
+                },
+                false => Self::advance(cell, cells, width),
+            };
+        }
+
+        cell
+    }
+
+    /// Returns the cell of the Input cursor in the terminal, see [`Input::screen_cell`]
+    pub fn cursor_cell(&self) -> ScreenCell {
+        self.screen_cell(self.cursor, term_width())
+    }
+
+    /// Returns the index of the char of the Input values displayed at the given cell of a terminal
+    /// of the given width, the inverse of [`Input::screen_cell`]
+    ///
+    /// the cells before the values map to 0, the cells after them to the values len, a cell in
+    /// the middle of a wide char maps to that char
+    pub fn index_at_cell(&self, cell: ScreenCell, width: usize) -> usize {
+        let width = width.max(2);
+        let mut at = ScreenCell {
+            row: self.prompt_len() / width,
+            column: self.prompt_len() % width,
+        };
+        // the first char whose next index is displayed after cell, the cells being accumulated
+        // like screen_cell does, the end of a cluster being where the next one starts
+        let mut end = None;
+        for (range, cells) in clusters(&self.values) {
+            let newline = self.multiline && self.mask.is_none() && self.values[range.start] == '\n';
+            let cells = self.shown_cells(cells);
+            let start = match !newline && at.column + cells > width {
+                true => ScreenCell {
+                    row: at.row + 1,
+                    column: 0,
+                },
+                false => at,
+            };
+            if end.is_some() && start > cell {
+                return range.start - 1;
+            }
+            at = match newline {
+                true => ScreenCell {
+                    row: at.row + 1,
+                    column: self.margin() + text_width(&self.continuation_prompt),
+                },
+                false => Self::advance(at, cells, width),
+            };
+            // the chars of the cluster after its first one
+            if range.len() > 1 && at > cell {
+                return range.start;
+            }
+            end = Some(range.end);
+        }
+
+        match end {
+            Some(end) if at > cell => end - 1,
+            _ => self.values.len(),
+        }
+    }
+
+    // the cell after a char of the given width written at cell, in a terminal of the given width
+    fn advance(cell: ScreenCell, cells: usize, width: usize) -> ScreenCell {
+        let mut cell = match cell.column + cells > width {
+            true => ScreenCell {
+                row: cell.row + 1,
+                column: cells,
+            },
+            false => ScreenCell {
+                row: cell.row,
+                column: cell.column + cells,
+            },
+        };
+        if cell.column == width {
+            cell = ScreenCell {
+                row: cell.row + 1,
+                column: 0,
+            };
+        }
+
+        cell
+    }
+
+    /// Returns the current position of the Input cursor
+    pub fn position(&self) -> Position {
+        let (line, column) = self.line_col();
+        Position {
+            line: self.multiline.then_some(line + 1),
+            column: column + 1,
+            len: self.values.len(),
+        }
+    }
+
+    /// Notifies the user of an invalid action according to the Input bell policy
+    pub fn ring(&self, sol: &mut dyn TerminalWriter) {
+        if self.plain {
+            return;
+        }
+        match self.bell {
+            Bell::Audible => _ = sol.write(b"\x07"),
+            Bell::Visual => {
+                _ = sol.write(b"\x1b[?5h");
+                _ = sol.flush();
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                _ = sol.write(b"\x1b[?5l");
+            }
+            Bell::None => return,
+        }
+        _ = sol.flush();
+    }
+
+    /// Switches the Input editing mode, the mode badge gets updated on the next render
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    // collects the texts of the enabled indicators into the
+    // [before prompt, right prompt, status line] slots
+    pub(crate) fn indicators(&self) -> [String; 3] {
+        let mut slots: [String; 3] = Default::default();
+        [
+            (self.mode_indicator, self.mode.badge().to_string()),
+            (self.indicator, self.position().to_string()),
+        ]
+        .into_iter()
+        .for_each(|(placement, text)| {
+            let slot = match placement {
+                Indicator::Hidden => return,
+                Indicator::Prompt => &mut slots[0],
+                Indicator::RightPrompt => &mut slots[1],
+                Indicator::StatusLine => &mut slots[2],
+            };
+            if !slot.is_empty() {
+                slot.push(' ');
+            }
+            slot.push_str(&text);
+        });
+        if !slots[0].is_empty() {
+            slots[0].push(' ');
+        }
+        if let Some(message) = &self.message {
+            if !slots[2].is_empty() {
+                slots[2].push(' ');
+            }
+            slots[2].push_str(message);
+        }
+        if let Some(diagnostic) = focused(&self.diagnostics, self.cursor, self.values.len()) {
+            if !slots[2].is_empty() {
+                slots[2].push(' ');
+            }
+            slots[2].push_str(&diagnostic.status());
+        }
 
-        match self.values[self.cursor - 1] == ' ' {
-            true => {
-                while self.cursor > 0 && self.values[self.cursor - 1] == ' ' {
-                    self.cursor -= 1;
+        slots
+    }
+
+    // the displayed values in their highlights, with the ghost text or suggestion dimmed at the
+    // cursor, the selection in reverse video and the diagnostics underlined
+    pub(crate) fn styled_values(&self) -> String {
+        let selection = self.selection().unwrap_or_default();
+        let len = self.values.len();
+        let mut styled = String::new();
+        for idx in 0..=len {
+            let style = self.highlight_at(idx).filter(|_| idx < len);
+            let restyled = style != idx.checked_sub(1).and_then(|idx| self.highlight_at(idx));
+            // a highlight change resets the selection and underline, they get turned back on
+            if restyled {
+                styled.push_str(&style.unwrap_or_default().sgr());
+                if selection.start < idx && idx < selection.end {
+                    styled.push_str("\x1b[7m");
                 }
             }
-            false => {
-                while self.cursor > 1 && !Self::STOPPERS.contains(&self.values[self.cursor - 1]) {
-                    self.cursor -= 1;
+            let underline = match restyled {
+                true => underline_of(&self.diagnostics, idx, len),
+                false => underline_at(&self.diagnostics, idx, len),
+            };
+            if let Some(underline) = underline {
+                styled.push_str(underline);
+            }
+            if !selection.is_empty() && idx == selection.end {
+                styled.push_str("\x1b[27m");
+            }
+            if idx == self.cursor {
+                if let Some(ghost) = self.preview() {
+                    styled.push_str("\x1b[2m");
+                    styled.push_str(ghost);
+                    styled.push_str("\x1b[22m");
                 }
-                self.cursor -= 1;
             }
+            if idx == self.values.len() {
+                break;
+            }
+            if !selection.is_empty() && idx == selection.start {
+                styled.push_str("\x1b[7m");
+            }
+            if self.multiline && self.mask.is_none() && self.values[idx] == '\n' {
+                styled.push_str("\r\n");
+                if self.margin() > 0 {
+                    styled.push_str(&format!("\x1b[{}G", self.margin() + 1));
+                }
+                styled.push_str(&self.continuation_prompt);
+                continue;
+            }
+            styled.push_str(&self.shown(idx..idx + 1));
         }
+
+        styled
     }
-}
 
-// NOTE: the cursor in both input and history does not point to the item it's on,
-// but is alawys pointing at the item to the left
-// basically cursor = 0 points at nothing and cursor = 4 points at eg. input[3]
-// this logic is implemented in the functionality
+    // the number of cells before the values: the zen margin, the indicators injected before
+    // the prompt and the prompt
+    fn prompt_len(&self) -> usize {
+        self.margin() + text_width(&self.indicators()[0]) + text_width(&self.prompt)
+    }
 
-#[derive(Debug)]
-pub struct History {
-    #[cfg(any(debug_assertions, feature = "debug_logs"))]
-    pub debug_log: std::fs::File,
-    pub values: Vec<Vec<char>>,
-    pub cursor: usize,
-    pub temp: Option<Vec<char>>,
-}
+    // the 1 based terminal row of the line in the zen layout, None outside of it
+    fn zen_row(&self) -> Option<usize> {
+        if self.zen.is_none() || !self.alt_screen || self.plain {
+            return None;
+        }
+        let height = window_size().map_or(24, |size| size.rows as usize);
 
-impl History {
-    /// Creates a new History instance
-    pub fn new() -> Self {
-        Self {
-            #[cfg(any(debug_assertions, feature = "debug_logs"))]
-            debug_log: std::fs::File::create("resources/logs/terminal/history").unwrap_or_else(
-                |_| {
-                    std::fs::create_dir_all("resources/logs/terminal").unwrap();
-                    std::fs::File::create("resources/logs/terminal/history").unwrap()
-                },
-            ),
-            values: Vec::new(),
-            cursor: 0,
-            temp: None,
+        // the status line goes on the row below the centered line
+        Some(height.saturating_sub(1).div_ceil(2).max(1))
+    }
+
+    // the blank columns left of the line and the status line
+    fn margin(&self) -> usize {
+        match self.zen_row() {
+            Some(_) => self.zen.unwrap_or_default(),
+            None => 0,
         }
     }
 
-    /// Binds the value of the previous history entry to the value variable and moves back the
-    /// History cursor by one
-    pub fn prev(&mut self, value: &mut Vec<char>) -> bool {
-        if self.cursor == 0 {
-            return false;
+    // moves the cursor over the margin of a freshly cleared row
+    fn write_margin(&self, sol: &mut dyn TerminalWriter) -> Result<(), RagoutError> {
+        if self.margin() > 0 {
+            sol.write_all(format!("\x1b[{}G", self.margin() + 1).as_bytes())?;
         }
 
-        if self.temp.is_none() || self.cursor == self.values.len() {
-            self.temp = Some(value.clone()); // temporarily keep input val
+        Ok(())
+    }
+
+    // writes text right aligned on the current line then puts the cursor back where it was
+    fn write_right(&self, sol: &mut dyn TerminalWriter, text: &str) -> Result<(), RagoutError> {
+        let width = sol.columns();
+        // the zen layout pads both sides
+        let width = width.saturating_sub(self.margin());
+        let len = text_width(text);
+        // don't draw over the user input
+        if self.prompt_len() + self.display_width() + self.ghost_len() + len >= width {
+            return Ok(());
         }
 
-        *value = self.values[self.cursor - 1].clone();
-        self.cursor -= 1;
+        sol.write_all(b"\x1b7")?;
+        sol.write_all(format!("\x1b[{}G", width - len + 1).as_bytes())?;
+        sol.write_all(&str_to_bytes(text))?;
+        sol.write_all(b"\x1b8")?;
 
-        true
+        Ok(())
     }
 
-    /// Binds the value of the next history entry to the value variable and moves forward the
-    /// History cursor by one
-    pub fn next(&mut self, value: &mut Vec<char>) -> bool {
-        if self.cursor == self.values.len() {
-            return false;
+    // writes text on a clean line below the input line then goes back to the end of the input
+    // line
+    fn write_status(&self, sol: &mut dyn TerminalWriter, text: &str) -> Result<(), RagoutError> {
+        sol.write_all(b"\n\r\x1b[2K")?;
+        self.write_margin(sol)?;
+        sol.write_all(&str_to_bytes(text))?;
+        sol.write_all(b"\x1b[A")?;
+        let end = self.end_cell(sol.columns());
+        sol.write_all(format!("\x1b[{}G", end.column + 1).as_bytes())?;
+
+        Ok(())
+    }
+
+    // the number of cells taken by the ghost text or suggestion
+    fn ghost_len(&self) -> usize {
+        self.preview().map_or(0, text_width)
+    }
+
+    /// Syncs the user input cursor displayed in the terminal to the cursor of Input
+    ///
+    /// # Errors
+    /// Same as [`Input::write_prompt`]
+    pub fn sync_cursor(&self, sol: &mut dyn TerminalWriter) -> Result<(), RagoutError> {
+        // the cell accounts for the wrapped rows and the lines of the multiline mode
+        let written = sol.written();
+        let cell = self.screen_cell(self.cursor, sol.columns());
+        self.move_to(sol, cell)?;
+        self.keep_drawn(written, sol);
+
+        Ok(())
+    }
+
+    // pub fn toggle_alt_screen(&mut self, sol: &mut StdoutLock) {
+    //     match self.alt_screen {
+    //         true => {
+    //             _ = sol.write(b"\x1b[?1049l");
+    //         }
+    //         false => {
+    //             _ = sol.write(b"\x1b[?1049h");
+    //         }
+    //     }
+    //
+    //     self.alt_screen = !self.alt_screen;
+    // }
+}
+
+// control chars (inserted by quoted insert) are displayed in caret notation, e.g. ^[ for escape
+pub(crate) fn visible(chars: &[char]) -> String {
+    chars
+        .iter()
+        .fold(String::with_capacity(chars.len()), |mut s, c| {
+            match c.is_ascii_control() {
+                true => {
+                    s.push('^');
+                    s.push((*c as u8 ^ 0x40) as char);
+                }
+                false => s.push(*c),
+            }
+            s
+        })
+}
+
+// the index of the grapheme cluster boundary before idx, what one press of the left arrow or
+// backspace goes over
+pub(crate) fn prev_boundary(chars: &[char], idx: usize) -> usize {
+    let mut idx = idx.min(chars.len()).saturating_sub(1);
+    while idx > 0 && joined(chars, idx) {
+        idx -= 1;
+    }
+
+    idx
+}
+
+// the index of the grapheme cluster boundary after idx
+pub(crate) fn next_boundary(chars: &[char], idx: usize) -> usize {
+    let mut idx = (idx + 1).min(chars.len());
+    while idx < chars.len() && joined(chars, idx) {
+        idx += 1;
+    }
+
+    idx
+}
+
+// whether the char at idx belongs to the grapheme cluster of the char before it, a simplified
+// take on the extended grapheme clusters of UAX #29: the combining marks, the variation
+// selectors, the skin tone modifiers and the chars after a zero width joiner extend a
+// cluster, and the regional indicators pair up into flags
+fn joined(chars: &[char], idx: usize) -> bool {
+    let (a, b) = (chars[idx - 1], chars[idx]);
+    if a.is_control() || b.is_control() {
+        return false;
+    }
+    if regional(a) && regional(b) {
+        // the indicators before b pair up first
+        let run = chars[..idx]
+            .iter()
+            .rev()
+            .take_while(|c| regional(**c))
+            .count();
+        return run % 2 == 1;
+    }
+
+    a == '\u{200d}' || extends(b)
+}
+
+// the flags are pairs of regional indicators
+fn regional(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+// the chars that never start a grapheme cluster
+pub(crate) fn extends(c: char) -> bool {
+    ('\u{1f3fb}'..='\u{1f3ff}').contains(&c) || c != '\u{200b}' && char_cells(c) == 0
+}
+
+// the width of the terminal, 80 columns when unknown
+fn term_width() -> usize {
+    window_size().map_or(80, |size| size.cols as usize)
+}
+
+// the number of terminal cells the chars take once displayed
+pub(crate) fn cells(chars: &[char]) -> usize {
+    clusters(chars).map(|(_, cells)| cells).sum()
+}
+
+// the grapheme clusters of the chars along with the cells each takes: the cells of its widest
+// char, the terminals rendering an emoji sequence as a single glyph, and 2 for a flag
+pub(crate) fn clusters(chars: &[char]) -> impl Iterator<Item = (Range<usize>, usize)> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if start >= chars.len() {
+            return None;
         }
+        let end = next_boundary(chars, start);
+        let cluster = &chars[start..end];
+        let cells = match cluster.len() == 2 && regional(cluster[0]) {
+            true => 2,
+            false => cluster.iter().map(|c| char_cells(*c)).max().unwrap_or(0),
+        };
+        let range = start..end;
+        start = end;
+
+        Some((range, cells))
+    })
+}
 
-        if self.cursor + 1 == self.values.len() {
-            *value = self.temp.as_ref().unwrap().clone();
-        } else {
-            *value = self.values[self.cursor + 1].clone();
+/// Returns the number of terminal cells text takes once printed, wcwidth style: the escape
+/// sequences and the control chars take none, the east asian wide chars and the emoji take 2
+/// and so do the emoji sequences joined into a single glyph
+pub fn text_width(text: &str) -> usize {
+    let mut chars = Vec::with_capacity(text.len());
+    let mut iter = text.chars();
+    while let Some(c) = iter.next() {
+        match c {
+            '\x1b' => match iter.next() {
+                // CSI: the parameters up to the final byte
+                Some('[') => _ = iter.by_ref().find(|c| ('\x40'..='\x7e').contains(c)),
+                // OSC: up to BEL or ST, the backslash of ST going along with its ESC
+                Some(']')
+                    if iter.by_ref().find(|c| *c == '\x07' || *c == '\x1b') == Some('\x1b') =>
+                {
+                    _ = iter.next()
+                }
+                _ => (),
+            },
+            c if c.is_control() => (),
+            c => chars.push(c),
         }
-        self.cursor += 1;
+    }
 
-        true
+    cells(&chars)
+}
+
+// the number of terminal cells a char takes, wcwidth style: 2 for the control chars (in caret
+// notation) and the east asian wide chars, 0 for the combining marks and joiners
+pub(crate) fn char_cells(c: char) -> usize {
+    const ZERO: [(char, char); 6] = [
+        ('\u{0300}', '\u{036f}'),
+        ('\u{0483}', '\u{0489}'),
+        ('\u{200b}', '\u{200f}'),
+        ('\u{20d0}', '\u{20ff}'),
+        ('\u{fe00}', '\u{fe0f}'),
+        ('\u{fe20}', '\u{fe2f}'),
+    ];
+    const WIDE: [(char, char); 14] = [
+        ('\u{1100}', '\u{115f}'),
+        ('\u{2e80}', '\u{303e}'),
+        ('\u{3041}', '\u{33ff}'),
+        ('\u{3400}', '\u{4dbf}'),
+        ('\u{4e00}', '\u{9fff}'),
+        ('\u{a000}', '\u{a4cf}'),
+        ('\u{ac00}', '\u{d7a3}'),
+        ('\u{f900}', '\u{faff}'),
+        ('\u{fe30}', '\u{fe4f}'),
+        ('\u{ff00}', '\u{ff60}'),
+        ('\u{ffe0}', '\u{ffe6}'),
+        ('\u{1f300}', '\u{1f64f}'),
+        ('\u{1f900}', '\u{1f9ff}'),
+        ('\u{20000}', '\u{3fffd}'),
+    ];
+    let within = |ranges: &[(char, char)]| ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&c));
+    match c {
+        c if c.is_ascii_control() => 2,
+        _ if within(&ZERO) => 0,
+        _ if within(&WIDE) => 2,
+        _ => 1,
     }
+}
 
-    /// Pushs a new history entry into the History.values
-    pub fn push(&mut self, value: Vec<char>) {
-        if value.iter().filter(|c| **c != ' ').count() > 0 && !self.values.contains(&value) {
-            self.values.push(value);
-        }
-        self.temp = None;
-        self.cursor = self.values.len();
+fn encode_char(c: char, bytes: &mut Vec<u8>) {
+    #[cfg(feature = "encoding")]
+    return crate::encoding::current().encode_char(c, bytes);
+    #[cfg(not(feature = "encoding"))]
+    match c.is_ascii() {
+        false => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        true => bytes.push(c as u8),
     }
 }
 
+fn str_to_bytes(s: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    s.chars().for_each(|c| encode_char(c, &mut bytes));
+
+    bytes
+}
+
 #[cfg(test)]
+// the first tests of the module are written in the style the crate started with
+#[allow(clippy::useless_conversion, clippy::map_clone)]
 mod test_input {
-    use super::{History, Input};
+    use super::{text_width, CursorShape, History, Indicator, Input, Mode, ScreenCell, Terminal};
+    use crate::context::{self, TermContext};
 
     #[test]
     fn test_put_char() {
@@ -344,6 +2111,28 @@ mod test_input {
         assert!({ i.cursor == input.len() - 1 && i.values[i.cursor - 1] == 'n' });
     }
 
+    #[test]
+    fn test_backspace_edited() {
+        let mut i = Input::new("testing input> ", false);
+        // nothing to delete
+        assert!(!i.backspace());
+
+        i.put_char('p');
+        assert!(i.backspace());
+        assert!(i.values.is_empty());
+    }
+
+    #[test]
+    fn test_backspace_at_home() {
+        let mut i = Input::new("testing input> ", false);
+        assert!(!i.backspace());
+
+        "pika".chars().for_each(|c| i.put_char(c));
+        i.to_home();
+        assert!(!i.backspace());
+        assert_eq!(i.values.len(), 4);
+    }
+
     #[test]
     fn test_to_end() {
         let mut i = Input::new("testing input> ", false);
@@ -465,68 +2254,435 @@ mod test_input {
         i.clear_left();
         assert_eq!(i.values.iter().map(|c| *c).collect::<String>(), "atto");
     }
-}
 
-impl Input {
-    /// Changes the Input prompt value to the provided string
-    pub fn overwrite_prompt(&mut self, new_prompt: &str) {
-        self.prompt.clear();
-        self.prompt.push_str(new_prompt);
+    #[test]
+    fn test_dirty() {
+        let mut i = Input::new("testing input> ", false);
+        let mut h = History::new();
+        let mut user_input = String::new();
+        assert!(!i.is_dirty());
+
+        "pika".chars().for_each(|c| i.put_char(c));
+        i.backspace();
+        i.to_home();
+        i.clear_left();
+        assert!(i.is_dirty());
+        assert_eq!(i.changes, 5);
+
+        i.cr_lf(&mut h, &mut user_input);
+        assert!(!i.is_dirty());
     }
 
-    /// Renders the Input prompt followed by the Input values on a clean line
-    pub fn write_prompt(&self, sol: &mut StdoutLock) {
-        _ = sol.write(b"\x1b[2K");
-        _ = sol.write(&[13]);
-        _ = sol.write(&str_to_bytes(&self.prompt));
-        _ = sol.write(&str_to_bytes(&self.as_str(&mut "".to_string())));
-        _ = sol.flush();
+    #[test]
+    fn test_set_text() {
+        let mut i = Input::new("testing input> ", false);
+
+        i.set_text("pikachu");
+        assert_eq!(i.cursor, 7);
+        assert!(i.is_dirty());
+
+        i.set_cursor(2);
+        assert_eq!(i.values[i.cursor], 'k');
+
+        i.set_cursor(42);
+        assert_eq!(i.cursor, 7);
     }
 
-    /// Syncs the user input cursor displayed in the terminal to the cursor of Input
-    pub fn sync_cursor(&self, sol: &mut StdoutLock) {
-        _ = sol.write(&[13]);
-        // BUG: at every first inputted char of an input line, the cursor was moving forward
-        // by the sum of the byte lengths of all non-ascii chars in the prompt
-        // this is because prompt(String).len() was counting the byte lengths of the chars not the
-        // number of the chars
-        // FIX: switch to prompt.chars.count() from prompt.len()
-        for _idx in 0..self.prompt.chars().count() + 1 + self.cursor {
-            _ = sol.write(b"\x1b[C");
+    #[test]
+    fn test_words() {
+        let mut i = Input::new("testing input> ", false);
+        i.set_text("pika chu, raichu");
+        i.set_cursor(6);
+
+        let (range, word) = i.word_at_cursor().unwrap();
+        assert_eq!(range, 5..8);
+        assert_eq!(word, "chu");
+        assert_eq!(i.prev_word().unwrap().0, 0..4);
+        assert_eq!(i.next_word().unwrap().0, 10..16);
+
+        i.set_cursor(0);
+        assert!(i.prev_word().is_none());
+        assert_eq!(i.next_word().unwrap().0, 5..8);
+
+        i.set_cursor(9);
+        assert!(i.word_at_cursor().is_none());
+        assert_eq!(i.prev_word().unwrap().0, 5..8);
+        assert_eq!(i.next_word().unwrap().0, 10..16);
+    }
+
+    #[test]
+    fn test_continues() {
+        let mut i = Input::new("testing input> ", false);
+
+        i.set_text("echo a \\");
+        assert!(i.continues());
+        i.set_text("echo a \\\\");
+        assert!(!i.continues());
+        i.set_text("echo a");
+        assert!(!i.continues());
+    }
+
+    #[test]
+    fn test_position() {
+        let mut i = Input::new("testing input> ", false);
+
+        "pikachu".chars().for_each(|c| i.put_char(c));
+        i.to_the_left();
+
+        assert_eq!(i.position().to_string(), "7/7");
+
+        i.to_home();
+        assert_eq!(i.position().column, 1);
+
+        // the column is counted from the start of the line the cursor is on
+        i.multiline = true;
+        i.set_text("if x {\n    y");
+        assert_eq!(i.position().line, Some(2));
+        assert_eq!(i.position().to_string(), "2:6/12");
+    }
+
+    #[test]
+    fn test_mode_indicator() {
+        let mut i = Input::new("testing input> ", false);
+        i.mode_indicator = Indicator::Prompt;
+        i.indicator = Indicator::Prompt;
+
+        assert_eq!(i.indicators()[0], "[I] 1/0 ");
+
+        i.set_mode(Mode::Normal);
+        i.indicator = Indicator::RightPrompt;
+        assert_eq!(
+            i.indicators(),
+            ["[N] ".to_string(), "1/0".into(), "".into()]
+        );
+        assert_eq!(i.prompt_len(), "[N] testing input> ".len());
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut i = Input::new("testing input> ", false);
+        i.set_text("pika");
+
+        let snapshot = i.clone();
+        i.set_text("chu");
+        assert_eq!(snapshot.values.iter().collect::<String>(), "pika");
+        assert_eq!(i.values.iter().collect::<String>(), "chu");
+    }
+
+    #[test]
+    fn test_control_chars() {
+        let mut i = Input::new("> ", false);
+        i.set_text("a\x1bb\x7f");
+        i.set_cursor(2);
+
+        assert_eq!(super::visible(&i.values), "a^[b^?");
+        assert_eq!(i.cursor_column(), 6);
+    }
+
+    #[test]
+    fn test_expand_codepoint() {
+        let mut i = Input::new("> ", false);
+        i.set_text("smile \\u{1F600} ok");
+        assert!(!i.expand_codepoint());
+
+        i.set_cursor(15);
+        assert!(i.expand_codepoint());
+        assert_eq!(i.values.iter().collect::<String>(), "smile \u{1F600} ok");
+        assert_eq!(i.cursor, 7);
+
+        i.set_text("\\u{d800}");
+        assert!(!i.expand_codepoint());
+    }
+
+    #[test]
+    fn test_find_char() {
+        let mut i = Input::new("> ", false);
+        i.set_text("cargo run --bin ragout");
+        i.to_home();
+
+        assert!(i.find_char_forward('r', false));
+        assert_eq!(i.cursor, 2);
+        assert!(i.repeat_find(false));
+        assert_eq!(i.cursor, 6);
+        assert!(i.find_char_forward('-', true));
+        assert_eq!(i.cursor, 9);
+        assert!(i.repeat_find(false));
+        assert_eq!(i.cursor, 10);
+
+        assert!(i.find_char_backward('a', false));
+        assert_eq!(i.cursor, 1);
+        assert!(i.repeat_find(true));
+        assert_eq!(i.cursor, 17);
+        assert!(!i.find_char_backward('z', true));
+    }
+
+    #[test]
+    fn test_cursor_shape() {
+        assert_eq!(Mode::Insert.cursor_shape(), CursorShape::Bar);
+        assert_eq!(Mode::Normal.cursor_shape().escape(), "\x1b[2 q");
+        assert_eq!(Mode::Overwrite.cursor_shape(), CursorShape::Underline);
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut i = Input::new("> ", false);
+        i.set_text("git commit -m");
+
+        assert_eq!(i.index_at(0), 0);
+        assert_eq!(i.index_at(6), 4);
+        assert_eq!(i.index_at(40), 13);
+        assert!(i.select_word(i.index_at(8)));
+        assert_eq!(i.selection(), Some(4..10));
+        assert_eq!(i.styled_values(), "git \x1b[7mcommit\x1b[27m -m");
+
+        i.mark = Some(10);
+        assert_eq!(i.selection(), None);
+    }
+
+    #[test]
+    fn test_subwords() {
+        let mut i = Input::new("testing input> ", false);
+        i.subwords = true;
+        i.set_text("parseHTTPServer_conf x");
+
+        let mut stops = Vec::new();
+        i.set_cursor(0);
+        while i.cursor < i.values.len() {
+            i.to_right_jump();
+            stops.push(i.cursor);
+        }
+        assert_eq!(stops, [5, 9, 15, 20, 22]);
+
+        stops.clear();
+        while i.cursor > 0 {
+            i.to_left_jump();
+            stops.push(i.cursor);
         }
+        assert_eq!(stops, [21, 16, 9, 5, 0]);
+
+        i.set_cursor(15);
+        assert!(i.delete_jump_left());
+        assert_eq!(i.values.iter().collect::<String>(), "parseHTTP_conf x");
+        assert!(i.delete_jump_right());
+        assert_eq!(i.values.iter().collect::<String>(), "parseHTTP x");
     }
 
-    // pub fn toggle_alt_screen(&mut self, sol: &mut StdoutLock) {
-    //     match self.alt_screen {
-    //         true => {
-    //             _ = sol.write(b"\x1b[?1049l");
-    //         }
-    //         false => {
-    //             _ = sol.write(b"\x1b[?1049h");
-    //         }
-    //     }
-    //
-    //     self.alt_screen = !self.alt_screen;
-    // }
-    fn as_str<'a>(&self, s: &'a mut String) -> &'a str {
-        *s = self.values.iter().map(|c| c).collect::<String>();
+    #[test]
+    fn test_screen_cell() {
+        let cell = |row, column| ScreenCell { row, column };
+        let mut i = Input::new("> ", false);
+        i.set_text("abc漢d\u{301}e");
+
+        // the wide char does not fit in the last column of the first row
+        assert_eq!(i.screen_cell(0, 6), cell(0, 2));
+        assert_eq!(i.screen_cell(2, 6), cell(0, 4));
+        assert_eq!(i.screen_cell(3, 6), cell(1, 0));
+        assert_eq!(i.screen_cell(4, 6), cell(1, 2));
+        // the combining accent takes no cell
+        assert_eq!(i.screen_cell(6, 6), cell(1, 3));
+        assert_eq!(i.screen_cell(99, 6), cell(1, 4));
+        assert_eq!(i.screen_cell(3, 80), cell(0, 5));
+
+        assert_eq!(i.index_at_cell(cell(0, 0), 6), 0);
+        assert_eq!(i.index_at_cell(cell(0, 5), 6), 2);
+        assert_eq!(i.index_at_cell(cell(1, 1), 6), 3);
+        assert_eq!(i.index_at_cell(cell(1, 3), 6), 6);
+        assert_eq!(i.index_at_cell(cell(4, 0), 6), 7);
+
+        // the cells accumulated in a single pass map like the screen cells of every index
+        i.multiline = true;
+        i.set_text("a漢\nb\u{301}c漢漢\n\nd");
+        for width in [2, 3, 5, 80] {
+            for row in 0..8 {
+                for column in 0..width {
+                    let at = cell(row, column);
+                    let idx = (0..i.values.len())
+                        .find(|idx| i.screen_cell(idx + 1, width) > at)
+                        .unwrap_or(i.values.len());
+                    assert_eq!(i.index_at_cell(at, width), idx, "{:?} {}", at, width);
+                }
+            }
+        }
+    }
 
-        s.as_str()
+    #[test]
+    fn test_zen() {
+        let mut i = Input::new("> ", false);
+        i.set_text("abc");
+        i.zen = Some(10);
+        assert_eq!(i.cursor_column(), 6);
+
+        i.alt_screen = true;
+        assert!(i.zen_row().is_some());
+        assert_eq!(i.cursor_column(), 16);
+        assert_eq!(i.index_at(13), 1);
     }
-}
 
-fn encode_char(c: char, bytes: &mut Vec<u8>) {
-    match c.is_ascii() {
-        false => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
-        true => bytes.push(c as u8),
+    #[test]
+    fn test_terminal_guard() {
+        let mut term = Terminal {
+            sol: std::io::stdout().lock(),
+            alt_screen: false,
+            restored: false,
+            writes: 0,
+            mock: None,
+        };
+        context::save(TermContext::default());
+        term.restore();
+        assert!(term.restored);
+        // the terminal state saved by init got restored
+        assert!(context::take().is_none());
+
+        // neither a second restore nor the drop restore again
+        context::save(TermContext::default());
+        term.restore();
+        drop(term);
+        assert!(context::take().is_some());
     }
-}
 
-fn str_to_bytes(s: &str) -> Vec<u8> {
-    let mut bytes = Vec::new();
-    s.chars()
-        .into_iter()
-        .for_each(|c| encode_char(c, &mut bytes));
+    #[test]
+    fn test_multiline() {
+        let cell = |row, column| ScreenCell { row, column };
+        let mut i = Input::new("> ", false);
+        i.set_text("fn main() {\n    ok\n}");
+        assert_eq!(i.lines().len(), 1);
+        assert!(!i.line_up());
+
+        i.multiline = true;
+        i.continuation_prompt = ". ".to_string();
+        assert_eq!(i.lines()[1].iter().collect::<String>(), "    ok");
+        assert_eq!(i.line_col(), (2, 1));
+        assert!(i.line_up());
+        assert_eq!((i.cursor, i.line_col()), (13, (1, 1)));
+        assert!(i.line_up());
+        assert_eq!(i.cursor, 1);
+        assert!(!i.line_up());
+        i.set_cursor(10);
+        assert!(i.line_down());
+        assert!(i.line_down());
+        assert_eq!(i.cursor, i.values.len());
+        assert!(!i.line_down());
+
+        // the lines after the first one start after the continuation prompt
+        assert_eq!(i.screen_cell(12, 80), cell(1, 2));
+        assert_eq!(i.screen_cell(18, 80), cell(1, 8));
+        assert_eq!(i.screen_cell(20, 80), cell(2, 3));
+        assert_eq!(i.screen_cell(18, 6), cell(4, 2));
+        assert_eq!(i.index_at_cell(cell(1, 4), 80), 14);
+    }
 
-    bytes
+    #[test]
+    fn test_graphemes() {
+        let mut i = Input::new("> ", false);
+        // e + acute, woman + zwj + laptop, two flags, thumbs up + skin tone
+        i.set_text("e\u{301}👩\u{200d}💻🇫🇷🇯🇵👍🏽");
+
+        assert!(i.to_the_left());
+        assert_eq!(i.cursor, 9);
+        assert!(i.to_the_left());
+        assert_eq!(i.cursor, 7);
+        assert!(i.backspace());
+        assert_eq!((i.cursor, i.values.len()), (5, 9));
+        assert!(i.backspace());
+        assert!(i.backspace());
+        assert_eq!(i.values, ['🇯', '🇵', '👍', '\u{1f3fd}']);
+        assert!(i.to_the_right());
+        assert_eq!(i.cursor, 2);
+        assert!(i.to_the_right());
+        assert_eq!(i.cursor, 4);
+
+        i.set_text("e\u{301}");
+        i.set_cursor(0);
+        assert!(i.to_the_right());
+        assert_eq!(i.cursor, 2);
+    }
+
+    #[test]
+    fn test_widths() {
+        assert_eq!(text_width("\x1b[1;32m❯\x1b[0m "), 2);
+        assert_eq!(text_width("\x1b]0;title\x07漢字> "), 6);
+        assert_eq!(text_width("\x1b]8;;https://x.y\x1b\\link\x1b]8;;\x1b\\"), 4);
+
+        let mut i = Input::new("漢> ", false);
+        i.set_text("a👩\u{200d}💻🇫🇷");
+        assert_eq!(i.display_width(), 5);
+        assert_eq!(i.cursor_cell(), ScreenCell { row: 0, column: 9 });
+
+        // the joined emoji take the 2 cells of a single glyph
+        i.set_cursor(1);
+        assert_eq!(i.cursor_cell().column, 5);
+        assert_eq!(i.index_at(6), 1);
+        assert_eq!(i.index_at(7), 4);
+    }
+
+    #[test]
+    fn test_word_ops() {
+        let mut i = Input::new("> ", false);
+        i.set_text("cargo test --release");
+        i.set_cursor(5);
+        assert!(i.delete_word_right());
+        assert_eq!(i.values.iter().collect::<String>(), "cargo --release");
+        assert!(i.delete_word_right());
+        assert!(!i.delete_word_right());
+        assert_eq!(i.kill_ring.latest(), Some(" test --release"));
+        assert!(i.delete_word_left());
+        assert!(i.values.is_empty());
+
+        i.set_text("cargo tset");
+        i.set_cursor(8);
+        assert!(i.transpose_chars());
+        assert_eq!(
+            (i.values.iter().collect::<String>(), i.cursor),
+            ("cargo test".into(), 9)
+        );
+        assert!(i.transpose_words());
+        assert_eq!(
+            (i.values.iter().collect::<String>(), i.cursor),
+            ("test cargo".into(), 10)
+        );
+        i.set_cursor(2);
+        assert!(i.transpose_words());
+        assert_eq!(i.values.iter().collect::<String>(), "cargo test");
+        i.set_text("cargo");
+        assert!(!i.transpose_words());
+    }
+
+    #[test]
+    fn test_paste() {
+        let mut i = Input::new("> ", false);
+        assert!(i.paste("cargo\ttest\r\nrm -rf /\x1b[A\n"));
+        assert_eq!(
+            i.values.iter().collect::<String>(),
+            "cargo    test rm -rf /[A"
+        );
+        assert!(!i.paste("\x07\n"));
+
+        i.clear_line();
+        i.multiline = true;
+        assert!(i.paste("a\rb"));
+        assert_eq!(i.values, ['a', '\n', 'b']);
+    }
+
+    #[test]
+    fn test_mask() {
+        let mut i = Input::new("> ", false);
+        let mut h = History::new();
+        i.set_text("pe\u{301}w");
+        i.set_mask(Some('*'));
+        assert!(i.is_secret());
+        assert_eq!(i.styled_values(), "***");
+        assert_eq!((i.display_width(), i.cursor_column()), (3, 6));
+        assert_eq!(i.screen_cell(4, 80), ScreenCell { row: 0, column: 5 });
+
+        i.set_mask(None);
+        assert_eq!(i.styled_values(), "");
+        assert_eq!(i.cursor_column(), 3);
+
+        let mut line = String::new();
+        i.cr_lf(&mut h, &mut line);
+        assert_eq!(line, "pe\u{301}w");
+        assert!(h.is_empty());
+        i.unmask();
+        assert!(!i.is_masked());
+    }
 }
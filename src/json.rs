@@ -0,0 +1,347 @@
+// a minimal JSON value, enough for the line delimited protocols and logs of the crate
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    // parses a whole JSON text, None if it is malformed or nests deeper than MAX_DEPTH
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_spaces();
+
+        (parser.pos == parser.chars.len()).then_some(value)
+    }
+
+    // the value of an object key
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Self::Obj(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_num(&self) -> Option<f64> {
+        match self {
+            Self::Num(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Self::Arr(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    // an object from its entries
+    pub(crate) fn obj<const N: usize>(entries: [(&str, Json); N]) -> Self {
+        Self::Obj(entries.map(|(k, v)| (k.to_string(), v)).into())
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Self::Str(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Self::Str(s)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Self::Num(n as f64)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl std::fmt::Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Num(n) => write!(f, "{}", n),
+            Self::Str(s) => write_str(f, s),
+            Self::Arr(values) => {
+                write!(f, "[")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Self::Obj(entries) => {
+                write!(f, "{{")?;
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_str(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+// how deep the arrays and objects may nest, which bounds the recursion of Parser::value
+const MAX_DEPTH: usize = 128;
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    // the arrays and objects the parsed value is in
+    depth: usize,
+}
+
+impl Parser {
+    fn skip_spaces(&mut self) {
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = *self.chars.get(self.pos)?;
+        self.pos += 1;
+
+        Some(c)
+    }
+
+    fn eat(&mut self, word: &str) -> bool {
+        let end = self.pos + word.chars().count();
+        let matched = self
+            .chars
+            .get(self.pos..end)
+            .is_some_and(|w| w.iter().copied().eq(word.chars()));
+        if matched {
+            self.pos = end;
+        }
+
+        matched
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.skip_spaces();
+        let c = *self.chars.get(self.pos)?;
+        if c == '[' || c == '{' {
+            if self.depth == MAX_DEPTH {
+                return None;
+            }
+            self.depth += 1;
+            let value = self.nested(c);
+            self.depth -= 1;
+            return value;
+        }
+        match c {
+            'n' if self.eat("null") => Some(Json::Null),
+            't' if self.eat("true") => Some(Json::Bool(true)),
+            'f' if self.eat("false") => Some(Json::Bool(false)),
+            '"' => self.string().map(Json::Str),
+            _ => {
+                let start = self.pos;
+                while self
+                    .chars
+                    .get(self.pos)
+                    .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(*c))
+                {
+                    self.pos += 1;
+                }
+                self.chars[start..self.pos]
+                    .iter()
+                    .collect::<String>()
+                    .parse()
+                    .ok()
+                    .map(Json::Num)
+            }
+        }
+    }
+
+    // the array or object opened by c, a '[' or a '{'
+    fn nested(&mut self, c: char) -> Option<Json> {
+        match c {
+            '[' => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                self.skip_spaces();
+                if self.eat("]") {
+                    return Some(Json::Arr(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.skip_spaces();
+                    match self.next()? {
+                        ',' => (),
+                        ']' => return Some(Json::Arr(values)),
+                        _ => return None,
+                    }
+                }
+            }
+            // '{'
+            _ => {
+                self.pos += 1;
+                let mut entries = Vec::new();
+                self.skip_spaces();
+                if self.eat("}") {
+                    return Some(Json::Obj(entries));
+                }
+                loop {
+                    self.skip_spaces();
+                    let key = self.string()?;
+                    self.skip_spaces();
+                    if self.next()? != ':' {
+                        return None;
+                    }
+                    entries.push((key, self.value()?));
+                    self.skip_spaces();
+                    match self.next()? {
+                        ',' => (),
+                        '}' => return Some(Json::Obj(entries)),
+                        _ => return None,
+                    }
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.next()? != '"' {
+            return None;
+        }
+        let mut s = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Some(s),
+                '\\' => s.push(match self.next()? {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'b' => '\x08',
+                    'f' => '\x0c',
+                    'u' => self.escaped_char()?,
+                    c => c,
+                }),
+                c => s.push(c),
+            }
+        }
+    }
+
+    // the char of a \u escape, its 4 hex digits next; the chars outside of the basic plane are
+    // escaped as a pair of surrogates, a lone surrogate gets replaced
+    fn escaped_char(&mut self) -> Option<char> {
+        let high = self.hex()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return Some(char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+        let pos = self.pos;
+        if self.eat("\\u") {
+            match self.hex()? {
+                low @ 0xdc00..0xe000 => {
+                    let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                    return char::from_u32(code);
+                }
+                // the escape after the lone surrogate is a char of its own
+                _ => self.pos = pos,
+            }
+        }
+
+        Some(char::REPLACEMENT_CHARACTER)
+    }
+
+    // the value of the 4 hex digits next
+    fn hex(&mut self) -> Option<u32> {
+        let hex = self.chars.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+
+        u32::from_str_radix(&hex.iter().collect::<String>(), 16).ok()
+    }
+}
+
+#[cfg(test)]
+mod test_json {
+    use super::Json;
+
+    #[test]
+    fn test_round_trip() {
+        let text =
+            r#"{"cmd":"keys","keys":["a","ctrl-c"],"n":3,"ok":true,"x":null,"s":"a\"b\\c\n"}"#;
+        let json = Json::parse(text).unwrap();
+
+        assert_eq!(json.get("cmd").and_then(Json::as_str), Some("keys"));
+        assert_eq!(json.get("n"), Some(&Json::Num(3.0)));
+        assert_eq!(json.get("s").and_then(Json::as_str), Some("a\"b\\c\n"));
+        assert_eq!(json.to_string(), text);
+        assert_eq!(
+            Json::parse(" [1, 2.5 , {} ] "),
+            Some(Json::Arr(vec![
+                Json::Num(1.0),
+                Json::Num(2.5),
+                Json::Obj(vec![])
+            ]))
+        );
+        assert_eq!(Json::parse("{\"a\":}"), None);
+        assert_eq!(Json::parse("[1] x"), None);
+    }
+
+    #[test]
+    fn test_surrogates_depth() {
+        let parsed =
+            |text: &str| Json::parse(text).and_then(|json| Some(json.as_str()?.to_string()));
+        assert_eq!(parsed(r#""\uD83D\uDE00!""#).as_deref(), Some("😀!"));
+        assert_eq!(parsed(r#""\ud83dx""#).as_deref(), Some("\u{fffd}x"));
+        assert_eq!(parsed(r#""\ud83d\u0041""#).as_deref(), Some("\u{fffd}A"));
+
+        assert!(Json::parse(&format!("{}{}", "[".repeat(128), "]".repeat(128))).is_some());
+        assert_eq!(Json::parse(&"[".repeat(100_000)), None);
+    }
+}
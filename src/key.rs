@@ -0,0 +1,381 @@
+/// A key press, independent of the terminal event source it was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: Code,
+    pub mods: Mods,
+}
+
+/// The key of a key press without its modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Code {
+    Char(char),
+    Enter,
+    Tab,
+    BackTab,
+    Backspace,
+    Delete,
+    Insert,
+    Esc,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    /// function key, F(1) is F1
+    F(u8),
+    /// any key this crate has no use for
+    Other,
+}
+
+/// The modifiers held during a key press
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Mods {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl Mods {
+    pub const NONE: Self = Self {
+        ctrl: false,
+        alt: false,
+        shift: false,
+    };
+    pub const CTRL: Self = Self {
+        ctrl: true,
+        ..Self::NONE
+    };
+    pub const ALT: Self = Self {
+        alt: true,
+        ..Self::NONE
+    };
+    pub const SHIFT: Self = Self {
+        shift: true,
+        ..Self::NONE
+    };
+}
+
+impl Key {
+    /// Creates a new Key without modifiers
+    pub const fn new(code: Code) -> Self {
+        Self {
+            code,
+            mods: Mods::NONE,
+        }
+    }
+
+    /// Creates a new Key with the given modifiers
+    pub const fn with(code: Code, mods: Mods) -> Self {
+        Self { code, mods }
+    }
+
+    /// Creates a new plain char Key
+    pub const fn char(c: char) -> Self {
+        Self::new(Code::Char(c))
+    }
+
+    /// Creates a new Ctrl + char Key
+    pub const fn ctrl(c: char) -> Self {
+        Self::with(Code::Char(c), Mods::CTRL)
+    }
+
+    /// Creates a new Alt + char Key
+    pub const fn alt(c: char) -> Self {
+        Self::with(Code::Char(c), Mods::ALT)
+    }
+}
+
+/// The error of a key name that [`Key::from_str`](std::str::FromStr) could not parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError(pub String);
+
+impl std::fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown key `{}`", self.0)
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+/// Parses key names like `a`, `enter`, `f5`, `ctrl-r` or `ctrl-alt-left`, the modifiers being
+/// `ctrl-`, `alt-` and `shift-` and the names of the keys other than the chars being those of
+/// [`Code`] in lowercase, along with `space`
+impl std::str::FromStr for Key {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseKeyError(s.to_string());
+        let mut mods = Mods::NONE;
+        let mut name = s;
+        loop {
+            let lower = name.to_ascii_lowercase();
+            // a lone `-` is the minus key
+            let (flag, rest) = match lower.split_once('-') {
+                Some(("ctrl", rest)) if !rest.is_empty() => (&mut mods.ctrl, rest.len()),
+                Some(("alt", rest)) if !rest.is_empty() => (&mut mods.alt, rest.len()),
+                Some(("shift", rest)) if !rest.is_empty() => (&mut mods.shift, rest.len()),
+                _ => break,
+            };
+            *flag = true;
+            name = &name[name.len() - rest..];
+        }
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => Code::Char(c),
+            _ => match name.to_ascii_lowercase().as_str() {
+                "space" => Code::Char(' '),
+                "enter" => Code::Enter,
+                "tab" => Code::Tab,
+                "backtab" => Code::BackTab,
+                "backspace" => Code::Backspace,
+                "delete" => Code::Delete,
+                "insert" => Code::Insert,
+                "esc" => Code::Esc,
+                "left" => Code::Left,
+                "right" => Code::Right,
+                "up" => Code::Up,
+                "down" => Code::Down,
+                "home" => Code::Home,
+                "end" => Code::End,
+                "pageup" => Code::PageUp,
+                "pagedown" => Code::PageDown,
+                "other" => Code::Other,
+                lower => match lower.strip_prefix('f').map(str::parse::<u8>) {
+                    Some(Ok(n)) if n > 0 => Code::F(n),
+                    _ => return Err(err()),
+                },
+            },
+        };
+
+        Ok(Self::with(code, mods))
+    }
+}
+
+/// Writes the name [`Key::from_str`](std::str::FromStr) parses back, e.g. `ctrl-alt-left`
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (held, name) in [
+            (self.mods.ctrl, "ctrl-"),
+            (self.mods.alt, "alt-"),
+            (self.mods.shift, "shift-"),
+        ] {
+            if held {
+                write!(f, "{}", name)?;
+            }
+        }
+        let name = match self.code {
+            Code::Char(' ') => "space",
+            Code::Char(c) => return write!(f, "{}", c),
+            Code::F(n) => return write!(f, "f{}", n),
+            Code::Enter => "enter",
+            Code::Tab => "tab",
+            Code::BackTab => "backtab",
+            Code::Backspace => "backspace",
+            Code::Delete => "delete",
+            Code::Insert => "insert",
+            Code::Esc => "esc",
+            Code::Left => "left",
+            Code::Right => "right",
+            Code::Up => "up",
+            Code::Down => "down",
+            Code::Home => "home",
+            Code::End => "end",
+            Code::PageUp => "pageup",
+            Code::PageDown => "pagedown",
+            Code::Other => "other",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl From<Code> for Key {
+    fn from(code: Code) -> Self {
+        Self::new(code)
+    }
+}
+
+// always compiled since the editor reads its events through crossterm,
+// the public From impl is behind the crossterm_keys feature
+pub(crate) fn from_crossterm(event: crossterm::event::KeyEvent) -> Key {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    let code = match event.code {
+        KeyCode::Char(c) => Code::Char(c),
+        KeyCode::Enter => Code::Enter,
+        KeyCode::Tab => Code::Tab,
+        KeyCode::BackTab => Code::BackTab,
+        KeyCode::Backspace => Code::Backspace,
+        KeyCode::Delete => Code::Delete,
+        KeyCode::Insert => Code::Insert,
+        KeyCode::Esc => Code::Esc,
+        KeyCode::Left => Code::Left,
+        KeyCode::Right => Code::Right,
+        KeyCode::Up => Code::Up,
+        KeyCode::Down => Code::Down,
+        KeyCode::Home => Code::Home,
+        KeyCode::End => Code::End,
+        KeyCode::PageUp => Code::PageUp,
+        KeyCode::PageDown => Code::PageDown,
+        KeyCode::F(n) => Code::F(n),
+        _ => Code::Other,
+    };
+    let mods = Mods {
+        ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        alt: event.modifiers.contains(KeyModifiers::ALT),
+        // the shift of a char is already in the char itself
+        shift: event.modifiers.contains(KeyModifiers::SHIFT) && !matches!(code, Code::Char(_)),
+    };
+
+    Key { code, mods }
+}
+
+#[cfg(feature = "crossterm_keys")]
+impl From<crossterm::event::KeyEvent> for Key {
+    fn from(event: crossterm::event::KeyEvent) -> Self {
+        from_crossterm(event)
+    }
+}
+
+#[cfg(all(unix, feature = "termion"))]
+impl From<termion::event::Key> for Key {
+    fn from(key: termion::event::Key) -> Self {
+        use termion::event::Key as K;
+
+        let (code, mods) = match key {
+            // termion reports Enter and Tab as their chars
+            K::Char('\n') | K::Char('\r') => (Code::Enter, Mods::NONE),
+            K::Char('\t') => (Code::Tab, Mods::NONE),
+            K::Char(c) => (Code::Char(c), Mods::NONE),
+            K::Alt(c) => (Code::Char(c), Mods::ALT),
+            K::Ctrl(c) => (Code::Char(c), Mods::CTRL),
+            K::Backspace => (Code::Backspace, Mods::NONE),
+            K::Left => (Code::Left, Mods::NONE),
+            K::ShiftLeft => (Code::Left, Mods::SHIFT),
+            K::AltLeft => (Code::Left, Mods::ALT),
+            K::CtrlLeft => (Code::Left, Mods::CTRL),
+            K::Right => (Code::Right, Mods::NONE),
+            K::ShiftRight => (Code::Right, Mods::SHIFT),
+            K::AltRight => (Code::Right, Mods::ALT),
+            K::CtrlRight => (Code::Right, Mods::CTRL),
+            K::Up => (Code::Up, Mods::NONE),
+            K::ShiftUp => (Code::Up, Mods::SHIFT),
+            K::AltUp => (Code::Up, Mods::ALT),
+            K::CtrlUp => (Code::Up, Mods::CTRL),
+            K::Down => (Code::Down, Mods::NONE),
+            K::ShiftDown => (Code::Down, Mods::SHIFT),
+            K::AltDown => (Code::Down, Mods::ALT),
+            K::CtrlDown => (Code::Down, Mods::CTRL),
+            K::Home => (Code::Home, Mods::NONE),
+            K::CtrlHome => (Code::Home, Mods::CTRL),
+            K::End => (Code::End, Mods::NONE),
+            K::CtrlEnd => (Code::End, Mods::CTRL),
+            K::PageUp => (Code::PageUp, Mods::NONE),
+            K::PageDown => (Code::PageDown, Mods::NONE),
+            K::BackTab => (Code::BackTab, Mods::NONE),
+            K::Delete => (Code::Delete, Mods::NONE),
+            K::Insert => (Code::Insert, Mods::NONE),
+            K::F(n) => (Code::F(n), Mods::NONE),
+            K::Esc => (Code::Esc, Mods::NONE),
+            _ => (Code::Other, Mods::NONE),
+        };
+
+        Self { code, mods }
+    }
+}
+
+/// Only key events convert, any other event is handed back as the error
+#[cfg(all(unix, feature = "termion"))]
+impl TryFrom<termion::event::Event> for Key {
+    type Error = termion::event::Event;
+
+    fn try_from(event: termion::event::Event) -> Result<Self, Self::Error> {
+        match event {
+            termion::event::Event::Key(key) => Ok(key.into()),
+            event => Err(event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_key {
+    use super::{from_crossterm, Code, Key, Mods};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn test_from_crossterm() {
+        let convert = |code, mods| from_crossterm(KeyEvent::new(code, mods));
+
+        assert_eq!(
+            convert(KeyCode::Char('a'), KeyModifiers::CONTROL),
+            Key::ctrl('a')
+        );
+        assert_eq!(
+            convert(KeyCode::Char('A'), KeyModifiers::SHIFT),
+            Key::char('A')
+        );
+        assert_eq!(
+            convert(KeyCode::Left, KeyModifiers::SHIFT | KeyModifiers::ALT),
+            Key::with(
+                Code::Left,
+                Mods {
+                    alt: true,
+                    shift: true,
+                    ..Mods::NONE
+                }
+            )
+        );
+        assert_eq!(
+            convert(KeyCode::CapsLock, KeyModifiers::NONE).code,
+            Code::Other
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("a".parse(), Ok(Key::char('a')));
+        assert_eq!("-".parse(), Ok(Key::char('-')));
+        assert_eq!("Ctrl-R".parse(), Ok(Key::ctrl('R')));
+        assert_eq!("alt-.".parse(), Ok(Key::alt('.')));
+        assert_eq!("ctrl-space".parse(), Ok(Key::ctrl(' ')));
+        assert_eq!(
+            "ctrl-alt-left".parse(),
+            Ok(Key::with(
+                Code::Left,
+                Mods {
+                    ctrl: true,
+                    alt: true,
+                    ..Mods::NONE
+                }
+            ))
+        );
+        assert_eq!("F12".parse(), Ok(Key::new(Code::F(12))));
+        assert!("ctrl-".parse::<Key>().is_err());
+        assert!("hyper-a".parse::<Key>().is_err());
+
+        for key in [
+            Key::ctrl(' '),
+            Key::alt('-'),
+            Key::with(Code::F(5), Mods::SHIFT),
+            Key::new(Code::PageDown),
+        ] {
+            assert_eq!(key.to_string().parse(), Ok(key));
+        }
+        assert_eq!(Key::ctrl('r').to_string(), "ctrl-r");
+    }
+
+    #[cfg(all(unix, feature = "termion"))]
+    #[test]
+    fn test_from_termion() {
+        use termion::event::{Event, Key as K};
+
+        assert_eq!(Key::from(K::Char('\n')), Key::new(Code::Enter));
+        assert_eq!(Key::from(K::Ctrl('r')), Key::ctrl('r'));
+        assert_eq!(Key::from(K::CtrlLeft), Key::with(Code::Left, Mods::CTRL));
+        assert_eq!(Key::try_from(Event::Key(K::Alt('.'))), Ok(Key::alt('.')));
+        assert!(Key::try_from(Event::Unsupported(vec![0])).is_err());
+    }
+}
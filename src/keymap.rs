@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use crate::key::{Code, Key, Mods, ParseKeyError};
+use crate::ops::InputOp;
+
+// the emacs style bindings of Keymap::default
+const EMACS: [(Key, InputOp); 27] = [
+    (Key::ctrl('a'), InputOp::MoveHome),
+    (Key::new(Code::Home), InputOp::MoveHome),
+    (Key::ctrl('e'), InputOp::MoveEnd),
+    (Key::new(Code::End), InputOp::MoveEnd),
+    (Key::ctrl('u'), InputOp::ClearLeft),
+    (Key::ctrl('k'), InputOp::ClearRight),
+    (Key::with(Code::Left, Mods::CTRL), InputOp::JumpLeft),
+    (Key::with(Code::Right, Mods::CTRL), InputOp::JumpRight),
+    (Key::new(Code::Left), InputOp::MoveLeft),
+    (Key::new(Code::Right), InputOp::MoveRight),
+    (
+        Key::with(Code::Backspace, Mods::ALT),
+        InputOp::DeleteWordLeft,
+    ),
+    (
+        Key::with(Code::Delete, Mods::CTRL),
+        InputOp::DeleteJumpRight,
+    ),
+    (Key::new(Code::Backspace), InputOp::Backspace),
+    (Key::new(Code::Delete), InputOp::DeleteChar),
+    (Key::new(Code::Up), InputOp::HistoryPrev),
+    (Key::new(Code::Down), InputOp::HistoryNext),
+    (Key::new(Code::PageUp), InputOp::HistoryPrevMatching),
+    (Key::new(Code::PageDown), InputOp::HistoryNextMatching),
+    (Key::alt('r'), InputOp::RevertLine),
+    (Key::ctrl('y'), InputOp::Yank),
+    (Key::alt('y'), InputOp::YankPop),
+    (Key::ctrl('_'), InputOp::Undo),
+    (Key::ctrl('7'), InputOp::Undo),
+    (Key::ctrl('z'), InputOp::Undo),
+    (Key::alt('_'), InputOp::Redo),
+    (Key::alt('d'), InputOp::DeleteWordRight),
+    (Key::alt('t'), InputOp::TransposeWords),
+];
+
+/// The key bindings of the editing operations, see [`crate::Editor::keymap`]
+///
+/// the chars typed without Ctrl or Alt insert themselves unless bound to something else, the
+/// other keys without a binding of their own fall back to the binding of the bare key (Shift-Up
+/// is Up), the keys of the editor commands (Enter, Tab, Ctrl-R, ...) are not part of the Keymap, but
+/// binding one of them overrides the command, Ctrl-C excepted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: HashMap<Key, InputOp>,
+}
+
+impl Default for Keymap {
+    /// The emacs style bindings: Ctrl-A/Ctrl-E, Ctrl-U/Ctrl-K, Ctrl-Y/Alt-Y, Ctrl-_, Alt-d,
+    /// Alt-Backspace, Delete, Alt-t, PageUp/PageDown for the history entries starting with the
+    /// typed line ...; Ctrl-T opens the fuzzy finder, `transpose-chars` is left for the users to
+    /// bind
+    ///
+    /// binding Up and Down to `history-prev-matching` and `history-next-matching` gives the
+    /// history-beginning-search of zsh
+    fn default() -> Self {
+        Self {
+            bindings: EMACS.into_iter().collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Creates a new Keymap without any binding, only the chars get inserted
+    pub fn new() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Binds a key to an operation, returns the operation the key was bound to
+    pub fn bind(&mut self, key: Key, op: InputOp) -> Option<InputOp> {
+        self.bindings.insert(key, op)
+    }
+
+    /// Removes the binding of a key, a char then inserts itself again
+    pub fn unbind(&mut self, key: &Key) -> Option<InputOp> {
+        self.bindings.remove(key)
+    }
+
+    /// Returns the operation bound to a key, if any
+    pub fn get(&self, key: &Key) -> Option<&InputOp> {
+        self.bindings.get(key)
+    }
+
+    /// Returns the operation a key press results in: its binding, the binding of the bare key,
+    /// or the insertion of its char
+    pub fn op(&self, key: Key) -> Option<InputOp> {
+        if let Some(op) = self.bindings.get(&key) {
+            return Some(op.clone());
+        }
+        match (key.code, key.mods.ctrl || key.mods.alt) {
+            (Code::Char(c), false) => Some(InputOp::InsertChar(c)),
+            (Code::Char(_), true) => None,
+            (code, _) => self.bindings.get(&Key::new(code)).cloned(),
+        }
+    }
+
+    /// Binds a key to an operation by their names, e.g. `("ctrl-b", "move-left")`, see
+    /// [`Key::from_str`](std::str::FromStr) and [`InputOp::from_name`]
+    ///
+    /// # Errors
+    /// Returns a [`KeymapError`] if either name is unknown
+    pub fn bind_names(&mut self, key: &str, op: &str) -> Result<Option<InputOp>, KeymapError> {
+        let key = key.parse::<Key>().map_err(KeymapError::Key)?;
+        let op = InputOp::from_name(op).ok_or_else(|| KeymapError::Op(op.to_string()))?;
+
+        Ok(self.bind(key, op))
+    }
+
+    /// Creates the default Keymap overridden by named bindings, e.g. read from a config file,
+    /// see [`Keymap::bind_names`]
+    ///
+    /// # Errors
+    /// Returns the [`KeymapError`] of the first binding that does not parse
+    pub fn from_config<'a>(
+        bindings: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self, KeymapError> {
+        let mut keymap = Self::default();
+        for (key, op) in bindings {
+            keymap.bind_names(key, op)?;
+        }
+
+        Ok(keymap)
+    }
+}
+
+/// The error of a binding [`Keymap::bind_names`] could not parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapError {
+    Key(ParseKeyError),
+    /// the operation name is unknown
+    Op(String),
+}
+
+impl std::fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Key(e) => e.fmt(f),
+            Self::Op(name) => write!(f, "unknown operation `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+impl InputOp {
+    /// Returns the operation of a name: the name of an operation without data in kebab case
+    /// (e.g. `move-home`, `delete-jump-left`, `yank-pop`), or `insert:` followed by the text to
+    /// insert
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(text) = name.strip_prefix("insert:") {
+            return Some(Self::InsertStr(text.to_string()));
+        }
+
+        Some(match name.to_ascii_lowercase().as_str() {
+            "backspace" => Self::Backspace,
+            "delete-char" => Self::DeleteChar,
+            "move-left" => Self::MoveLeft,
+            "move-right" => Self::MoveRight,
+            "move-home" => Self::MoveHome,
+            "move-end" => Self::MoveEnd,
+            "jump-left" => Self::JumpLeft,
+            "jump-right" => Self::JumpRight,
+            "delete-jump-left" => Self::DeleteJumpLeft,
+            "delete-jump-right" => Self::DeleteJumpRight,
+            "delete-word-right" => Self::DeleteWordRight,
+            "delete-word-left" => Self::DeleteWordLeft,
+            "transpose-chars" => Self::TransposeChars,
+            "transpose-words" => Self::TransposeWords,
+            "clear-line" => Self::ClearLine,
+            "clear-left" => Self::ClearLeft,
+            "clear-right" => Self::ClearRight,
+            "history-prev" => Self::HistoryPrev,
+            "history-next" => Self::HistoryNext,
+            "history-prev-matching" => Self::HistoryPrevMatching,
+            "history-next-matching" => Self::HistoryNextMatching,
+            "revert-line" => Self::RevertLine,
+            "yank" => Self::Yank,
+            "yank-pop" => Self::YankPop,
+            "undo" => Self::Undo,
+            "redo" => Self::Redo,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_keymap {
+    use super::{Keymap, KeymapError};
+    use crate::key::{Code, Key, Mods};
+    use crate::ops::InputOp;
+
+    #[test]
+    fn test_bindings() {
+        let mut keymap = Keymap::default();
+        assert_eq!(keymap.op(Key::ctrl('a')), Some(InputOp::MoveHome));
+        assert_eq!(keymap.op(Key::char('a')), Some(InputOp::InsertChar('a')));
+        assert_eq!(keymap.op(Key::ctrl('q')), None);
+        assert_eq!(keymap.op(Key::new(Code::Delete)), Some(InputOp::DeleteChar));
+        assert_eq!(
+            keymap.op(Key::with(Code::Backspace, Mods::ALT)),
+            Some(InputOp::DeleteWordLeft)
+        );
+        assert_eq!(
+            keymap.op(Key::with(Code::Up, Mods::SHIFT)),
+            Some(InputOp::HistoryPrev)
+        );
+
+        assert_eq!(
+            keymap.bind(Key::ctrl('a'), InputOp::Undo),
+            Some(InputOp::MoveHome)
+        );
+        assert_eq!(
+            keymap.unbind(&Key::new(Code::Home)),
+            Some(InputOp::MoveHome)
+        );
+        assert_eq!(keymap.op(Key::new(Code::Home)), None);
+        assert!(Keymap::new().get(&Key::ctrl('e')).is_none());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let keymap =
+            Keymap::from_config([("ctrl-b", "move-left"), ("alt-g", "insert:git ")]).unwrap();
+        assert_eq!(keymap.op(Key::ctrl('b')), Some(InputOp::MoveLeft));
+        assert_eq!(
+            keymap.op(Key::with(Code::Char('g'), Mods::ALT)),
+            Some(InputOp::InsertStr("git ".to_string()))
+        );
+        assert_eq!(keymap.op(Key::ctrl('e')), Some(InputOp::MoveEnd));
+
+        assert_eq!(
+            Keymap::from_config([("ctrl-b", "fly")]),
+            Err(KeymapError::Op("fly".to_string()))
+        );
+        assert!(matches!(
+            Keymap::from_config([("hyper-b", "undo")]),
+            Err(KeymapError::Key(_))
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+use std::ops::Range;
+
+use crate::input::Input;
+
+/// The texts killed or copied from the Input, newest last, like emacs's kill ring
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillRing {
+    entries: Vec<String>,
+    /// the number of entries kept, the oldest ones get dropped first
+    pub max: usize,
+    // the Input cursor and changes right after the last kill, a kill starting from there
+    // grows the newest entry instead of pushing a new one
+    killed: Option<(usize, usize)>,
+    // the text inserted by the last yank
+    yanked: Option<Yanked>,
+}
+
+// what Input::yank_pop needs to replace the text of the last yank
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Yanked {
+    // the chars of the Input holding the yanked text
+    span: Range<usize>,
+    // how many entries back from the newest the yanked text is
+    back: usize,
+    // the Input changes right after the yank
+    changes: usize,
+}
+
+impl Default for KillRing {
+    fn default() -> Self {
+        Self::new(60)
+    }
+}
+
+impl KillRing {
+    /// Creates a new empty KillRing keeping up to max entries
+    pub fn new(max: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            max,
+            killed: None,
+            yanked: None,
+        }
+    }
+
+    /// Adds a new entry to the KillRing, empty texts are ignored
+    pub fn push(&mut self, text: String) {
+        if text.is_empty() || self.max == 0 {
+            return;
+        }
+        if self.entries.len() == self.max {
+            self.entries.remove(0);
+        }
+        self.entries.push(text);
+    }
+
+    /// Returns the newest entry
+    pub fn latest(&self) -> Option<&str> {
+        self.entries.last().map(String::as_str)
+    }
+
+    /// Returns the entries, oldest first
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Returns the number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the KillRing has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // the entry the given number of entries back from the newest one
+    fn back(&self, back: usize) -> Option<&str> {
+        let idx = self.entries.len().checked_sub(back + 1)?;
+
+        Some(&self.entries[idx])
+    }
+}
+
+impl Input {
+    /// Inserts the newest kill ring entry at the cursor
+    ///
+    /// returns false if the kill ring is empty
+    pub fn yank(&mut self) -> bool {
+        let Some(text) = self.kill_ring.latest().map(str::to_string) else {
+            return false;
+        };
+        self.insert_yanked(&text, 0);
+
+        true
+    }
+
+    /// Replaces the text inserted by the previous yank (or yank pop) with the kill ring entry
+    /// older than it, the oldest entry is followed by the newest one again
+    ///
+    /// returns false if the line was edited or the cursor moved since the yank
+    pub fn yank_pop(&mut self) -> bool {
+        let Some(yanked) = self.kill_ring.yanked.take() else {
+            return false;
+        };
+        if yanked.changes != self.changes || yanked.span.end != self.cursor {
+            return false;
+        }
+        let back = (yanked.back + 1) % self.kill_ring.len();
+        let text = self.kill_ring.back(back).unwrap_or_default().to_string();
+        self.values.drain(yanked.span.clone());
+        self.cursor = yanked.span.start;
+        self.insert_yanked(&text, back);
+
+        true
+    }
+
+    // inserts a kill ring entry at the cursor, remembering where for yank_pop
+    fn insert_yanked(&mut self, text: &str, back: usize) {
+        let start = self.cursor;
+        self.put_str(text.chars());
+        self.kill_ring.yanked = Some(Yanked {
+            span: start..self.cursor,
+            back,
+            changes: self.changes,
+        });
+    }
+
+    // puts the text removed by a kill on the kill ring, before being the cursor and changes of
+    // the Input before the kill: a kill following another one grows the same entry, the text
+    // of the backward kills going in front
+    pub(crate) fn killed(&mut self, text: String, backward: bool, before: (usize, usize)) {
+        // the secrets typed in a sensitive line must not outlive it
+        if text.is_empty() || self.sensitive {
+            return;
+        }
+        let ring = &mut self.kill_ring;
+        match (ring.killed == Some(before), ring.entries.last_mut()) {
+            (true, Some(last)) if backward => last.insert_str(0, &text),
+            (true, Some(last)) => last.push_str(&text),
+            _ => ring.push(text),
+        }
+        ring.killed = Some((self.cursor, self.changes));
+    }
+
+    /// Returns the text from the start of the word before the cursor up to the cursor,
+    /// what a backward word kill would remove
+    pub fn copy_word_before(&self) -> Option<String> {
+        let mut start = self.cursor;
+        while start > 0 && self.values[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !self.values[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        text(&self.values[start..self.cursor])
+    }
+
+    /// Returns the text from the cursor up to the end of the word after it,
+    /// what a forward word kill would remove
+    pub fn copy_word_after(&self) -> Option<String> {
+        let mut end = self.cursor;
+        while end < self.values.len() && self.values[end].is_whitespace() {
+            end += 1;
+        }
+        while end < self.values.len() && !self.values[end].is_whitespace() {
+            end += 1;
+        }
+
+        text(&self.values[self.cursor..end])
+    }
+
+    /// Returns the selected text, see [`Input::selection`]
+    pub fn copy_selection(&self) -> Option<String> {
+        text(&self.values[self.selection()?])
+    }
+
+    /// Returns the whole Input line
+    pub fn copy_line(&self) -> Option<String> {
+        text(&self.values)
+    }
+}
+
+fn text(chars: &[char]) -> Option<String> {
+    match chars.is_empty() {
+        true => None,
+        false => Some(chars.iter().collect()),
+    }
+}
+
+/// Returns the OSC 52 escape putting text on the system clipboard of terminals supporting it,
+/// which also works over ssh
+pub fn osc52(text: &str) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(text.len().div_ceil(3) * 4);
+    text.as_bytes().chunks(3).for_each(|chunk| {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (idx, b)| bits | (*b as u32) << (16 - idx * 8));
+        (0..4).for_each(|idx| match idx <= chunk.len() {
+            true => encoded.push(ALPHABET[(bits >> (18 - idx * 6)) as usize & 63] as char),
+            false => encoded.push('='),
+        });
+    });
+
+    format!("\x1b]52;c;{}\x07", encoded)
+}
+
+#[cfg(test)]
+mod test_kill {
+    use super::{osc52, KillRing};
+    use crate::history::History;
+    use crate::input::Input;
+    use crate::ops::InputOp;
+
+    #[test]
+    fn test_kill_ring() {
+        let mut ring = KillRing::new(2);
+        ["a", "", "b", "c"]
+            .into_iter()
+            .for_each(|t| ring.push(t.to_string()));
+
+        assert_eq!(ring.entries(), ["b", "c"]);
+        assert_eq!(ring.latest(), Some("c"));
+    }
+
+    #[test]
+    fn test_copy() {
+        let mut i = Input::new("> ", false);
+        i.set_text("git commit  -m wip");
+        i.set_cursor(12);
+
+        assert_eq!(i.copy_word_before().as_deref(), Some("commit  "));
+        assert_eq!(i.copy_word_after().as_deref(), Some("-m"));
+        assert_eq!(i.copy_line().as_deref(), Some("git commit  -m wip"));
+        assert_eq!(i.values.len(), 18);
+        assert_eq!(osc52("pika"), "\x1b]52;c;cGlrYQ==\x07");
+    }
+
+    #[test]
+    fn test_yank() {
+        let mut i = Input::new("> ", false);
+        let mut h = History::new();
+        i.set_text("cargo test release");
+        assert!(i.apply(&InputOp::DeleteJumpLeft, &mut h));
+        assert!(i.apply(&InputOp::DeleteJumpLeft, &mut h));
+        assert_eq!(i.kill_ring.entries(), [" test release"]);
+
+        assert!(i.apply(&InputOp::MoveHome, &mut h));
+        assert!(i.apply(&InputOp::ClearRight, &mut h));
+        assert_eq!(i.kill_ring.entries(), [" test release", "cargo"]);
+
+        assert!(i.apply(&InputOp::Yank, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "cargo");
+        assert!(i.apply(&InputOp::YankPop, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), " test release");
+        assert!(i.apply(&InputOp::YankPop, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "cargo");
+
+        assert!(i.apply(&InputOp::MoveLeft, &mut h));
+        assert!(!i.apply(&InputOp::YankPop, &mut h));
+    }
+}
@@ -0,0 +1,200 @@
+use std::ops::Range;
+
+use crate::screen::Screen;
+
+/// A region of the prompt area, the variants are declared from the highest to the lowest
+/// priority when rows are short
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Region {
+    /// the prompt and input rows
+    Input,
+    /// the status row
+    Status,
+    /// the hint row, e.g. autosuggestions or diagnostics
+    Hint,
+    /// the completion menu
+    Menu,
+}
+
+/// The number of rows a region asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraint {
+    /// the rows the region needs to be shown at all, regions that can't get them are hidden
+    pub min: usize,
+    /// the rows the region would use if there was room for them
+    pub max: usize,
+}
+
+impl Constraint {
+    /// Creates a new Constraint
+    pub const fn new(min: usize, max: usize) -> Self {
+        Self { min, max }
+    }
+
+    /// A Constraint on exactly n rows
+    pub const fn exact(n: usize) -> Self {
+        Self { min: n, max: n }
+    }
+}
+
+/// Splits the rows of the prompt area between the UI regions stacked on top of each other
+///
+/// every region first gets its minimum rows then the remaining rows go to the regions wanting
+/// more, both in [`Region`] priority order, so a shrinking terminal takes the rows away from the
+/// menu first and from the input last
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    /// the regions and their constraints, in top to bottom order
+    pub regions: Vec<(Region, Constraint)>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            regions: vec![
+                (Region::Input, Constraint::new(1, 1)),
+                (Region::Hint, Constraint::new(0, 0)),
+                (Region::Menu, Constraint::new(0, 0)),
+                (Region::Status, Constraint::new(0, 0)),
+            ],
+        }
+    }
+}
+
+impl Layout {
+    /// Creates the default Layout: input rows, hint row, menu and status row,
+    /// where only one input row is asked for
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the constraint of a region, appending it at the bottom if it is not in the Layout
+    pub fn set(&mut self, region: Region, constraint: Constraint) {
+        match self.regions.iter_mut().find(|(r, _)| *r == region) {
+            Some((_, c)) => *c = constraint,
+            None => self.regions.push((region, constraint)),
+        }
+    }
+
+    /// Returns the rows of each region for an area of the given height,
+    /// regions that get no rows are left out
+    pub fn split(&self, height: usize) -> Vec<(Region, Range<usize>)> {
+        let mut order = (0..self.regions.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|idx| self.regions[*idx].0);
+
+        let mut rows = vec![0; self.regions.len()];
+        let mut left = height;
+        for idx in &order {
+            let min = self.regions[*idx].1.min;
+            if min <= left {
+                rows[*idx] = min;
+                left -= min;
+            }
+        }
+        for idx in &order {
+            let c = self.regions[*idx].1;
+            // regions that didn't get their minimum stay hidden
+            if rows[*idx] < c.min {
+                continue;
+            }
+            let extra = c.max.saturating_sub(rows[*idx]).min(left);
+            rows[*idx] += extra;
+            left -= extra;
+        }
+
+        let mut top = 0;
+        self.regions
+            .iter()
+            .zip(rows)
+            .filter(|(_, n)| *n > 0)
+            .map(|((region, _), n)| {
+                top += n;
+                (*region, top - n..top)
+            })
+            .collect()
+    }
+
+    /// Returns the rows of a region for an area of the given height
+    pub fn area(&self, height: usize, region: Region) -> Option<Range<usize>> {
+        self.split(height)
+            .into_iter()
+            .find_map(|(r, rows)| (r == region).then_some(rows))
+    }
+
+    /// Negotiates the rows of the regions for a terminal of the given size then resizes the
+    /// Screen to the rows actually used, to be called on every terminal resize
+    pub fn fit(
+        &self,
+        screen: &mut Screen,
+        width: usize,
+        height: usize,
+    ) -> Vec<(Region, Range<usize>)> {
+        let split = self.split(height);
+        let rows = split.last().map_or(0, |(_, rows)| rows.end);
+        if screen.size() != (width, rows) {
+            screen.resize(width, rows);
+        }
+
+        split
+    }
+
+    /// Returns the total number of rows the Layout would use if there was room for them
+    pub fn height(&self) -> usize {
+        self.regions.iter().map(|(_, c)| c.max.max(c.min)).sum()
+    }
+}
+
+#[cfg(test)]
+mod test_layout {
+    use super::{Constraint, Layout, Region};
+    use crate::screen::Screen;
+
+    fn layout() -> Layout {
+        let mut l = Layout::new();
+        l.set(Region::Hint, Constraint::new(0, 1));
+        l.set(Region::Menu, Constraint::new(2, 5));
+        l.set(Region::Status, Constraint::exact(1));
+
+        l
+    }
+
+    #[test]
+    fn test_split() {
+        let l = layout();
+        assert_eq!(l.height(), 8);
+        assert_eq!(
+            l.split(10),
+            vec![
+                (Region::Input, 0..1),
+                (Region::Hint, 1..2),
+                (Region::Menu, 2..7),
+                (Region::Status, 7..8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shrink() {
+        let l = layout();
+        // the menu shrinks down to its minimum first, then the hint goes, then the menu
+        assert_eq!(
+            l.split(5),
+            vec![
+                (Region::Input, 0..1),
+                (Region::Hint, 1..2),
+                (Region::Menu, 2..4),
+                (Region::Status, 4..5),
+            ]
+        );
+        assert_eq!(l.area(4, Region::Hint), None);
+        assert_eq!(l.area(4, Region::Menu), Some(1..3));
+        assert_eq!(
+            l.split(2),
+            vec![(Region::Input, 0..1), (Region::Status, 1..2)]
+        );
+
+        let mut screen = Screen::new(80, 1);
+        l.fit(&mut screen, 40, 3);
+        assert_eq!(screen.size(), (40, 3));
+    }
+}
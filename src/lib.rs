@@ -1,9 +1,116 @@
+#[cfg(feature = "clap")]
+pub mod args;
+pub mod autosuggest;
+pub mod builder;
+pub mod cancel;
+pub mod completion;
+pub mod context;
+pub mod diagnostic;
+mod diff;
+pub mod editor;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+pub mod error;
+pub mod events;
+pub mod expand;
+pub mod finder;
+mod glob;
+pub mod highlight;
+pub mod history;
+pub mod hooks;
 pub mod input;
+mod json;
+pub mod key;
+pub mod keymap;
+pub mod kill;
+pub mod layout;
+pub mod log;
+pub mod middleware;
+pub mod mock;
+pub mod newline;
+pub mod ops;
+pub mod pager;
+pub mod prompts;
+mod regex;
+pub mod remote;
+pub mod repl;
+pub mod replay;
+pub mod screen;
+pub mod search;
+pub mod segments;
+#[cfg(all(unix, feature = "signals"))]
+pub mod signals;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod state;
+pub mod store;
+pub mod style;
+pub mod suggest;
+pub mod term;
+pub mod text;
+pub mod tokens;
+#[cfg(feature = "ratatui")]
+pub mod tui;
+pub mod undo;
+pub mod validate;
+pub mod vi;
+#[cfg(windows)]
+mod windows;
+pub mod winsize;
 
 use std::io::StdoutLock;
 
-pub use input::init;
-pub use input::{History, Input};
+pub use autosuggest::Suggestion;
+pub use builder::EditorBuilder;
+pub use cancel::{CancelToken, Cancelled};
+pub use completion::{Completer, Completion, PathCompleter, WordCompleter};
+pub use context::TermContext;
+pub use diagnostic::{Diagnostic, FnLinter, Linter, Severity};
+pub use editor::Editor;
+#[cfg(feature = "encoding")]
+pub use encoding::Encoding;
+pub use error::RagoutError;
+#[cfg(unix)]
+pub use events::read_events;
+pub use events::{Decoder, KeyEvent, Mouse, MouseKind};
+pub use expand::{EnvVars, Expander, FnExpander};
+pub use finder::{fuzzy_match, FinderSource};
+pub use highlight::{FnHighlighter, Highlighter};
+pub use history::{Dups, History, HistoryPolicy, Usage};
+pub use hooks::{EventHook, FnHook, Hooked};
+pub use input::{deinit, init, init_with, init_with_config, text_width, Init};
+pub use input::{Bell, CharSearch, CursorShape, Indicator, Input, Mode, Position, ScreenCell};
+pub use input::{Terminal, TerminalWriter};
+pub use key::{Code, Key, Mods, ParseKeyError};
+pub use keymap::{Keymap, KeymapError};
+pub use kill::KillRing;
+pub use layout::{Constraint, Layout, Region};
+pub use log::{LogEntry, LogFormat, LogSink, Redaction};
+pub use middleware::{Flow, FnMiddleware, KeyMiddleware};
+pub use mock::MockTerminal;
+pub use newline::{Enter, LineEndings};
+pub use ops::InputOp;
+pub use prompts::{pause, read_key, read_line_timeout, read_password};
+pub use remote::Remote;
+pub use repl::{Outcome, Repl};
+pub use replay::{Recorded, Recorder};
+pub use screen::{Cell, Screen};
+pub use search::Search;
+pub use segments::PromptSegments;
+#[cfg(all(unix, feature = "signals"))]
+pub use signals::install_cleanup;
+pub use state::{Modes, TermStack};
+pub use store::{FileStore, HistoryStore};
+pub use style::{Color, Style};
+#[cfg(unix)]
+pub use term::Termios;
+pub use term::{default_backend, Native, RawConfig, TermBackend};
+pub use term::{disable_raw_mode, enable_raw_mode};
+pub use text::Text;
+pub use tokens::{tokenize, Token};
+pub use undo::{Edit, UndoStack};
+pub use validate::{Brackets, FnValidator, Validation, Validator};
+pub use winsize::{take_resized, watch_resize, window_size, WinSize};
 
 // this trait can be implemented be it at the ragout lib or ragout_custom_events macro, once
 // InputAction has been defined,
@@ -12,17 +119,121 @@ pub use input::{History, Input};
 /// Implemented for Input and History in the ragout crate
 /// # Object Safety
 ///
-/// This trait is NOT [`Object safe`]("https://doc.rust-lang.org/nightly/reference/items/traits.html#object-safety")
+/// This trait is [`Object safe`]("https://doc.rust-lang.org/nightly/reference/items/traits.html#object-safety")
+/// for any given event type, `Vec<Box<dyn DebugLog<E>>>` is itself a DebugLog that logs to
+/// every sink it holds
 pub trait DebugLog<E> {
     fn log(&mut self, event: &E);
 
     fn dl_rfd(&self) -> i32;
+
+    /// Returns the JSON lines record of an event, for the logs that replay tooling and tests
+    /// read back with [`LogEntry::from_json`]: its kind, its Debug payload, the line (redacted
+    /// when secret) and the Input and History cursors, see [`LogEntry::to_json`]
+    fn json_record(&self, kind: &str, event: &E, i: &Input, h: &History) -> String
+    where
+        E: std::fmt::Debug,
+    {
+        LogEntry::new(kind, &format!("{:?}", event), i)
+            .history(h)
+            .to_json()
+    }
+}
+
+impl<E, T: DebugLog<E> + ?Sized> DebugLog<E> for Box<T> {
+    fn log(&mut self, event: &E) {
+        (**self).log(event)
+    }
+
+    fn dl_rfd(&self) -> i32 {
+        (**self).dl_rfd()
+    }
+}
+
+impl<E, T: DebugLog<E>> DebugLog<E> for Vec<T> {
+    fn log(&mut self, event: &E) {
+        self.iter_mut().for_each(|sink| sink.log(event))
+    }
+
+    /// Returns the raw fd of the first sink, or -1 if there is none
+    fn dl_rfd(&self) -> i32 {
+        self.first().map_or(-1, |sink| sink.dl_rfd())
+    }
 }
 
 /// Implements the write method which takes an event and reacts to it.
 /// # Object Safety
 ///
-/// This trait is NOT [`Object safe`]("https://doc.rust-lang.org/nightly/reference/items/traits.html#object-safety")
+/// This trait is [`Object safe`]("https://doc.rust-lang.org/nightly/reference/items/traits.html#object-safety")
+/// for any given event type, `Vec<Box<dyn Writer<E>>>` is itself a Writer that hands every event
+/// to each of its handlers in order
 pub trait Writer<E> {
     fn write(&mut self, h: &mut History, ia: &E, sol: &mut StdoutLock<'_>, ui: &mut String);
 }
+
+impl<E, T: Writer<E> + ?Sized> Writer<E> for Box<T> {
+    fn write(&mut self, h: &mut History, ia: &E, sol: &mut StdoutLock<'_>, ui: &mut String) {
+        (**self).write(h, ia, sol, ui)
+    }
+}
+
+impl<E, T: Writer<E>> Writer<E> for Vec<T> {
+    fn write(&mut self, h: &mut History, ia: &E, sol: &mut StdoutLock<'_>, ui: &mut String) {
+        self.iter_mut().for_each(|w| w.write(h, ia, sol, ui))
+    }
+}
+
+#[cfg(test)]
+mod test_traits {
+    use super::{DebugLog, History, Input, Writer};
+    use std::io::StdoutLock;
+
+    struct Counter(usize);
+
+    impl DebugLog<char> for Counter {
+        fn log(&mut self, _event: &char) {
+            self.0 += 1;
+        }
+
+        fn dl_rfd(&self) -> i32 {
+            self.0 as i32
+        }
+    }
+
+    struct Pusher;
+
+    impl Writer<char> for Pusher {
+        fn write(
+            &mut self,
+            _h: &mut History,
+            ia: &char,
+            _sol: &mut StdoutLock<'_>,
+            ui: &mut String,
+        ) {
+            ui.push(*ia);
+        }
+    }
+
+    #[test]
+    fn test_dyn_debug_log() {
+        let mut sinks: Vec<Box<dyn DebugLog<char>>> =
+            vec![Box::new(Counter(0)), Box::new(Counter(5))];
+
+        sinks.log(&'a');
+        sinks.log(&'b');
+        assert_eq!(sinks.dl_rfd(), 2);
+        let record = sinks.json_record("key", &'a', &Input::new("> ", false), &History::new());
+        assert!(record.starts_with(r#"{"event":"key","payload":"'a'","line":""#));
+        assert_eq!(sinks[1].dl_rfd(), 7);
+    }
+
+    #[test]
+    fn test_dyn_writer() {
+        let mut writers: Vec<Box<dyn Writer<char>>> = vec![Box::new(Pusher), Box::new(Pusher)];
+        let mut h = History::new();
+        let mut ui = String::new();
+
+        writers.write(&mut h, &'x', &mut std::io::stdout().lock(), &mut ui);
+        assert_eq!(ui, "xx");
+    }
+}
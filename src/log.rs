@@ -0,0 +1,399 @@
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::history::History;
+use crate::input::Input;
+use crate::json::Json;
+use crate::regex::Regex;
+
+/// The text written to the debug logs in place of anything redacted
+pub const REDACTED: &str = "<redacted>";
+
+/// A debug log record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub timestamp: SystemTime,
+    /// what the record is about, e.g. `key`, `render` or `submit`
+    pub kind: String,
+    pub message: String,
+    /// the cursor of the Input or History when the record was made
+    pub cursor: usize,
+    /// the number of chars of the line, or of entries of the History
+    pub len: usize,
+    /// the line when the record was made, redacted when secret
+    pub line: String,
+    /// the History cursor when the record was made, if known
+    pub history_cursor: Option<usize>,
+}
+
+impl LogEntry {
+    /// Creates a new LogEntry of the current state of an Input, the message and line get
+    /// redacted if the line is secret
+    pub fn new(kind: &str, message: &str, i: &Input) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            kind: kind.to_string(),
+            message: match i.is_secret() {
+                true => REDACTED.to_string(),
+                false => message.to_string(),
+            },
+            cursor: i.cursor,
+            len: i.values.len(),
+            line: i.log_snapshot(),
+            history_cursor: None,
+        }
+    }
+
+    /// Records the History cursor along with the Input state
+    pub fn history(mut self, h: &History) -> Self {
+        self.history_cursor = Some(h.cursor);
+        self
+    }
+
+    /// Returns the entry as a single line JSON object, with the `event` kind, the `payload`
+    /// message, the `line`, `cursor` and `len` of the Input, the `history_cursor` (or null) and
+    /// the `time` in milliseconds since the unix epoch
+    pub fn to_json(&self) -> String {
+        let millis = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        Json::obj([
+            ("event", self.kind.as_str().into()),
+            ("payload", self.message.as_str().into()),
+            ("line", self.line.as_str().into()),
+            ("cursor", self.cursor.into()),
+            ("len", self.len.into()),
+            (
+                "history_cursor",
+                self.history_cursor.map_or(Json::Null, Json::from),
+            ),
+            ("time", Json::Num(millis as f64)),
+        ])
+        .to_string()
+    }
+
+    /// Parses a JSON line written by [`LogEntry::to_json`], e.g. to replay a log or make
+    /// assertions on it; None if it is malformed
+    pub fn from_json(line: &str) -> Option<Self> {
+        let json = Json::parse(line)?;
+        let num = |key: &str| json.get(key)?.as_num();
+        let text = |key: &str| json.get(key)?.as_str().map(str::to_string);
+
+        Some(Self {
+            timestamp: UNIX_EPOCH + Duration::from_millis(num("time")? as u64),
+            kind: text("event")?,
+            message: text("payload")?,
+            cursor: num("cursor")? as usize,
+            len: num("len")? as usize,
+            line: text("line")?,
+            history_cursor: num("history_cursor").map(|n| n as usize),
+        })
+    }
+}
+
+/// How a [`LogSink`] writes its records to a file or stderr
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// one line of text per record, see the Display impl of [`LogEntry`]
+    #[default]
+    Text,
+    /// one JSON object per line, see [`LogEntry::to_json`]
+    Json,
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let since = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(
+            f,
+            "{}.{:03} [{}] {}/{} {}",
+            since.as_secs(),
+            since.subsec_millis(),
+            self.kind,
+            self.cursor,
+            self.len,
+            self.message
+        )
+    }
+}
+
+// where the records of a LogSink go
+#[derive(Debug)]
+enum Target {
+    Disabled,
+    Stderr,
+    File(Mutex<std::fs::File>),
+    Memory(Mutex<VecDeque<LogEntry>>, usize),
+}
+
+/// Where the debug log records of an Input or History go: a file, stderr, an in memory ring
+/// buffer, or nowhere, which is the default
+///
+/// shared by the clones of the instance
+#[derive(Debug, Clone)]
+pub struct LogSink {
+    target: Arc<Target>,
+    pub format: LogFormat,
+}
+
+impl Default for LogSink {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl LogSink {
+    /// Creates a LogSink dropping the records
+    pub fn disabled() -> Self {
+        Self {
+            target: Arc::new(Target::Disabled),
+            format: LogFormat::Text,
+        }
+    }
+
+    /// Creates a LogSink writing the records to stderr, one per line
+    pub fn stderr() -> Self {
+        Self {
+            target: Arc::new(Target::Stderr),
+            format: LogFormat::Text,
+        }
+    }
+
+    /// Creates a LogSink writing the records to a file, one per line, truncating it; its parent
+    /// directories get created
+    ///
+    /// # Errors
+    /// Returns the error of creating the directories or the file
+    pub fn file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        Ok(Self {
+            target: Arc::new(Target::File(Mutex::new(std::fs::File::create(path)?))),
+            format: LogFormat::Text,
+        })
+    }
+
+    /// Creates a LogSink keeping the newest capacity records in memory, see
+    /// [`LogSink::entries`]
+    pub fn memory(capacity: usize) -> Self {
+        Self {
+            target: Arc::new(Target::Memory(
+                Mutex::new(VecDeque::with_capacity(capacity)),
+                capacity,
+            )),
+            format: LogFormat::Text,
+        }
+    }
+
+    /// Switches the records written to a file or stderr to another format, e.g.
+    /// `LogSink::file(path)?.with_format(LogFormat::Json)` for JSON lines
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Returns true if the records get dropped
+    pub fn is_disabled(&self) -> bool {
+        matches!(*self.target, Target::Disabled)
+    }
+
+    /// Writes a record, a failed write loses it
+    pub fn write(&self, entry: LogEntry) {
+        match &*self.target {
+            Target::Disabled => (),
+            Target::Stderr => _ = writeln!(std::io::stderr(), "{}", self.line(&entry)),
+            Target::File(file) => {
+                if let Ok(mut file) = file.lock() {
+                    _ = writeln!(file, "{}", self.line(&entry));
+                }
+            }
+            Target::Memory(entries, capacity) => {
+                if let Ok(mut entries) = entries.lock() {
+                    if entries.len() == *capacity {
+                        entries.pop_front();
+                    }
+                    if *capacity > 0 {
+                        entries.push_back(entry);
+                    }
+                }
+            }
+        }
+    }
+
+    // the line a record gets written as
+    fn line(&self, entry: &LogEntry) -> String {
+        match self.format {
+            LogFormat::Text => entry.to_string(),
+            LogFormat::Json => entry.to_json(),
+        }
+    }
+
+    /// Returns the records kept by a memory LogSink, oldest first, none for the other sinks
+    pub fn entries(&self) -> Vec<LogEntry> {
+        match &*self.target {
+            Target::Memory(entries, _) => entries
+                .lock()
+                .map_or(Vec::new(), |entries| entries.iter().cloned().collect()),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the raw fd of a file LogSink, -1 for the other sinks
+    pub fn raw_fd(&self) -> i32 {
+        #[cfg(unix)]
+        if let Target::File(file) = &*self.target {
+            use std::os::fd::AsRawFd;
+            return file.lock().map_or(-1, |file| file.as_raw_fd());
+        }
+
+        -1
+    }
+}
+
+/// Rules deciding which lines never make it to the debug logs in clear
+#[derive(Debug, Clone, Default)]
+pub struct Redaction {
+    /// regex patterns of the lines to redact, matching anywhere in the line, e.g.
+    /// `--password|token=\S+`
+    ///
+    /// the literals, `.`, `[...]` classes, `\d`, `\w` and `\s` classes, `^` and `$` anchors,
+    /// `*`, `+`, `?` and `{m,n}` repetitions and `(...)` groups with `|` alternatives are
+    /// understood; a pattern that fails to parse redacts every line, a typo never letting a
+    /// secret through
+    pub patterns: Vec<String>,
+}
+
+impl Redaction {
+    /// Returns true if the line matches any of the redaction patterns
+    pub fn matches(&self, line: &[char]) -> bool {
+        self.patterns
+            .iter()
+            .any(|p| Regex::new(p).map_or(true, |regex| regex.is_match(line)))
+    }
+}
+
+impl Input {
+    /// Returns true if records about the current line have to be redacted,
+    /// which is always the case for sensitive (masked) lines
+    pub fn is_secret(&self) -> bool {
+        self.sensitive || self.redaction.matches(&self.values)
+    }
+
+    /// Returns the Input values as they are allowed to appear in a log record
+    pub fn log_snapshot(&self) -> String {
+        match self.is_secret() {
+            true => REDACTED.to_string(),
+            false => self.values.iter().collect(),
+        }
+    }
+
+    /// Writes a record to the Input debug log, the whole record gets redacted if the current line
+    /// is secret, key records included
+    pub fn write_log(&mut self, record: &str) {
+        self.log_event("log", record)
+    }
+
+    /// Writes a record of the given kind to the Input [`LogSink`] along with the cursor state,
+    /// redacted like with [`Input::write_log`]
+    #[cfg(any(debug_assertions, feature = "debug_logs"))]
+    pub fn log_event(&mut self, kind: &str, message: &str) {
+        if !self.debug_log.is_disabled() {
+            self.debug_log.write(LogEntry::new(kind, message, self));
+        }
+    }
+
+    /// Writes a record to the Input debug log, no-op when debug logs are disabled
+    #[cfg(not(any(debug_assertions, feature = "debug_logs")))]
+    pub fn log_event(&mut self, _kind: &str, _message: &str) {}
+}
+
+#[cfg(test)]
+mod test_log {
+    use super::{LogEntry, REDACTED};
+    use crate::history::History;
+    use crate::input::Input;
+
+    #[test]
+    fn test_redaction() {
+        let mut i = Input::new("testing input> ", false);
+        i.redaction.patterns.push("--token|key=\\S+".into());
+
+        i.set_text("curl x.y");
+        assert_eq!(i.log_snapshot(), "curl x.y");
+
+        i.set_text("curl --token 123 x.y");
+        assert_eq!(i.log_snapshot(), REDACTED);
+        i.set_text("curl x.y?key=123");
+        assert_eq!(i.log_snapshot(), REDACTED);
+
+        // an invalid pattern redacts every line
+        i.set_text("curl x.y");
+        i.redaction.patterns.push("(".into());
+        assert_eq!(i.log_snapshot(), REDACTED);
+        i.redaction.patterns.pop();
+
+        i.set_text("hunter2");
+        i.sensitive = true;
+        assert!(i.is_secret());
+        assert_eq!(i.log_snapshot(), REDACTED);
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "debug_logs"))]
+    fn test_memory_sink() {
+        use super::LogSink;
+
+        let mut i = Input::new("testing input> ", false);
+        i.debug_log = LogSink::memory(2);
+        let shared = i.clone();
+
+        i.set_text("ls");
+        i.log_event("key", "l");
+        i.write_log("s");
+        i.sensitive = true;
+        i.log_event("submit", "ls");
+        let entries = shared.debug_log.entries();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|e| (e.kind.as_str(), e.message.as_str(), e.cursor))
+                .collect::<Vec<_>>(),
+            [("log", "s", 2), ("submit", REDACTED, 2)]
+        );
+        assert!(entries[0].to_string().ends_with(" [log] 2/2 s"));
+        assert!(LogSink::default().entries().is_empty());
+    }
+
+    #[test]
+    fn test_json_entry() {
+        let mut i = Input::new("testing input> ", false);
+        let h = History::new();
+        i.set_text("say \"hi\"");
+        let mut entry = LogEntry::new("key", "Char('i')", &i).history(&h);
+        entry.timestamp = std::time::UNIX_EPOCH + std::time::Duration::from_millis(1500);
+
+        let json = entry.to_json();
+        assert_eq!(
+            json,
+            r#"{"event":"key","payload":"Char('i')","line":"say \"hi\"","cursor":8,"len":8,"history_cursor":0,"time":1500}"#
+        );
+        assert_eq!(LogEntry::from_json(&json), Some(entry));
+        assert_eq!(LogEntry::from_json(r#"{"event":"key"}"#), None);
+
+        i.sensitive = true;
+        let json = LogEntry::new("submit", "say", &i).to_json();
+        assert!(json.contains(r#""payload":"<redacted>","line":"<redacted>""#));
+        assert!(json.contains(r#""history_cursor":null"#));
+    }
+}
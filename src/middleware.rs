@@ -0,0 +1,92 @@
+use std::fmt::Debug;
+
+use crate::input::Input;
+use crate::key::Key;
+
+/// What a [`KeyMiddleware`] does with a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// hands the key, as it is or modified, to the next middleware or else to the keymap
+    Pass(Key),
+    /// drops the key, neither the next middlewares nor the keymap see it
+    Consume,
+}
+
+/// Sees each key press before the Editor keymap, enabling e.g. disabling the editing while a
+/// task runs or custom chord engines, see [`crate::Editor::middlewares`]
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait KeyMiddleware: Debug {
+    /// Decides what happens to the key, the Input is the line being edited
+    fn intercept(&mut self, key: Key, i: &Input) -> Flow;
+}
+
+/// A KeyMiddleware calling a closure
+pub struct FnMiddleware<F>(pub F);
+
+impl<F> Debug for FnMiddleware<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnMiddleware").finish_non_exhaustive()
+    }
+}
+
+impl<F: FnMut(Key, &Input) -> Flow> KeyMiddleware for FnMiddleware<F> {
+    fn intercept(&mut self, key: Key, i: &Input) -> Flow {
+        (self.0)(key, i)
+    }
+}
+
+/// Runs a key through the middlewares in order, stopping at the first one consuming it
+///
+/// returns the key the keymap gets, if any
+pub fn intercept(chain: &mut [Box<dyn KeyMiddleware>], key: Key, i: &Input) -> Option<Key> {
+    chain
+        .iter_mut()
+        .try_fold(key, |key, middleware| match middleware.intercept(key, i) {
+            Flow::Pass(key) => Some(key),
+            Flow::Consume => None,
+        })
+}
+
+#[cfg(test)]
+mod test_middleware {
+    use super::{intercept, Flow, FnMiddleware, KeyMiddleware};
+    use crate::input::Input;
+    use crate::key::{Code, Key};
+
+    #[test]
+    fn test_intercept() {
+        let i = Input::new("testing input> ", false);
+        let mut chain: Vec<Box<dyn KeyMiddleware>> = vec![
+            // jk chord to Esc
+            Box::new(FnMiddleware({
+                let mut pending = false;
+                move |key: Key, _: &Input| match (std::mem::take(&mut pending), key.code) {
+                    (false, Code::Char('j')) => {
+                        pending = true;
+                        Flow::Consume
+                    }
+                    (true, Code::Char('k')) => Flow::Pass(Key::new(Code::Esc)),
+                    _ => Flow::Pass(key),
+                }
+            })),
+            // read only line
+            Box::new(FnMiddleware(|key: Key, _: &Input| match key.code {
+                Code::Char(_) => Flow::Consume,
+                _ => Flow::Pass(key),
+            })),
+        ];
+
+        assert_eq!(intercept(&mut chain, Key::char('j'), &i), None);
+        assert_eq!(
+            intercept(&mut chain, Key::char('k'), &i),
+            Some(Key::new(Code::Esc))
+        );
+        assert_eq!(intercept(&mut chain, Key::char('k'), &i), None);
+        assert_eq!(
+            intercept(&mut chain, Key::new(Code::Left), &i),
+            Some(Key::new(Code::Left))
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use std::io::Write;
+
+use crate::input::{text_width, TerminalWriter};
+use crate::screen::Screen;
+use crate::style::Style;
+
+/// A fake terminal recording what gets written to it, for testing the rendering without a tty
+///
+/// keeps every written byte and plays the text and the cursor moves, line and screen clears
+/// it understands onto a virtual grid of cells; the styles and modes are left out of the grid
+/// e.g. `i.render(&mut mock)?; assert_eq!(mock.row(0), "> ls");`
+#[derive(Debug, Clone)]
+pub struct MockTerminal {
+    /// every byte written so far, the escapes included
+    pub output: Vec<u8>,
+    /// the number of times the bell was rung
+    pub bells: usize,
+    grid: Screen,
+    // the terminal cursor as (column, row)
+    cursor: (usize, usize),
+    // the cursor saved by ESC 7
+    saved: (usize, usize),
+    // a char written at the last column leaves the cursor there until the next char wraps it
+    wrap_pending: bool,
+    // an escape or utf8 char that got cut by the end of a write
+    pending: Vec<u8>,
+    writes: u64,
+}
+
+impl MockTerminal {
+    /// Creates a new blank MockTerminal of the given size, the cursor in its top left cell
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            output: Vec::new(),
+            bells: 0,
+            grid: Screen::new(columns.max(1), rows.max(1)),
+            cursor: (0, 0),
+            saved: (0, 0),
+            wrap_pending: false,
+            pending: Vec::new(),
+            writes: 0,
+        }
+    }
+
+    /// Returns the text of a row, its trailing blanks trimmed; empty for out of bounds rows
+    pub fn row(&self, row: usize) -> String {
+        let (width, height) = self.grid.size();
+        if row >= height {
+            return String::new();
+        }
+
+        (0..width)
+            .filter_map(|x| self.grid.get(x, row).map(|cell| cell.c))
+            // the cells following a wide char
+            .filter(|c| *c != '\0')
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    /// Returns the text of every row, see [`MockTerminal::row`]
+    pub fn rows(&self) -> Vec<String> {
+        (0..self.grid.size().1).map(|row| self.row(row)).collect()
+    }
+
+    /// Returns the terminal cursor as its 0 based (column, row)
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Returns what was written since the last call, the escapes included
+    pub fn take_output(&mut self) -> String {
+        String::from_utf8_lossy(&std::mem::take(&mut self.output)).into_owned()
+    }
+
+    // plays the pending bytes onto the grid, keeping a cut escape or char for the next write
+    fn play(&mut self) {
+        let bytes = std::mem::take(&mut self.pending);
+        let end = match std::str::from_utf8(&bytes) {
+            Ok(_) => bytes.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            // the invalid bytes get replaced
+            Err(_) => bytes.len(),
+        };
+        let chars = String::from_utf8_lossy(&bytes[..end])
+            .chars()
+            .collect::<Vec<char>>();
+
+        let mut idx = 0;
+        while idx < chars.len() {
+            match self.step(&chars[idx..]) {
+                Some(len) => idx += len,
+                None => break,
+            }
+        }
+        self.pending = chars[idx..].iter().collect::<String>().into_bytes();
+        self.pending.extend_from_slice(&bytes[end..]);
+    }
+
+    // plays the char or escape the chars start with, returns how many chars it took or None if
+    // the escape is cut
+    fn step(&mut self, chars: &[char]) -> Option<usize> {
+        let width = self.grid.size().0;
+        match chars[0] {
+            '\x1b' => return self.escape(chars),
+            '\r' => self.cursor.0 = 0,
+            '\n' => self.line_feed(),
+            '\x07' => self.bells += 1,
+            '\x08' => self.cursor.0 = self.cursor.0.saturating_sub(1),
+            '\t' => self.cursor.0 = ((self.cursor.0 / 8 + 1) * 8).min(width - 1),
+            c if c.is_control() => (),
+            c => {
+                self.print(c, width);
+                return Some(1);
+            }
+        }
+        self.wrap_pending = false;
+
+        Some(1)
+    }
+
+    fn print(&mut self, c: char, width: usize) {
+        let cells = text_width(&c.to_string());
+        // the combining chars go along with the char before them
+        if cells == 0 {
+            return;
+        }
+        if std::mem::take(&mut self.wrap_pending) || self.cursor.0 + cells > width {
+            self.cursor.0 = 0;
+            self.line_feed();
+        }
+
+        let (x, y) = self.cursor;
+        self.grid.put(x, y, c, Style::new());
+        (1..cells).for_each(|dx| self.grid.put(x + dx, y, '\0', Style::new()));
+        self.cursor.0 += cells;
+        if self.cursor.0 >= width {
+            self.cursor.0 = width - 1;
+            self.wrap_pending = true;
+        }
+    }
+
+    // goes down a row, scrolling the grid up from its last row
+    fn line_feed(&mut self) {
+        let (width, height) = self.grid.size();
+        if self.cursor.1 + 1 < height {
+            self.cursor.1 += 1;
+            return;
+        }
+
+        for y in 1..height {
+            for x in 0..width {
+                let cell = *self.grid.get(x, y).unwrap();
+                self.grid.put(x, y - 1, cell.c, cell.style);
+            }
+        }
+        self.grid.clear_row(height - 1);
+    }
+
+    fn escape(&mut self, chars: &[char]) -> Option<usize> {
+        match chars.get(1)? {
+            '[' => {
+                let end = chars[2..]
+                    .iter()
+                    .position(|c| ('\x40'..='\x7e').contains(c))?
+                    + 2;
+                let params = chars[2..end].iter().collect::<String>();
+                self.csi(&params, chars[end]);
+
+                Some(end + 1)
+            }
+            // OSC: up to BEL or ST
+            ']' => {
+                let end = chars
+                    .windows(2)
+                    .position(|w| w[1] == '\x07' || w == ['\x1b', '\\'])?;
+                Some(end + 2)
+            }
+            '7' => {
+                self.saved = self.cursor;
+                Some(2)
+            }
+            '8' => {
+                self.cursor = self.saved;
+                self.wrap_pending = false;
+                Some(2)
+            }
+            _ => Some(2),
+        }
+    }
+
+    fn csi(&mut self, params: &str, action: char) {
+        // the private modes, e.g. the bracketed paste and the alternate screen
+        if params.starts_with('?') || action == 'm' {
+            return;
+        }
+        let (width, height) = self.grid.size();
+        let nums = params
+            .split(';')
+            .map(|p| p.parse::<usize>().unwrap_or(0))
+            .collect::<Vec<usize>>();
+        let arg = |idx: usize| nums.get(idx).copied().filter(|n| *n > 0).unwrap_or(1);
+        let (x, y) = self.cursor;
+        self.wrap_pending = false;
+
+        match action {
+            'A' => self.cursor.1 = y.saturating_sub(arg(0)),
+            'B' => self.cursor.1 = (y + arg(0)).min(height - 1),
+            'C' => self.cursor.0 = (x + arg(0)).min(width - 1),
+            'D' => self.cursor.0 = x.saturating_sub(arg(0)),
+            'G' => self.cursor.0 = (arg(0) - 1).min(width - 1),
+            'H' | 'f' => self.cursor = ((arg(1) - 1).min(width - 1), (arg(0) - 1).min(height - 1)),
+            'J' => match nums[0] {
+                0 => {
+                    self.clear_cells(y, x..width);
+                    (y + 1..height).for_each(|row| self.grid.clear_row(row));
+                }
+                1 => {
+                    (0..y).for_each(|row| self.grid.clear_row(row));
+                    self.clear_cells(y, 0..x + 1);
+                }
+                _ => self.grid.clear(),
+            },
+            'K' => match nums[0] {
+                0 => self.clear_cells(y, x..width),
+                1 => self.clear_cells(y, 0..x + 1),
+                _ => self.grid.clear_row(y),
+            },
+            _ => (),
+        }
+    }
+
+    fn clear_cells(&mut self, row: usize, columns: std::ops::Range<usize>) {
+        columns.for_each(|x| self.grid.put(x, row, ' ', Style::new()));
+    }
+}
+
+impl Write for MockTerminal {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writes += 1;
+        self.output.extend_from_slice(buf);
+        self.pending.extend_from_slice(buf);
+        self.play();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl TerminalWriter for MockTerminal {
+    fn columns(&self) -> usize {
+        self.grid.size().0
+    }
+
+    fn written(&self) -> Option<u64> {
+        Some(self.writes)
+    }
+}
+
+#[cfg(test)]
+mod test_mock {
+    use super::MockTerminal;
+    use crate::history::History;
+    use crate::input::Input;
+    use crate::ops::InputOp;
+    use std::io::Write;
+
+    #[test]
+    fn test_escapes() {
+        let mut term = MockTerminal::new(6, 3);
+        term.write_all(b"abcdef").unwrap();
+        assert_eq!(term.cursor(), (5, 0));
+        term.write_all("g\x1b[2Gx\x07\r\n漢\x1b".as_bytes())
+            .unwrap();
+        assert_eq!(term.rows(), ["abcdef", "gx", "漢"]);
+        assert_eq!(term.cursor(), (2, 2));
+        term.write_all(b"[2A\x1b[K\x1b]0;title\x07\x1b[?2004h\x1b[1m!")
+            .unwrap();
+        assert_eq!(term.rows(), ["ab!", "gx", "漢"]);
+        assert_eq!(term.bells, 1);
+        term.write_all(b"\x1b[3;1H\n").unwrap();
+        assert_eq!(term.rows(), ["gx", "漢", ""]);
+        assert!(term.take_output().starts_with("abcdefg\x1b[2G"));
+        assert!(term.output.is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut term = MockTerminal::new(10, 4);
+        let mut i = Input::new("> ", false);
+        let mut h = History::new();
+        i.set_text("echo hello");
+        i.render(&mut term).unwrap();
+        assert_eq!(term.rows(), ["> echo hel", "lo", "", ""]);
+        assert_eq!(term.cursor(), (2, 1));
+
+        (0..5).for_each(|_| _ = i.apply(&InputOp::MoveLeft, &mut h));
+        i.sync_cursor(&mut term).unwrap();
+        assert_eq!(term.cursor(), (7, 0));
+
+        i.apply(&InputOp::ClearLine, &mut h);
+        i.render(&mut term).unwrap();
+        assert_eq!(term.rows(), [">", "", "", ""]);
+        assert_eq!(term.cursor(), (2, 0));
+    }
+}
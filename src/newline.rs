@@ -0,0 +1,91 @@
+use crate::key::{Code, Key};
+
+/// What the terminal sends when Enter is pressed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Enter {
+    /// a carriage return, what terminal emulators send in raw mode
+    #[default]
+    Cr,
+    /// a line feed, decoded as Ctrl-J
+    Lf,
+    /// a carriage return followed by a line feed, e.g. telnet like transports
+    CrLf,
+    /// any of the above, for serial consoles of unknown configuration
+    Any,
+}
+
+/// The line ending handling of the editor, for serial links and odd terminals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineEndings {
+    pub enter: Enter,
+    /// what the editor writes to end a line
+    pub output: String,
+}
+
+impl Default for LineEndings {
+    fn default() -> Self {
+        Self {
+            enter: Enter::Cr,
+            output: "\r\n".to_string(),
+        }
+    }
+}
+
+impl LineEndings {
+    /// Translates the line feeds of the output text into the configured line ending
+    pub fn translate(&self, text: &str) -> String {
+        text.replace("\r\n", "\n").replace('\n', &self.output)
+    }
+
+    /// Maps the key a terminal sent to the one the editor sees: line feeds become Enter (or get
+    /// dropped when they follow a carriage return) according to the Enter configuration
+    ///
+    /// after_cr tracks whether the last key was a carriage return
+    pub fn key(&self, key: Key, after_cr: &mut bool) -> Option<Key> {
+        let lf = key == Key::ctrl('j');
+        let cr = std::mem::replace(&mut *after_cr, key.code == Code::Enter);
+        match (self.enter, lf) {
+            (Enter::Cr, _) | (_, false) => Some(key),
+            (Enter::CrLf | Enter::Any, true) if cr => None,
+            (Enter::CrLf, true) => Some(key),
+            (Enter::Lf | Enter::Any, true) => Some(Key::from(Code::Enter)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_newline {
+    use super::{Enter, LineEndings};
+    use crate::key::{Code, Key};
+
+    fn keys(enter: Enter, keys: &[Key]) -> Vec<Key> {
+        let endings = LineEndings {
+            enter,
+            ..LineEndings::default()
+        };
+        let mut after_cr = false;
+
+        keys.iter()
+            .filter_map(|k| endings.key(*k, &mut after_cr))
+            .collect()
+    }
+
+    #[test]
+    fn test_enter() {
+        let (cr, lf, a) = (Key::from(Code::Enter), Key::ctrl('j'), Key::char('a'));
+        assert_eq!(keys(Enter::Cr, &[cr, lf]), [cr, lf]);
+        assert_eq!(keys(Enter::Lf, &[a, lf]), [a, cr]);
+        assert_eq!(keys(Enter::CrLf, &[cr, lf, lf]), [cr, lf]);
+        assert_eq!(keys(Enter::Any, &[cr, lf, lf, a, cr]), [cr, cr, a, cr]);
+    }
+
+    #[test]
+    fn test_translate() {
+        let endings = LineEndings {
+            output: "\n".into(),
+            ..LineEndings::default()
+        };
+        assert_eq!(endings.translate("a\r\nb\nc"), "a\nb\nc");
+        assert_eq!(LineEndings::default().translate("a\nb"), "a\r\nb");
+    }
+}
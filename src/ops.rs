@@ -0,0 +1,257 @@
+use crate::history::History;
+use crate::input::{extends, next_boundary, CharSearch, Input, Mode};
+
+/// An editing operation that can be applied to an Input (and its History)
+///
+/// gives scripted drivers (property based tests, fuzzers, remote controllers) a single
+/// deterministic entry point to the editor, see [`Input::apply`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputOp {
+    InsertChar(char),
+    InsertStr(String),
+    /// inserts a pasted text in a single edit, see [`Input::paste`]
+    Paste(String),
+    Backspace,
+    /// deletes the grapheme cluster under the cursor, see [`Input::delete_char`]
+    DeleteChar,
+    MoveLeft,
+    MoveRight,
+    MoveHome,
+    MoveEnd,
+    JumpLeft,
+    JumpRight,
+    /// deletes the chars the left jump goes over
+    DeleteJumpLeft,
+    /// deletes the chars the right jump goes over
+    DeleteJumpRight,
+    /// deletes up to the end of the next word, see [`Input::delete_word_right`]
+    DeleteWordRight,
+    /// deletes back to the start of the previous word, see [`Input::delete_word_left`]
+    DeleteWordLeft,
+    /// swaps the chars around the cursor, see [`Input::transpose_chars`]
+    TransposeChars,
+    /// swaps the words around the cursor, see [`Input::transpose_words`]
+    TransposeWords,
+    ClearLine,
+    ClearLeft,
+    ClearRight,
+    SetText(String),
+    SetCursor(usize),
+    HistoryPrev,
+    HistoryNext,
+    /// recalls the previous history entry starting with the line typed before navigating the
+    /// history, see [`History::prev_matching`]
+    HistoryPrevMatching,
+    /// recalls the next history entry starting with the line typed before navigating the
+    /// history
+    HistoryNextMatching,
+    /// recalls the history entry at the given 1 based index
+    HistoryGoto(usize),
+    /// undoes all the edits of the recalled history entry, or brings a fresh line back to what
+    /// was typed before navigating the history
+    RevertLine,
+    /// jumps to the next or previous occurrence of a char
+    FindChar(CharSearch),
+    /// repeats the last char jump, in the opposite direction if true
+    RepeatFind(bool),
+    /// inserts the newest kill ring entry, see [`Input::yank`]
+    Yank,
+    /// cycles the text of the last yank through the older kill ring entries
+    YankPop,
+    /// reverts the newest edit of the line, see [`Input::undo`]
+    Undo,
+    /// applies the newest undone edit again
+    Redo,
+}
+
+impl Input {
+    /// Applies an editing operation to the Input and History
+    ///
+    /// returns false if the operation had no effect (e.g. backspace at column 0 or moving past
+    /// the oldest history entry), which is when the bell should be rung
+    ///
+    /// the edits get recorded for [`Input::undo`], recalling a history entry drops them
+    pub fn apply(&mut self, op: &InputOp, h: &mut History) -> bool {
+        let (cursor, generation) = (self.cursor, self.undo.generation);
+        let outer = self.values.track();
+        let applied = self.apply_op(op, h);
+        let edit = self.values.untrack(outer, cursor, self.cursor);
+        // the undos, redos and recalls already left the undo stack as it should be
+        if let Some(edit) = edit.filter(|_| self.undo.generation == generation) {
+            self.undo.push(edit);
+        }
+
+        applied
+    }
+
+    fn apply_op(&mut self, op: &InputOp, h: &mut History) -> bool {
+        let (cursor, changes) = (self.cursor, self.changes);
+        match op {
+            InputOp::InsertChar(c) => {
+                // the overwritten glyph goes whole, the combining chars only add to the one
+                // before the cursor
+                if self.mode == Mode::Overwrite && self.cursor < self.values.len() && !extends(*c) {
+                    let end = next_boundary(&self.values, self.cursor);
+                    self.values.drain(self.cursor..end);
+                }
+                self.put_char(*c)
+            }
+            InputOp::InsertStr(s) => {
+                // overwrites a glyph per inserted glyph, like InsertChar
+                if self.mode == Mode::Overwrite {
+                    let glyphs = s.chars().filter(|c| !extends(*c)).count();
+                    let end =
+                        (0..glyphs).fold(self.cursor, |end, _| match end < self.values.len() {
+                            true => next_boundary(&self.values, end),
+                            false => end,
+                        });
+                    self.values.drain(self.cursor..end);
+                }
+                self.put_str(s.chars())
+            }
+            InputOp::Paste(s) => return self.paste(s),
+            InputOp::Backspace => return self.backspace(),
+            InputOp::DeleteChar => return self.delete_char(),
+            InputOp::MoveLeft => return self.to_the_left(),
+            // the autosuggestion gets accepted from the end of the line
+            InputOp::MoveRight | InputOp::MoveEnd
+                if self.suggestion.is_some() && self.cursor == self.values.len() =>
+            {
+                return self.accept_suggestion()
+            }
+            InputOp::MoveRight => return self.to_the_right(),
+            InputOp::MoveHome => return self.to_home(),
+            InputOp::MoveEnd => return self.to_end() > 0,
+            InputOp::JumpLeft => self.to_left_jump(),
+            InputOp::JumpRight => self.to_right_jump(),
+            InputOp::DeleteJumpLeft => return self.delete_jump_left(),
+            InputOp::DeleteJumpRight => return self.delete_jump_right(),
+            InputOp::DeleteWordRight => return self.delete_word_right(),
+            InputOp::DeleteWordLeft => return self.delete_word_left(),
+            InputOp::TransposeChars => return self.transpose_chars(),
+            InputOp::TransposeWords => return self.transpose_words(),
+            InputOp::ClearLine => self.clear_line(),
+            InputOp::ClearLeft => self.clear_left(),
+            InputOp::ClearRight => self.clear_right(),
+            InputOp::SetText(s) => self.set_text(s),
+            InputOp::SetCursor(idx) => self.set_cursor(*idx),
+            // the lines of a multiline Input come before the history
+            InputOp::HistoryPrev => {
+                return self.line_up() || h.prev(&mut self.values) && self.recalled()
+            }
+            InputOp::HistoryNext => {
+                return self.line_down() || h.next(&mut self.values) && self.recalled()
+            }
+            InputOp::HistoryPrevMatching => {
+                let prefix = h.search_prefix(&self.values);
+                return self.line_up()
+                    || h.prev_matching(&prefix, &mut self.values) && self.recalled();
+            }
+            InputOp::HistoryNextMatching => {
+                let prefix = h.search_prefix(&self.values);
+                return self.line_down()
+                    || h.next_matching(&prefix, &mut self.values) && self.recalled();
+            }
+            InputOp::HistoryGoto(n) => return h.goto(*n, &mut self.values) && self.recalled(),
+            InputOp::FindChar(search) => match search.forward {
+                true => return self.find_char_forward(search.c, search.till),
+                false => return self.find_char_backward(search.c, search.till),
+            },
+            InputOp::RepeatFind(reverse) => return self.repeat_find(*reverse),
+            InputOp::Yank => return self.yank(),
+            InputOp::YankPop => return self.yank_pop(),
+            InputOp::Undo => return self.undo(),
+            InputOp::Redo => return self.redo(),
+            InputOp::RevertLine => {
+                let original = match h.values.get(h.cursor) {
+                    Some(entry) => entry.clone(),
+                    None => h.temp.clone().unwrap_or_default(),
+                };
+                if original == self.values {
+                    return false;
+                }
+                self.values.set(original);
+                return self.recalled();
+            }
+        }
+
+        self.cursor != cursor || self.changes != changes
+    }
+
+    // puts the cursor at the end of a history entry that was just bound to the Input values
+    fn recalled(&mut self) -> bool {
+        self.cursor = self.values.len();
+        self.mark_clean();
+        // the edits were made to another line
+        self.undo.clear();
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test_ops {
+    use super::InputOp;
+    use crate::history::History;
+    use crate::input::{Input, Mode};
+
+    #[test]
+    fn test_apply() {
+        let mut i = Input::new("testing input> ", false);
+        let mut h = History::new();
+        h.push("cargo test".chars().collect());
+
+        assert!(!i.apply(&InputOp::Backspace, &mut h));
+        assert!(i.apply(&InputOp::InsertStr("pika chu".into()), &mut h));
+        assert!(i.apply(&InputOp::JumpLeft, &mut h));
+        assert!(i.apply(&InputOp::ClearRight, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "pika");
+        assert!(!i.apply(&InputOp::MoveRight, &mut h));
+
+        assert!(i.apply(&InputOp::HistoryPrev, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "cargo test");
+        assert!(!i.apply(&InputOp::HistoryPrev, &mut h));
+        assert!(i.apply(&InputOp::HistoryNext, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "pika");
+    }
+
+    #[test]
+    fn test_revert_line() {
+        let mut i = Input::new("testing input> ", false);
+        let mut h = History::new();
+        h.push("cargo test".chars().collect());
+
+        assert!(i.apply(&InputOp::InsertStr("pika".into()), &mut h));
+        assert!(i.apply(&InputOp::HistoryPrev, &mut h));
+        assert!(i.apply(&InputOp::Backspace, &mut h));
+        assert!(i.apply(&InputOp::RevertLine, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "cargo test");
+        assert!(!i.apply(&InputOp::RevertLine, &mut h));
+
+        assert!(i.apply(&InputOp::HistoryNext, &mut h));
+        assert!(i.apply(&InputOp::InsertChar('!'), &mut h));
+        assert!(i.apply(&InputOp::RevertLine, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "pika");
+    }
+
+    #[test]
+    fn test_overwrite() {
+        let mut i = Input::new("testing input> ", false);
+        let mut h = History::new();
+        i.set_text("pika");
+        i.set_cursor(2);
+        i.set_mode(Mode::Overwrite);
+
+        "chu"
+            .chars()
+            .for_each(|c| _ = i.apply(&InputOp::InsertChar(c), &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "pichu");
+
+        i.set_cursor(1);
+        assert!(i.apply(&InputOp::InsertStr("ea".into()), &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "peahu");
+        i.set_cursor(4);
+        assert!(i.apply(&InputOp::InsertStr("m!".into()), &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "peahm!");
+    }
+}
@@ -0,0 +1,182 @@
+use std::io::Write;
+use std::time::SystemTime;
+
+use crate::editor::Editor;
+use crate::finder::fuzzy_match;
+use crate::history::History;
+use crate::key::Code;
+use crate::state::set_alt_screen;
+
+// the rows taken by the query line and the hints line
+const CHROME: usize = 2;
+
+impl Editor {
+    /// Opens a full screen pager listing the History entries newest first, with when they were
+    /// last submitted and how many times, filtered as a query gets typed, see [`fuzzy_match`]
+    ///
+    /// Up/Down (or Ctrl-P/Ctrl-N) and PageUp/PageDown move the selection, Enter recalls the
+    /// selected entry into the line being edited, Delete removes it from the History, Esc,
+    /// Ctrl-C or Ctrl-G close the pager; bound to Ctrl-X h
+    ///
+    /// the pager takes the alternate screen, an Editor already in the alternate screen
+    /// gets its line repainted at the top of the screen once the pager closes
+    ///
+    /// returns false if no entry was recalled
+    pub fn browse_history(&mut self) -> std::io::Result<bool> {
+        let alt_screen = self.input.alt_screen;
+        if !alt_screen {
+            set_alt_screen(&mut self.sol, true)?;
+        }
+        let overlay = self.overlay("history> ");
+        let primary = std::mem::replace(&mut self.input, overlay);
+
+        let picked = self.read_pager();
+        self.input = primary;
+        match alt_screen {
+            true => self.sol.write_all(b"\x1b[2J\x1b[H")?,
+            false => set_alt_screen(&mut self.sol, false)?,
+        }
+        let picked = picked?;
+        if let Some(idx) = picked {
+            if self.history.goto(idx + 1, &mut self.input.values) {
+                self.input.cursor = self.input.values.len();
+                self.input.changes += 1;
+            }
+        }
+        self.input.render(&mut self.sol)?;
+
+        Ok(picked.is_some())
+    }
+
+    // the event loop of browse_history, returns the 0 based index of the recalled entry
+    fn read_pager(&mut self) -> std::io::Result<Option<usize>> {
+        let mut scratch = self.scratch_history();
+        let mut matches = filter(&self.history, "");
+        let (mut selected, mut top) = (0usize, 0usize);
+        loop {
+            let (width, height) = self.backend.size().unwrap_or((80, 24));
+            let (width, rows) = (
+                width as usize,
+                (height as usize).saturating_sub(CHROME).max(1),
+            );
+            // scrolls the list just enough for the selection to show
+            top = top.min(selected).max((selected + 1).saturating_sub(rows));
+
+            _ = self.sol.write(b"\x1b[H\x1b[J\x1b[2;1H");
+            let now = SystemTime::now();
+            for (row, idx) in matches.iter().enumerate().skip(top).take(rows) {
+                let line = row_text(&self.history, *idx, width, now);
+                match row == selected {
+                    true => _ = write!(self.sol, "\x1b[7m{}\x1b[27m\r\n", line),
+                    false => _ = write!(self.sol, "{}\r\n", line),
+                }
+            }
+            _ = write!(
+                self.sol,
+                "\x1b[{};1H\x1b[2m{}/{} Enter recall  Delete remove  Esc close\x1b[22m\x1b[H",
+                height,
+                matches.len(),
+                self.history.len()
+            );
+            self.input.render(&mut self.sol)?;
+
+            let key = self.next_key()?;
+            let page = rows.saturating_sub(1).max(1);
+            let last = matches.len().saturating_sub(1);
+            match (key.code, key.mods.ctrl) {
+                (Code::Enter, _) => return Ok(matches.get(selected).copied()),
+                (Code::Esc, _) | (Code::Char('c' | 'g'), true) => return Ok(None),
+                (Code::Up, _) | (Code::Char('p'), true) => selected = selected.saturating_sub(1),
+                (Code::Down, _) | (Code::Char('n'), true) => selected = (selected + 1).min(last),
+                (Code::PageUp, _) => selected = selected.saturating_sub(page),
+                (Code::PageDown, _) => selected = (selected + page).min(last),
+                (Code::Delete, _) => match matches.get(selected) {
+                    Some(idx) => {
+                        self.history.remove(*idx);
+                        matches = filter(&self.history, &self.query());
+                        selected = selected.min(matches.len().saturating_sub(1));
+                    }
+                    None => self.input.ring(&mut self.sol),
+                },
+                _ => match self.keymap.op(key) {
+                    Some(op) if self.input.apply(&op, &mut scratch) => {
+                        matches = filter(&self.history, &self.query());
+                        (selected, top) = (0, 0);
+                    }
+                    _ => self.input.ring(&mut self.sol),
+                },
+            }
+        }
+    }
+
+    // the query typed in an overlay
+    fn query(&self) -> String {
+        self.input.values.iter().collect()
+    }
+}
+
+// the 0 based indices of the History entries matching the query, newest first
+fn filter(h: &History, query: &str) -> Vec<usize> {
+    (0..h.values.len())
+        .rev()
+        .filter(|idx| fuzzy_match(query, &h.values[*idx].iter().collect::<String>()).is_some())
+        .collect()
+}
+
+// a row of the pager: the age of the last submission, the submission count then the entry,
+// cut to width cells
+fn row_text(h: &History, idx: usize, width: usize, now: SystemTime) -> String {
+    let entry = &h.values[idx];
+    let (age, count) = match h.usage_of(entry) {
+        Some(usage) => (
+            age(now
+                .duration_since(usage.last_used)
+                .unwrap_or_default()
+                .as_secs()),
+            usage.count.to_string(),
+        ),
+        // the entries loaded from a store were never submitted in this session
+        None => ("-".to_string(), "-".to_string()),
+    };
+    let row = format!("{:>7} {:>4}  {}", age, count, crate::input::visible(entry));
+
+    row.chars().take(width).collect()
+}
+
+// a short human readable age
+fn age(secs: u64) -> String {
+    match secs {
+        0..=59 => "now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod test_pager {
+    use super::{age, filter, row_text};
+    use crate::history::History;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_rows() {
+        let mut h = History::new();
+        ["cargo build", "ls", "cargo test", "ls"]
+            .iter()
+            .for_each(|e| h.push(e.chars().collect()));
+        h.values.insert(0, "git log".chars().collect());
+
+        assert_eq!(filter(&h, ""), [3, 2, 1, 0]);
+        assert_eq!(filter(&h, "cg"), [3, 1]);
+
+        let now = SystemTime::now() + Duration::from_secs(120);
+        assert_eq!(row_text(&h, 2, 80, now), " 2m ago    2  ls");
+        assert_eq!(row_text(&h, 0, 12, now), "      -    -");
+        assert_eq!(age(7200), "2h ago");
+
+        assert_eq!(h.remove(2), Some("ls".chars().collect()));
+        assert_eq!(h.usage_of(&['l', 's']), None);
+        assert_eq!(h.cursor, 2);
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Write};
+use std::sync::atomic::{compiler_fence, Ordering};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{poll, read, Event, KeyEventKind};
+
+#[cfg(unix)]
+use crate::events::{read_events, Decoder, KeyEvent};
+use crate::history::History;
+use crate::input::Input;
+use crate::key::{from_crossterm, Code, Key};
+use crate::keymap::Keymap;
+use crate::term::default_backend;
+
+// runs f with the terminal in raw mode, cooked mode is restored even if f fails
+fn in_raw_mode<T>(f: impl FnOnce() -> std::io::Result<T>) -> std::io::Result<T> {
+    let mut backend = default_backend();
+    backend.enable_raw_mode()?;
+    let result = f();
+    backend.disable_raw_mode()?;
+
+    result
+}
+
+// the key presses of a terminal in raw mode, decoded by the crate like those of the Editor
+// (see Editor::decoder) or else read through crossterm
+struct Keys {
+    #[cfg(unix)]
+    decoder: Option<Decoder>,
+    // the keys decoded along with the one returned
+    queued: VecDeque<Key>,
+}
+
+impl Keys {
+    fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            decoder: std::io::IsTerminal::is_terminal(&std::io::stdin()).then(Decoder::new),
+            queued: VecDeque::new(),
+        }
+    }
+
+    // waits up to timeout (forever if None) for the next key press, None if none came in time
+    fn next(&mut self, timeout: Option<Duration>) -> std::io::Result<Option<Key>> {
+        loop {
+            if let Some(key) = self.queued.pop_front() {
+                return Ok(Some(key));
+            }
+            #[cfg(unix)]
+            if let Some(decoder) = self.decoder.as_mut() {
+                let ms = timeout.map_or(-1, |t| t.as_millis().min(i32::MAX as u128) as i32);
+                let events = read_events(decoder, ms)?;
+                if events.is_empty() && timeout.is_some() {
+                    return Ok(None);
+                }
+                self.queued
+                    .extend(events.into_iter().filter_map(|event| match event {
+                        KeyEvent::Key(key) => Some(key),
+                        _ => None,
+                    }));
+                continue;
+            }
+            if timeout.is_some_and(|t| !poll(t).is_ok_and(|ready| ready)) {
+                return Ok(None);
+            }
+            if let Event::Key(key) = read()? {
+                if key.kind != KeyEventKind::Release {
+                    return Ok(Some(from_crossterm(key)));
+                }
+            }
+        }
+    }
+
+    // blocks until a key gets pressed
+    fn wait(&mut self) -> std::io::Result<Key> {
+        loop {
+            if let Some(key) = self.next(None)? {
+                return Ok(key);
+            }
+        }
+    }
+}
+
+/// Reads a single key press, without echoing it and without an [`crate::Input`] or
+/// [`crate::History`], then hands the terminal back as it was
+pub fn read_key() -> std::io::Result<Key> {
+    in_raw_mode(|| Keys::new().wait())
+}
+
+/// Writes message then waits for any key press, e.g. `pause("press any key to continue")`
+pub fn pause(message: &str) -> std::io::Result<Key> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(message.as_bytes())?;
+    stdout.flush()?;
+    let key = read_key();
+    stdout.write_all(b"\r\n")?;
+    stdout.flush()?;
+
+    key
+}
+
+/// Reads a line after writing prompt, the prompt is followed by a countdown and default gets
+/// returned if the user doesn't start typing before the timeout
+///
+/// the countdown stops at the first key press; submitting an empty line also returns default
+/// # Errors
+/// an [`ErrorKind::Interrupted`] error on Ctrl-C
+pub fn read_line_timeout(
+    prompt: &str,
+    timeout: Duration,
+    default: &str,
+) -> std::io::Result<String> {
+    let mut sol = std::io::stdout().lock();
+    let mut i = Input::new(prompt, false);
+    let mut h = History::new();
+    let keymap = Keymap::default();
+    let deadline = Instant::now() + timeout;
+    let mut counting = true;
+    let mut keys = Keys::new();
+
+    let line = in_raw_mode(|| loop {
+        let mut wait = Duration::ZERO;
+        if counting {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Ok(default.to_string());
+            }
+            i.overwrite_prompt(&format!("{}({}s) ", prompt, countdown(left)));
+            // wakes up when the displayed number of seconds changes
+            wait = match left.subsec_nanos() {
+                0 => Duration::from_secs(1),
+                nanos => Duration::from_nanos(nanos as u64),
+            };
+        }
+        i.render(&mut sol)?;
+        let Some(key) = keys.next(counting.then_some(wait))? else {
+            continue;
+        };
+        if std::mem::take(&mut counting) {
+            i.overwrite_prompt(prompt);
+        }
+        match (key.code, key.mods.ctrl) {
+            (Code::Enter, _) if i.values.is_empty() => return Ok(default.to_string()),
+            (Code::Enter, _) => return Ok(i.values.iter().collect()),
+            (Code::Char('c'), true) => return Err(ErrorKind::Interrupted.into()),
+            _ => {
+                if !keymap.op(key).is_some_and(|op| i.apply(&op, &mut h)) {
+                    i.ring(&mut sol);
+                }
+            }
+        }
+    });
+    sol.write_all(b"\r\n")?;
+    sol.flush()?;
+
+    line
+}
+
+// the number of seconds displayed by the read_line_timeout countdown
+fn countdown(left: Duration) -> u64 {
+    left.as_secs() + (left.subsec_nanos() > 0) as u64
+}
+
+/// Reads a password after writing prompt, with echo disabled entirely (not even mask chars),
+/// Backspace and Ctrl-U being the only editing keys
+///
+/// the password never reaches a [`crate::History`] or the debug logs and the terminal gets
+/// restored even on error; the buffer the chars get collected in is zeroed before being freed,
+/// and the returned String is allocated at its final size, but the bytes read from the terminal
+/// and the keys decoded from them are not wiped
+/// # Errors
+/// an [`ErrorKind::Interrupted`] error on Ctrl-C
+/// and an [`ErrorKind::UnexpectedEof`] error on Ctrl-D in an empty password
+pub fn read_password(prompt: &str) -> std::io::Result<String> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(prompt.as_bytes())?;
+    stdout.flush()?;
+
+    let mut buf = Vec::with_capacity(64);
+    let mut keys = Keys::new();
+    let read = in_raw_mode(|| loop {
+        let key = keys.wait()?;
+        match (key.code, key.mods.ctrl) {
+            (Code::Enter, _) => return Ok(()),
+            (Code::Char('c'), true) => return Err(ErrorKind::Interrupted.into()),
+            (Code::Char('d'), true) if buf.is_empty() => {
+                return Err(ErrorKind::UnexpectedEof.into())
+            }
+            (Code::Char('u'), true) => wipe(&mut buf),
+            (Code::Backspace, _) => {
+                if let Some(last) = buf.last_mut() {
+                    zero(last);
+                    buf.pop();
+                }
+            }
+            (Code::Char(c), false) if !key.mods.alt => push(&mut buf, c),
+            _ => (),
+        }
+    });
+    stdout.write_all(b"\r\n")?;
+    stdout.flush()?;
+
+    let password = read.map(|_| collected(&buf));
+    wipe(&mut buf);
+
+    password
+}
+
+// grows the buffer by hand so that the old allocation gets zeroed before being freed
+fn push(buf: &mut Vec<char>, c: char) {
+    if buf.len() == buf.capacity() {
+        let mut grown = Vec::with_capacity(buf.capacity().max(32) * 2);
+        grown.extend_from_slice(buf);
+        wipe(buf);
+        *buf = grown;
+    }
+    buf.push(c);
+}
+
+// the chars as a String sized up front, no reallocation leaves a copy of them behind
+fn collected(buf: &[char]) -> String {
+    let mut password = String::with_capacity(buf.iter().map(|c| c.len_utf8()).sum());
+    password.extend(buf);
+
+    password
+}
+
+fn zero(c: &mut char) {
+    // SAFETY: c is a valid, aligned and exclusive reference
+    unsafe { std::ptr::write_volatile(c, '\0') };
+}
+
+// zeroes the chars of the buffer then empties it, volatile writes don't get optimized away
+fn wipe(buf: &mut Vec<char>) {
+    buf.iter_mut().for_each(zero);
+    compiler_fence(Ordering::SeqCst);
+    buf.clear();
+}
+
+#[cfg(test)]
+mod test_prompts {
+    use super::{collected, countdown, push, wipe};
+    use std::time::Duration;
+
+    #[test]
+    fn test_wipe() {
+        let mut buf = Vec::with_capacity(2);
+        "secret".chars().for_each(|c| push(&mut buf, c));
+        assert_eq!(buf.iter().collect::<String>(), "secret");
+
+        let ptr = buf.as_ptr();
+        wipe(&mut buf);
+        assert!(buf.is_empty());
+        // SAFETY: the allocation is still owned by buf, only its length was reset
+        let wiped = unsafe { std::slice::from_raw_parts(ptr, 6) };
+        assert!(wiped.iter().all(|c| *c == '\0'));
+
+        let password = collected(&['s', 'é', 'c', '🔑']);
+        assert_eq!((password.len(), password.capacity()), (8, 8));
+    }
+
+    #[test]
+    fn test_countdown() {
+        assert_eq!(countdown(Duration::from_secs(5)), 5);
+        assert_eq!(countdown(Duration::from_millis(4200)), 5);
+        assert_eq!(countdown(Duration::from_millis(1)), 1);
+    }
+}
@@ -0,0 +1,332 @@
+// minimal backtracking regex matching used for the log redaction rules: literals, `.`, the
+// `[...]` and `[^...]` classes with ranges, the `\d`, `\w` and `\s` classes and their negations,
+// `\` escapes, the `^` and `$` anchors, the `*`, `+`, `?` and `{m,n}` greedy repetitions and
+// the `(...)` groups with `|` alternatives
+
+// an item of a char class
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    Range(char, char),
+    Digit,
+    Word,
+    Space,
+}
+
+impl Item {
+    fn contains(&self, c: char) -> bool {
+        match self {
+            Self::Range(low, high) => (*low..=*high).contains(&c),
+            Self::Digit => c.is_ascii_digit(),
+            Self::Word => c.is_alphanumeric() || c == '_',
+            Self::Space => c.is_whitespace(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Char(char),
+    Any,
+    // the items and whether the class is negated
+    Class(Vec<Item>, bool),
+    Start,
+    End,
+    // the alternatives, each a sequence of nodes
+    Group(Vec<Vec<Node>>),
+    Repeat(Box<Node>, usize, Option<usize>),
+}
+
+/// A compiled regex pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Regex {
+    alts: Vec<Vec<Node>>,
+}
+
+impl Regex {
+    /// Compiles a pattern, returns the reason it is invalid otherwise
+    pub(crate) fn new(pattern: &str) -> Result<Self, String> {
+        let chars = pattern.chars().collect::<Vec<char>>();
+        let mut pos = 0;
+        let alts = alternatives(&chars, &mut pos)?;
+        if pos < chars.len() {
+            return Err(format!("unmatched `)` at {}", pos));
+        }
+
+        Ok(Self { alts })
+    }
+
+    /// Returns true if the pattern matches anywhere in text
+    pub(crate) fn is_match(&self, text: &[char]) -> bool {
+        let group = [Node::Group(self.alts.clone())];
+        (0..=text.len()).any(|start| sequence(&group, text, start, &mut |_| true))
+    }
+}
+
+// parses the alternatives up to the end of the pattern or of the group
+fn alternatives(chars: &[char], pos: &mut usize) -> Result<Vec<Vec<Node>>, String> {
+    let mut alts = vec![nodes(chars, pos)?];
+    while chars.get(*pos) == Some(&'|') {
+        *pos += 1;
+        alts.push(nodes(chars, pos)?);
+    }
+
+    Ok(alts)
+}
+
+// parses the nodes of an alternative along with their repetitions
+fn nodes(chars: &[char], pos: &mut usize) -> Result<Vec<Node>, String> {
+    let mut nodes = Vec::new();
+    while let Some(c) = chars.get(*pos).filter(|c| !matches!(c, '|' | ')')) {
+        let (min, max) = match c {
+            '*' => (0, None),
+            '+' => (1, None),
+            '?' => (0, Some(1)),
+            '{' => match bounds(chars, pos)? {
+                Some(bounds) => bounds,
+                None => {
+                    nodes.push(Node::Char('{'));
+                    *pos += 1;
+                    continue;
+                }
+            },
+            _ => {
+                nodes.push(atom(chars, pos)?);
+                continue;
+            }
+        };
+        if *c != '{' {
+            *pos += 1;
+        }
+        match nodes.pop() {
+            Some(node) if !matches!(node, Node::Start | Node::End | Node::Repeat(..)) => {
+                nodes.push(Node::Repeat(Box::new(node), min, max))
+            }
+            _ => return Err(format!("nothing to repeat at {}", *pos - 1)),
+        }
+    }
+
+    Ok(nodes)
+}
+
+// parses the `{m}`, `{m,}` or `{m,n}` bounds at pos, None if the brace is a literal one
+fn bounds(chars: &[char], pos: &mut usize) -> Result<Option<(usize, Option<usize>)>, String> {
+    let Some(len) = chars[*pos..].iter().position(|c| *c == '}') else {
+        return Ok(None);
+    };
+    let inner = chars[*pos + 1..*pos + len].iter().collect::<String>();
+    let num = |text: &str| text.parse::<usize>().ok();
+    let bounds = match inner.split_once(',') {
+        None => num(&inner).map(|n| (n, Some(n))),
+        Some((min, "")) => num(min).map(|min| (min, None)),
+        Some((min, max)) => num(min).zip(num(max)).map(|(min, max)| (min, Some(max))),
+    };
+    match bounds {
+        Some((min, Some(max))) if max < min => Err(format!("bad repetition bounds at {}", *pos)),
+        Some(bounds) => {
+            *pos += len + 1;
+            Ok(Some(bounds))
+        }
+        None => Ok(None),
+    }
+}
+
+fn atom(chars: &[char], pos: &mut usize) -> Result<Node, String> {
+    let c = chars[*pos];
+    *pos += 1;
+    let node = match c {
+        '.' => Node::Any,
+        '^' => Node::Start,
+        '$' => Node::End,
+        '(' => {
+            // a non capturing group is a group as well
+            if chars[*pos..].starts_with(&['?', ':']) {
+                *pos += 2;
+            }
+            let alts = alternatives(chars, pos)?;
+            if chars.get(*pos) != Some(&')') {
+                return Err(format!("unclosed `(` at {}", *pos));
+            }
+            *pos += 1;
+            Node::Group(alts)
+        }
+        '[' => class(chars, pos)?,
+        '\\' => match escape(chars, pos)? {
+            Escaped::Char(c) => Node::Char(c),
+            Escaped::Class(item, negated) => Node::Class(vec![item], negated),
+        },
+        c => Node::Char(c),
+    };
+
+    Ok(node)
+}
+
+enum Escaped {
+    Char(char),
+    // a class item and whether it is negated
+    Class(Item, bool),
+}
+
+// parses the escape after a `\`
+fn escape(chars: &[char], pos: &mut usize) -> Result<Escaped, String> {
+    let Some(c) = chars.get(*pos) else {
+        return Err(format!("trailing `\\` at {}", *pos - 1));
+    };
+    *pos += 1;
+    let escaped = match c {
+        'd' | 'D' => Escaped::Class(Item::Digit, *c == 'D'),
+        'w' | 'W' => Escaped::Class(Item::Word, *c == 'W'),
+        's' | 'S' => Escaped::Class(Item::Space, *c == 'S'),
+        'n' => Escaped::Char('\n'),
+        't' => Escaped::Char('\t'),
+        'r' => Escaped::Char('\r'),
+        c if c.is_alphanumeric() => {
+            return Err(format!("unknown escape `\\{}` at {}", c, *pos - 2))
+        }
+        c => Escaped::Char(*c),
+    };
+
+    Ok(escaped)
+}
+
+// parses a class after its `[`, a `]` right after the `[` or `[^` is a literal one
+fn class(chars: &[char], pos: &mut usize) -> Result<Node, String> {
+    let start = *pos - 1;
+    let negated = chars.get(*pos) == Some(&'^');
+    if negated {
+        *pos += 1;
+    }
+    let mut items = Vec::new();
+    let mut first = true;
+    loop {
+        let c = match chars.get(*pos) {
+            None => return Err(format!("unclosed `[` at {}", start)),
+            Some(']') if !first => break,
+            Some(c) => *c,
+        };
+        *pos += 1;
+        first = false;
+        let low = match c {
+            '\\' => match escape(chars, pos)? {
+                Escaped::Char(c) => c,
+                Escaped::Class(item, false) => {
+                    items.push(item);
+                    continue;
+                }
+                Escaped::Class(..) => {
+                    return Err(format!("negated class escape in `[` at {}", start))
+                }
+            },
+            c => c,
+        };
+        // a `-` first or last is a literal one
+        let high = match (chars.get(*pos), chars.get(*pos + 1)) {
+            (Some('-'), Some(high)) if *high != ']' => {
+                *pos += 2;
+                match high {
+                    '\\' => match escape(chars, pos)? {
+                        Escaped::Char(c) => c,
+                        Escaped::Class(..) => {
+                            return Err(format!("class escape ending a range at {}", *pos - 2))
+                        }
+                    },
+                    c => *c,
+                }
+            }
+            _ => low,
+        };
+        if high < low {
+            return Err(format!("bad range `{}-{}` at {}", low, high, *pos));
+        }
+        items.push(Item::Range(low, high));
+    }
+    *pos += 1;
+
+    Ok(Node::Class(items, negated))
+}
+
+// matches the nodes against text from at, then the continuation k from where they ended
+fn sequence(nodes: &[Node], text: &[char], at: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+    let Some((node, rest)) = nodes.split_first() else {
+        return k(at);
+    };
+    match node {
+        Node::Repeat(inner, min, max) => repeat(inner, (*min, *max), 0, rest, text, at, k),
+        node => one(node, text, at, &mut |next| sequence(rest, text, next, k)),
+    }
+}
+
+// matches a single node that is not a repetition
+fn one(node: &Node, text: &[char], at: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+    match node {
+        Node::Char(c) => text.get(at) == Some(c) && k(at + 1),
+        Node::Any => at < text.len() && k(at + 1),
+        Node::Class(items, negated) => {
+            text.get(at)
+                .is_some_and(|c| items.iter().any(|item| item.contains(*c)) != *negated)
+                && k(at + 1)
+        }
+        Node::Start => at == 0 && k(at),
+        Node::End => at == text.len() && k(at),
+        Node::Group(alts) => alts.iter().any(|alt| sequence(alt, text, at, k)),
+        Node::Repeat(..) => sequence(std::slice::from_ref(node), text, at, k),
+    }
+}
+
+// matches as many repetitions of inner as possible, giving them back one by one for the rest
+// to match
+fn repeat(
+    inner: &Node,
+    (min, max): (usize, Option<usize>),
+    count: usize,
+    rest: &[Node],
+    text: &[char],
+    at: usize,
+    k: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    // a repetition matching nothing once the minimum is reached would loop forever
+    if max.is_none_or(|max| count < max)
+        && one(inner, text, at, &mut |next| {
+            (next != at || count < min) && repeat(inner, (min, max), count + 1, rest, text, next, k)
+        })
+    {
+        return true;
+    }
+
+    count >= min && sequence(rest, text, at, k)
+}
+
+#[cfg(test)]
+mod test_regex {
+    use super::Regex;
+
+    fn m(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern)
+            .unwrap()
+            .is_match(&text.chars().collect::<Vec<char>>())
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(m("--token", "curl --token abc"));
+        assert!(!m("^--token", "curl --token abc"));
+        assert!(m(r"^\w+ -p\s*\S+$", "mysql -p hunter2"));
+        assert!(m("pass(word)?=", "login password=x"));
+        assert!(m("(api|auth)_key", "AUTH=1 auth_key=2"));
+        assert!(!m("(api|auth)_key", "apikey"));
+        assert!(m(r"[A-Za-z0-9]{20,}", "key AKIAIOSFODNN7EXAMPLE1"));
+        assert!(!m(r"[A-Za-z0-9]{20,}", "key AKIA"));
+        assert!(m(r"^a{2}b?$", "aab"));
+        assert!(m("[^a-z]", "abc1"));
+        assert!(!m("[^a-z]", "abc"));
+        assert!(m(r"\d+\.\d+", "v1.2"));
+        assert!(m("(a*)*b", "aaab"));
+        assert!(m("x{", "x{"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        ["(ab", "ab)", "*a", "[a-", r"a\", "[z-a]", "a{3,1}", r"\q"]
+            .into_iter()
+            .for_each(|pattern| assert!(Regex::new(pattern).is_err(), "{}", pattern));
+    }
+}
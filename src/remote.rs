@@ -0,0 +1,246 @@
+use std::io::{BufRead, ErrorKind, Write};
+
+use crate::editor::Editor;
+use crate::events::KeyEvent;
+use crate::json::Json;
+use crate::key::Key;
+
+/// Drives an [`Editor::headless`] through a line delimited JSON protocol, so that non Rust
+/// tools and tests can run the editor as a black box subprocess, see [`Remote::serve`]
+///
+/// every request is a JSON object with a `cmd` field:
+/// - `{"cmd":"set_prompt","prompt":"> "}` replaces the prompt
+/// - `{"cmd":"keys","keys":["h","i","ctrl-a","enter"]}` presses the keys, named as in
+///   [`Key::from_str`](std::str::FromStr)
+/// - `{"cmd":"text","text":"hi"}` types the text
+/// - `{"cmd":"state"}` asks for the prompt, line, cursor and history
+/// - `{"cmd":"quit"}` ends the session
+///
+/// the replies are JSON objects with an `event` field: `submitted` (with the `line`),
+/// `interrupted` (Ctrl-C), `eof` (Ctrl-D in an empty line) and `bell` (a key had no effect)
+/// are sent as they happen, then every request is acknowledged by an `ok`, a `state`, an
+/// `error` (with a `message`) or a `bye`
+///
+/// the keys go through [`Editor::feed`], the keymap, middlewares, validator and completions of
+/// the Editor included; it renders to a [`crate::MockTerminal`], the terminal is never touched
+#[derive(Debug)]
+pub struct Remote {
+    pub editor: Editor,
+}
+
+impl Remote {
+    /// Creates a new Remote with an empty line and History
+    pub fn new(prompt: &str) -> Self {
+        Self {
+            editor: Editor::headless(prompt),
+        }
+    }
+
+    /// Answers the requests read from r, one per line, until r ends or a quit request comes in
+    ///
+    /// e.g. `Remote::new("> ").serve(std::io::stdin().lock(), std::io::stdout().lock())`
+    pub fn serve(&mut self, r: impl BufRead, mut w: impl Write) -> std::io::Result<()> {
+        for request in r.lines() {
+            let request = request?;
+            if request.trim().is_empty() {
+                continue;
+            }
+            let replies = self.handle(&request);
+            for reply in &replies {
+                writeln!(w, "{}", reply)?;
+            }
+            w.flush()?;
+            if replies
+                .last()
+                .is_some_and(|r| r.starts_with(r#"{"event":"bye""#))
+            {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Answers a single request, returns the reply lines
+    pub fn handle(&mut self, request: &str) -> Vec<String> {
+        let mut replies = Vec::new();
+        let ack = match Json::parse(request) {
+            Some(request) => self.dispatch(&request, &mut replies),
+            None => Err("malformed request".to_string()),
+        };
+        replies.push(match ack {
+            Ok(ack) => ack,
+            Err(message) => Json::obj([("event", "error".into()), ("message", message.into())]),
+        });
+
+        replies.iter().map(Json::to_string).collect()
+    }
+
+    fn dispatch(&mut self, request: &Json, replies: &mut Vec<Json>) -> Result<Json, String> {
+        let field = |name: &str| {
+            request
+                .get(name)
+                .ok_or_else(|| format!("missing field `{}`", name))
+        };
+        let str_field = |name: &str| {
+            field(name)?
+                .as_str()
+                .ok_or_else(|| format!("`{}` is not a string", name))
+        };
+        match str_field("cmd")? {
+            "set_prompt" => self.editor.input.overwrite_prompt(str_field("prompt")?),
+            "keys" => {
+                let keys = field("keys")?
+                    .as_array()
+                    .ok_or("`keys` is not an array")?
+                    .iter()
+                    .map(|key| {
+                        let name = key.as_str().ok_or("a key is not a string")?;
+                        name.parse::<Key>().map_err(|e| e.to_string())
+                    })
+                    .collect::<Result<Vec<Key>, String>>()?;
+                self.press(keys, replies)?;
+            }
+            "text" => self.press(str_field("text")?.chars().map(Key::char).collect(), replies)?,
+            "state" => return Ok(self.state()),
+            "quit" => return Ok(Json::obj([("event", "bye".into())])),
+            cmd => return Err(format!("unknown cmd `{}`", cmd)),
+        }
+
+        Ok(Json::obj([("event", "ok".into())]))
+    }
+
+    // feeds the keys to the Editor, the nested prompts reading the keys that follow theirs, the
+    // events they cause go to replies
+    fn press(&mut self, keys: Vec<Key>, replies: &mut Vec<Json>) -> Result<(), String> {
+        let editor = &mut self.editor;
+        editor.queued.extend(keys.into_iter().map(KeyEvent::Key));
+        while let Some(event) = editor.queued.pop_front() {
+            let KeyEvent::Key(key) = event else {
+                continue;
+            };
+            let bells = editor.sol.mock().map_or(0, |mock| mock.bells);
+            let event = match editor.feed(key) {
+                Ok(Some(line)) => {
+                    replies.push(Json::obj([
+                        ("event", "submitted".into()),
+                        ("line", line.into()),
+                    ]));
+                    continue;
+                }
+                Ok(None) if editor.sol.mock().is_some_and(|mock| mock.bells > bells) => "bell",
+                Ok(None) => continue,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {
+                    editor.input.clear_line();
+                    "interrupted"
+                }
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => "eof",
+                Err(e) => {
+                    editor.queued.clear();
+                    return Err(e.to_string());
+                }
+            };
+            replies.push(Json::obj([("event", event.into())]));
+        }
+        // nothing reads what got rendered
+        _ = editor.sol.mock().map(|mock| mock.take_output());
+
+        Ok(())
+    }
+
+    // the state reply
+    fn state(&self) -> Json {
+        let editor = &self.editor;
+        Json::obj([
+            ("event", "state".into()),
+            ("prompt", editor.input.prompt.as_str().into()),
+            (
+                "line",
+                editor.input.values.iter().collect::<String>().into(),
+            ),
+            ("cursor", editor.input.cursor.into()),
+            (
+                "history",
+                Json::Arr(
+                    editor
+                        .history
+                        .values
+                        .iter()
+                        .map(|e| e.iter().collect::<String>().into())
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test_remote {
+    use super::Remote;
+    use crate::key::Key;
+    use crate::middleware::{Flow, FnMiddleware};
+    use crate::validate::{FnValidator, Validation};
+
+    #[test]
+    fn test_serve() {
+        let mut remote = Remote::new("> ");
+        let requests = [
+            r#"{"cmd":"set_prompt","prompt":"$ "}"#,
+            r#"{"cmd":"text","text":"ls"}"#,
+            r#"{"cmd":"keys","keys":["backspace","backspace","backspace","s","enter"]}"#,
+            r#"{"cmd":"keys","keys":["up","ctrl-a"]}"#,
+            r#"{"cmd":"state"}"#,
+            r#"{"cmd":"keys","keys":["hyper-a"]}"#,
+            r#"{"cmd":"quit"}"#,
+            r#"{"cmd":"state"}"#,
+        ]
+        .join("\n");
+        let mut out = Vec::new();
+        remote.serve(requests.as_bytes(), &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap().lines().collect::<Vec<_>>(),
+            [
+                r#"{"event":"ok"}"#,
+                r#"{"event":"ok"}"#,
+                r#"{"event":"bell"}"#,
+                r#"{"event":"submitted","line":"s"}"#,
+                r#"{"event":"ok"}"#,
+                r#"{"event":"ok"}"#,
+                r#"{"event":"state","prompt":"$ ","line":"s","cursor":0,"history":["s"]}"#,
+                r#"{"event":"error","message":"unknown key `hyper-a`"}"#,
+                r#"{"event":"bye"}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_editor_config() {
+        let mut remote = Remote::new("> ");
+        // the keys go through the middlewares and the validator of the Editor
+        remote
+            .editor
+            .middlewares
+            .push(Box::new(FnMiddleware(|key: Key, _: &_| {
+                match key == Key::char('x') {
+                    true => Flow::Pass(Key::char('y')),
+                    false => Flow::Pass(key),
+                }
+            })));
+        remote.editor.validator = Some(Box::new(FnValidator(|line: &str| match line.len() < 2 {
+            true => Validation::Invalid("too short".into()),
+            false => Validation::Valid,
+        })));
+
+        assert_eq!(
+            remote.handle(r#"{"cmd":"keys","keys":["x","enter","x","enter","ctrl-d"]}"#),
+            [
+                // the line too short to be submitted
+                r#"{"event":"bell"}"#,
+                r#"{"event":"submitted","line":"yy"}"#,
+                r#"{"event":"eof"}"#,
+                r#"{"event":"ok"}"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+
+use crate::completion::{word_before, Completer, Completion};
+use crate::editor::Editor;
+use crate::style::{Color, Style};
+use crate::suggest::nearest;
+use crate::tokens::tokenize;
+
+// the commands every Repl has
+const BUILTINS: [(&str, &str); 3] = [
+    (
+        "help",
+        "lists the commands, or shows the help of the given one",
+    ),
+    (
+        "history",
+        "lists the submitted lines, or only those of the given command",
+    ),
+    ("exit", "leaves the repl"),
+];
+
+// a command handler, gets the args (quotes and escapes resolved) and returns the output
+type Handler = Box<dyn FnMut(&[String]) -> Result<String, String>>;
+
+/// A command registered in a [`Repl`]
+pub struct Command {
+    pub name: String,
+    pub help: String,
+    /// the words Tab completes in the args of the command
+    pub completions: Vec<String>,
+    handler: Handler,
+}
+
+impl std::fmt::Debug for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Command")
+            .field("name", &self.name)
+            .field("help", &self.help)
+            .field("completions", &self.completions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Command {
+    /// Adds words for Tab to complete
+    pub fn completions<S: Into<String>>(
+        &mut self,
+        words: impl IntoIterator<Item = S>,
+    ) -> &mut Self {
+        self.completions.extend(words.into_iter().map(Into::into));
+
+        self
+    }
+}
+
+/// What a line dispatched by a [`Repl`] resulted in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// the command ran, with this output
+    Output(String),
+    /// the command failed or does not exist
+    Error(String),
+    /// the line was blank
+    Empty,
+    /// the exit command ran
+    Exit,
+}
+
+/// A REPL toolkit layered on the [`Editor`]: register commands with their names, help texts,
+/// completions and handlers, then [`Repl::run`] provides the loop and the dispatch along with
+/// the built-in `help`, `history` and `exit` commands
+#[derive(Debug, Default)]
+pub struct Repl {
+    commands: Vec<Command>,
+    // the dispatched lines, all of them then per command
+    lines: Vec<String>,
+    histories: HashMap<String, Vec<String>>,
+}
+
+impl Repl {
+    /// Creates a new Repl with only the built-in commands
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a command, replacing any command of the same name
+    ///
+    /// returns the command so that its completions can be added
+    ///
+    /// # Errors
+    /// Returns an error when the name is the one of a built-in command, which would shadow it
+    pub fn command(
+        &mut self,
+        name: &str,
+        help: &str,
+        handler: impl FnMut(&[String]) -> Result<String, String> + 'static,
+    ) -> Result<&mut Command, String> {
+        if BUILTINS.iter().any(|(builtin, _)| *builtin == name) {
+            return Err(format!("`{}` is a built-in command", name));
+        }
+        self.commands.retain(|c| c.name != name);
+        self.commands.push(Command {
+            name: name.to_string(),
+            help: help.to_string(),
+            completions: Vec::new(),
+            handler: Box::new(handler),
+        });
+
+        Ok(self.commands.last_mut().unwrap())
+    }
+
+    /// Returns the lines dispatched to the given command, oldest first
+    pub fn command_history(&self, name: &str) -> &[String] {
+        self.histories.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Reads and dispatches lines until the exit command or Ctrl-D, the outputs and errors
+    /// get printed above the prompt; Ctrl-C only drops the line being edited
+    ///
+    /// Tab completes the command names in the first word and the completions of the command
+    /// in its args; the completer, candidates and commands of the editor are restored once
+    /// the loop ends
+    ///
+    /// # Errors
+    /// Returns the errors of [`Editor::read_line`] other than Ctrl-C and Ctrl-D
+    pub fn run(&mut self, editor: &mut Editor) -> std::io::Result<()> {
+        let completer = CommandCompleter {
+            names: self.names(),
+            completions: self
+                .commands
+                .iter()
+                .map(|c| (c.name.clone(), c.completions.clone()))
+                .collect(),
+        };
+        let completer = editor.completer.replace(Box::new(completer));
+        let candidates = std::mem::take(&mut editor.candidates);
+        let commands = std::mem::replace(&mut editor.commands, self.names());
+
+        let result = self.run_loop(editor);
+        editor.completer = completer;
+        editor.candidates = candidates;
+        editor.commands = commands;
+
+        result
+    }
+
+    fn run_loop(&mut self, editor: &mut Editor) -> std::io::Result<()> {
+        loop {
+            let line = match editor.read_line() {
+                Ok(line) => line,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {
+                    editor.drop_line()?;
+                    continue;
+                }
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            match self.dispatch(&line) {
+                Outcome::Output(output) if !output.is_empty() => editor.print_above(&output)?,
+                Outcome::Error(e) => editor.print_above(&format!(
+                    "{}{}\x1b[0m",
+                    Style::new().fg(Color::Red).sgr(),
+                    e
+                ))?,
+                Outcome::Exit => return Ok(()),
+                _ => (),
+            }
+        }
+    }
+
+    /// Runs the command of a line, the first word of the line is the command name and the
+    /// others its args
+    pub fn dispatch(&mut self, line: &str) -> Outcome {
+        let args = tokenize(&line.chars().collect::<Vec<char>>())
+            .into_iter()
+            .map(|t| t.text)
+            .collect::<Vec<String>>();
+        let Some((name, args)) = args.split_first() else {
+            return Outcome::Empty;
+        };
+        self.lines.push(line.to_string());
+        self.histories
+            .entry(name.clone())
+            .or_default()
+            .push(line.to_string());
+
+        match name.as_str() {
+            "help" => self.help(args.first()),
+            "history" => {
+                let lines = match args.first() {
+                    Some(name) => self.command_history(name),
+                    None => &self.lines,
+                };
+                Outcome::Output(
+                    lines
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, line)| format!("{:>4}  {}", idx + 1, line))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                )
+            }
+            "exit" => Outcome::Exit,
+            _ => match self.commands.iter_mut().find(|c| c.name == *name) {
+                Some(command) => match (command.handler)(args) {
+                    Ok(output) => Outcome::Output(output),
+                    Err(e) => Outcome::Error(e),
+                },
+                None => {
+                    let names = self.names();
+                    Outcome::Error(match nearest(name, names.iter().map(String::as_str)) {
+                        Some(near) => {
+                            format!("unknown command `{}`, did you mean `{}`?", name, near)
+                        }
+                        None => format!("unknown command `{}`, try `help`", name),
+                    })
+                }
+            },
+        }
+    }
+
+    // the names of the commands, built-ins included
+    fn names(&self) -> Vec<String> {
+        BUILTINS
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .chain(self.commands.iter().map(|c| c.name.clone()))
+            .collect()
+    }
+
+    // the help of a command, or the list of the commands
+    fn help(&self, name: Option<&String>) -> Outcome {
+        let helps = BUILTINS.iter().map(|(name, help)| (*name, *help)).chain(
+            self.commands
+                .iter()
+                .map(|c| (c.name.as_str(), c.help.as_str())),
+        );
+        match name {
+            Some(name) => match helps.into_iter().find(|(n, _)| n == name) {
+                Some((_, help)) => Outcome::Output(help.to_string()),
+                None => Outcome::Error(format!("unknown command `{}`", name)),
+            },
+            None => {
+                let helps = helps.collect::<Vec<_>>();
+                let width = helps
+                    .iter()
+                    .map(|(n, _)| n.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                Outcome::Output(
+                    helps
+                        .into_iter()
+                        .map(|(name, help)| format!("{:width$}  {}", name, help))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                )
+            }
+        }
+    }
+}
+
+// completes the command names in the first word of the line and the completions of the
+// command in the other words
+#[derive(Debug)]
+struct CommandCompleter {
+    names: Vec<String>,
+    completions: HashMap<String, Vec<String>>,
+}
+
+impl Completer for CommandCompleter {
+    fn complete(&self, line: &str, cursor: usize) -> Vec<Completion> {
+        let (start, word) = word_before(line, cursor);
+        let before = line.chars().take(start).collect::<String>();
+        let words = match before.split_whitespace().next() {
+            None => &self.names,
+            Some(name) => match self.completions.get(name) {
+                Some(words) => words,
+                None => return Vec::new(),
+            },
+        };
+
+        words
+            .iter()
+            .filter(|w| w.starts_with(&word))
+            .map(|w| Completion::new(start, w))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_repl {
+    use super::{CommandCompleter, Outcome, Repl};
+    use crate::completion::Completer;
+    use crate::editor::Editor;
+    use crate::events::KeyEvent;
+    use crate::key::{Code, Key};
+
+    #[test]
+    fn test_dispatch() {
+        let mut repl = Repl::new();
+        repl.command("echo", "prints its args", |args| Ok(args.join(" ")))
+            .unwrap()
+            .completions(["--loud"]);
+        repl.command("fail", "always fails", |_| Err("nope".to_string()))
+            .unwrap();
+        assert_eq!(
+            repl.command("exit", "shadows exit", |_| Ok(String::new()))
+                .unwrap_err(),
+            "`exit` is a built-in command"
+        );
+
+        assert_eq!(repl.dispatch("  "), Outcome::Empty);
+        assert_eq!(
+            repl.dispatch("echo 'a b' c"),
+            Outcome::Output("a b c".to_string())
+        );
+        assert_eq!(repl.dispatch("fail"), Outcome::Error("nope".to_string()));
+        assert_eq!(
+            repl.dispatch("ecko"),
+            Outcome::Error("unknown command `ecko`, did you mean `echo`?".to_string())
+        );
+        assert_eq!(
+            repl.dispatch("help echo"),
+            Outcome::Output("prints its args".to_string())
+        );
+        assert_eq!(repl.command_history("echo"), ["echo 'a b' c"]);
+        assert_eq!(
+            repl.dispatch("history fail"),
+            Outcome::Output("   1  fail".to_string())
+        );
+        assert_eq!(repl.dispatch("exit"), Outcome::Exit);
+    }
+
+    #[test]
+    fn test_run_completions() {
+        let completer = CommandCompleter {
+            names: vec!["echo".to_string(), "exit".to_string()],
+            completions: [("echo".to_string(), vec!["--loud".to_string()])].into(),
+        };
+        let texts = |line: &str| {
+            completer
+                .complete(line, line.chars().count())
+                .into_iter()
+                .map(|c| c.text)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(texts("e"), ["echo", "exit"]);
+        assert_eq!(texts("echo -"), ["--loud"]);
+        assert!(texts("exit -").is_empty());
+        assert!(texts("echo e").is_empty());
+
+        let mut repl = Repl::new();
+        repl.command("echo", "prints its args", |args| Ok(args.join(" ")))
+            .unwrap();
+        let mut editor = Editor::headless("> ");
+        editor.candidates = vec!["mine".to_string()];
+        editor
+            .queued
+            .extend("exit".chars().map(|c| KeyEvent::Key(Key::char(c))));
+        editor
+            .queued
+            .push_back(KeyEvent::Key(Key::from(Code::Enter)));
+        repl.run(&mut editor).unwrap();
+        assert!(editor.completer.is_none());
+        assert_eq!(editor.candidates, ["mine"]);
+        assert!(editor.commands.is_empty());
+    }
+
+    #[test]
+    fn test_run_interrupted() {
+        let mut repl = Repl::new();
+        let mut editor = Editor::headless("> ");
+        // the line typed before Ctrl-C would be submitted along with exit, keeping the loop on
+        let keys = "ls".chars().map(Key::char).chain([Key::ctrl('c')]);
+        editor.queued.extend(keys.map(KeyEvent::Key));
+        editor
+            .queued
+            .extend("exit".chars().map(|c| KeyEvent::Key(Key::char(c))));
+        editor
+            .queued
+            .push_back(KeyEvent::Key(Key::from(Code::Enter)));
+
+        assert!(repl.run(&mut editor).is_ok());
+        assert!(editor.input.values.is_empty());
+    }
+}
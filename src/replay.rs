@@ -0,0 +1,213 @@
+use std::io::{BufRead, BufWriter, Error, ErrorKind, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::editor::Editor;
+use crate::events::KeyEvent;
+use crate::json::Json;
+use crate::key::Key;
+
+/// A key event of a recording, along with when it came in since the recording started
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recorded {
+    pub at: Duration,
+    pub event: KeyEvent,
+}
+
+impl Recorded {
+    /// Returns the event as a JSON line: `{"t":12,"key":"ctrl-a"}` or `{"t":12,"paste":"text"}`,
+    /// with the milliseconds since the recording started and the key named as in
+    /// [`Key::from_str`](std::str::FromStr); None for the unknown sequences and the mouse reports
+    pub fn to_json(&self) -> Option<String> {
+        let t = Json::Num(self.at.as_millis() as f64);
+        let json = match &self.event {
+            KeyEvent::Key(key) => Json::obj([("t", t), ("key", key.to_string().into())]),
+            KeyEvent::Paste(text) => Json::obj([("t", t), ("paste", text.as_str().into())]),
+            KeyEvent::Unknown(_) | KeyEvent::Mouse(_) => return None,
+        };
+
+        Some(json.to_string())
+    }
+
+    /// Parses a JSON line written by [`Recorded::to_json`], None if it is malformed
+    pub fn from_json(line: &str) -> Option<Self> {
+        let json = Json::parse(line)?;
+        let at = Duration::from_millis(json.get("t")?.as_num()? as u64);
+        let event = match (json.get("key"), json.get("paste")) {
+            (Some(key), None) => KeyEvent::Key(key.as_str()?.parse::<Key>().ok()?),
+            (None, Some(text)) => KeyEvent::Paste(text.as_str()?.to_string()),
+            _ => return None,
+        };
+
+        Some(Self { at, event })
+    }
+}
+
+/// Records the key events read by an Editor to a file, one JSON line per event, see
+/// [`crate::Editor::recorder`]
+///
+/// the events are left out while the line is sensitive, which the masked lines are, see
+/// [`crate::Input::sensitive`] and [`crate::Input::set_mask`]
+#[derive(Debug)]
+pub struct Recorder {
+    start: Instant,
+    file: BufWriter<std::fs::File>,
+}
+
+impl Recorder {
+    /// Creates a new Recorder writing to path, truncating it
+    ///
+    /// # Errors
+    /// Returns the error of creating the file
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self {
+            start: Instant::now(),
+            file: BufWriter::new(std::fs::File::create(path)?),
+        })
+    }
+
+    /// Records an event, flushed right away so that a crash keeps it
+    ///
+    /// # Errors
+    /// Returns the error of writing to the file
+    pub fn record(&mut self, event: &KeyEvent) -> std::io::Result<()> {
+        let recorded = Recorded {
+            at: self.start.elapsed(),
+            event: event.clone(),
+        };
+        if let Some(line) = recorded.to_json() {
+            writeln!(self.file, "{}", line)?;
+        }
+
+        self.file.flush()
+    }
+}
+
+/// Reads the events of a recording written by a [`Recorder`], the blank lines skipped
+///
+/// # Errors
+/// Returns the error of reading the file, or an [`ErrorKind::InvalidData`] error for a
+/// malformed line
+pub fn load(path: impl AsRef<Path>) -> std::io::Result<Vec<Recorded>> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut events = Vec::new();
+    for (idx, line) in file.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let recorded = Recorded::from_json(&line).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("malformed event on line {}", idx + 1),
+            )
+        })?;
+        events.push(recorded);
+    }
+
+    Ok(events)
+}
+
+/// Replays the events through [`Editor::feed`], with the keymap, middlewares, validator and
+/// completions of the editor, without waiting between the events; a Ctrl-C drops the line like
+/// in [`Editor::run`], a Ctrl-D in an empty line ends the replay
+///
+/// e.g. against an [`Editor::headless`], returns the lines submitted along the way
+///
+/// # Errors
+/// Returns the errors of [`Editor::feed`] other than Ctrl-C and Ctrl-D
+pub fn replay(events: &[Recorded], editor: &mut Editor) -> std::io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    editor
+        .queued
+        .extend(events.iter().map(|recorded| recorded.event.clone()));
+    while let Some(event) = editor.queued.pop_front() {
+        let key = match event {
+            KeyEvent::Key(key) => key,
+            KeyEvent::Paste(text) => {
+                editor.paste(&text)?;
+                continue;
+            }
+            KeyEvent::Unknown(_) | KeyEvent::Mouse(_) => continue,
+        };
+        match editor.feed(key) {
+            Ok(Some(line)) => lines.push(line),
+            Ok(None) => (),
+            Err(e) if e.kind() == ErrorKind::Interrupted => editor.input.clear_line(),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => {
+                editor.queued.clear();
+                return Err(e);
+            }
+        }
+    }
+    editor.queued.clear();
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod test_replay {
+    use super::{load, replay, Recorded, Recorder};
+    use crate::editor::Editor;
+    use crate::events::KeyEvent;
+    use crate::key::{Code, Key};
+
+    #[test]
+    fn test_record_replay() {
+        let path = std::env::temp_dir().join(format!("ragout_test_replay_{}", std::process::id()));
+        let mut recorder = Recorder::create(&path).unwrap();
+        [
+            KeyEvent::Key(Key::char('l')),
+            KeyEvent::Paste("s -la".into()),
+            KeyEvent::Unknown(b"\x1b[M".to_vec()),
+            KeyEvent::Key(Key::ctrl('a')),
+            KeyEvent::Key(Key::char('#')),
+            KeyEvent::Key(Key::from(Code::Enter)),
+            KeyEvent::Key(Key::from(Code::Other)),
+            KeyEvent::Key(Key::from(Code::Up)),
+        ]
+        .iter()
+        .for_each(|event| recorder.record(event).unwrap());
+        drop(recorder);
+
+        let events = load(&path).unwrap();
+        _ = std::fs::remove_file(path);
+        assert_eq!(events.len(), 7);
+        assert_eq!(events[1].event, KeyEvent::Paste("s -la".into()));
+        assert_eq!(
+            Recorded::from_json(r#"{"t":3,"key":"alt-."}"#).map(|r| r.event),
+            Some(KeyEvent::Key(Key::alt('.')))
+        );
+
+        let mut editor = Editor::headless("testing input> ");
+        assert_eq!(replay(&events, &mut editor).unwrap(), ["#ls -la"]);
+        assert_eq!(editor.history.len(), 1);
+        assert_eq!(editor.input.values.iter().collect::<String>(), "#ls -la");
+        assert_eq!(editor.input.prompt, "testing input> ");
+    }
+
+    #[test]
+    fn test_record_secrets() {
+        let path = std::env::temp_dir().join(format!("ragout_test_secrets_{}", std::process::id()));
+        let mut editor = Editor::headless("password: ");
+        editor.recorder = Some(Recorder::create(&path).unwrap());
+        editor.input.set_mask(Some('*'));
+        "hunter2"
+            .chars()
+            .for_each(|c| _ = editor.feed(Key::char(c)));
+        assert_eq!(
+            editor.feed(Key::from(Code::Enter)).unwrap().as_deref(),
+            Some("hunter2")
+        );
+        editor.input.unmask();
+        editor.input.sensitive = false;
+        editor.feed(Key::char('y')).unwrap();
+        editor.recorder = None;
+
+        let events = load(&path).unwrap();
+        _ = std::fs::remove_file(path);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, KeyEvent::Key(Key::char('y')));
+    }
+}
@@ -0,0 +1,245 @@
+use std::io::Write;
+
+use crate::input::Input;
+use crate::style::Style;
+
+/// A single styled cell of the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub c: char,
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            c: ' ',
+            style: Style::new(),
+        }
+    }
+}
+
+/// A grid of styled cells modelling the rows of the terminal owned by the editor
+/// (the prompt area), which gets diff-painted onto the terminal.
+///
+/// The area is positioned relatively to its first row, so it works inside and outside of the
+/// alternate screen; painting assumes the terminal cursor is where the last paint left it
+/// (or at the start of the first row before the first paint)
+#[derive(Debug, Clone)]
+pub struct Screen {
+    width: usize,
+    height: usize,
+    // what is to be painted
+    back: Vec<Cell>,
+    // what is currently on the terminal
+    front: Vec<Cell>,
+    // where the terminal cursor is, as (column, row) in the area
+    painted_cursor: (usize, usize),
+    /// where the cursor goes once painting is done, as (column, row) in the area
+    pub cursor: (usize, usize),
+}
+
+impl Screen {
+    /// Creates a new blank Screen of the given size
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            back: vec![Cell::default(); width * height],
+            front: vec![Cell::default(); width * height],
+            painted_cursor: (0, 0),
+            cursor: (0, 0),
+        }
+    }
+
+    /// Returns the Screen size as (width, height)
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Resizes the Screen, the whole area gets repainted on the next paint
+    pub fn resize(&mut self, width: usize, height: usize) {
+        *self = Self {
+            painted_cursor: self.painted_cursor,
+            cursor: self.cursor,
+            ..Self::new(width, height)
+        };
+        // an impossible front forces every cell to be repainted
+        self.front.fill(Cell {
+            c: '\0',
+            style: Style::new(),
+        });
+    }
+
+    /// Blanks every cell of the Screen
+    pub fn clear(&mut self) {
+        self.back.fill(Cell::default());
+    }
+
+    /// Blanks every cell of a row
+    pub fn clear_row(&mut self, row: usize) {
+        if row < self.height {
+            self.back[row * self.width..(row + 1) * self.width].fill(Cell::default());
+        }
+    }
+
+    /// Returns the cell at the given column and row
+    pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
+        (x < self.width && y < self.height).then(|| &self.back[y * self.width + x])
+    }
+
+    /// Sets the cell at the given column and row, out of bounds cells are ignored
+    pub fn put(&mut self, x: usize, y: usize, c: char, style: Style) {
+        if x < self.width && y < self.height {
+            self.back[y * self.width + x] = Cell { c, style };
+        }
+    }
+
+    /// Writes text starting at the given column and row, clipped at the end of the row
+    ///
+    /// returns the column after the last written char
+    pub fn print(&mut self, x: usize, y: usize, text: &str, style: Style) -> usize {
+        text.chars()
+            .fold(x, |x, c| {
+                self.put(x, y, c, style);
+                x + 1
+            })
+            .min(self.width)
+    }
+
+    /// Returns the text of a row, without its styling
+    pub fn row_text(&self, row: usize) -> String {
+        self.back[row * self.width..(row + 1) * self.width]
+            .iter()
+            .map(|cell| cell.c)
+            .collect()
+    }
+
+    /// Writes the escapes turning what is on the terminal into the Screen content,
+    /// only the changed cells get written
+    pub fn paint(&mut self, w: &mut impl Write) -> std::io::Result<()> {
+        let mut out = Vec::new();
+        let mut style: Option<Style> = None;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let idx = y * self.width + x;
+                let cell = self.back[idx];
+                if cell == self.front[idx] {
+                    continue;
+                }
+                if self.painted_cursor != (x, y) {
+                    self.move_to(&mut out, x, y);
+                }
+                if style != Some(cell.style) {
+                    out.extend_from_slice(cell.style.sgr().as_bytes());
+                    style = Some(cell.style);
+                }
+                out.extend_from_slice(cell.c.encode_utf8(&mut [0; 4]).as_bytes());
+                // the terminal cursor stays on the last column after writing to it
+                self.painted_cursor = ((x + 1).min(self.width - 1), y);
+            }
+        }
+        if style.is_some() {
+            out.extend_from_slice(b"\x1b[0m");
+        }
+        let (x, y) = self.cursor;
+        self.move_to(&mut out, x, y);
+        self.front.copy_from_slice(&self.back);
+
+        w.write_all(&out)?;
+        w.flush()
+    }
+
+    // moves the terminal cursor, rows relatively and columns absolutely
+    fn move_to(&mut self, out: &mut Vec<u8>, x: usize, y: usize) {
+        let (_, row) = self.painted_cursor;
+        match y.cmp(&row) {
+            std::cmp::Ordering::Greater => out.extend(format!("\x1b[{}B", y - row).bytes()),
+            std::cmp::Ordering::Less => out.extend(format!("\x1b[{}A", row - y).bytes()),
+            std::cmp::Ordering::Equal => (),
+        }
+        out.extend(format!("\x1b[{}G", x + 1).bytes());
+        self.painted_cursor = (x, y);
+    }
+}
+
+impl Input {
+    /// Draws the Input prompt line (indicators, prompt, values and ghost text or suggestion) on a
+    /// row of the Screen and puts the Screen cursor at the Input cursor
+    pub fn draw(&self, screen: &mut Screen, row: usize) {
+        let [prefix, _, _] = self.indicators();
+        screen.clear_row(row);
+        let x = screen.print(0, row, &prefix, Style::new());
+        let x = screen.print(x, row, &self.prompt, Style::new());
+        let x = screen.print(x, row, &self.shown(0..self.cursor), Style::new());
+        let x = match self.preview() {
+            Some(ghost) => screen.print(x, row, ghost, Style::new().dim()),
+            None => x,
+        };
+        screen.print(
+            x,
+            row,
+            &self.shown(self.cursor..self.values.len()),
+            Style::new(),
+        );
+        screen.cursor = ((self.cursor_column() - 1).min(screen.size().0), row);
+    }
+}
+
+#[cfg(test)]
+mod test_screen {
+    use super::Screen;
+    use crate::input::Input;
+    use crate::style::Style;
+
+    fn paint(screen: &mut Screen) -> String {
+        let mut out = Vec::new();
+        screen.paint(&mut out).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_diff_paint() {
+        let mut screen = Screen::new(6, 2);
+        screen.print(0, 0, "pika", Style::new());
+        assert_eq!(paint(&mut screen), "\x1b[0mpika\x1b[0m\x1b[1G");
+
+        // only the changed cells get painted
+        screen.print(2, 0, "ku", Style::new());
+        screen.put(1, 1, 'x', Style::new().bold());
+        screen.cursor = (4, 0);
+        assert_eq!(
+            paint(&mut screen),
+            "\x1b[4G\x1b[0mu\x1b[1B\x1b[2G\x1b[0;1mx\x1b[0m\x1b[1A\x1b[5G"
+        );
+        assert_eq!(paint(&mut screen), "\x1b[5G");
+    }
+
+    #[test]
+    fn test_draw_input() {
+        let mut screen = Screen::new(20, 1);
+        let mut i = Input::new("> ", false);
+        i.set_text("pikachu");
+        i.set_cursor(4);
+
+        i.draw(&mut screen, 0);
+        assert_eq!(screen.row_text(0), "> pikachu           ");
+        assert_eq!(screen.cursor, (6, 0));
+    }
+
+    #[test]
+    fn test_draw_ghost() {
+        let mut screen = Screen::new(12, 1);
+        let mut i = Input::new("> ", false);
+        i.set_text("git  -v");
+        i.set_cursor(4);
+        i.ghost = Some("log".into());
+
+        i.draw(&mut screen, 0);
+        assert_eq!(screen.row_text(0), "> git log -v");
+        assert!(screen.get(6, 0).unwrap().style.dim);
+        assert!(!screen.get(10, 0).unwrap().style.dim);
+        assert_eq!(screen.cursor, (6, 0));
+    }
+}
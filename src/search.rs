@@ -0,0 +1,215 @@
+use crate::history::History;
+use crate::input::{Input, TerminalWriter};
+
+/// The state of an incremental reverse history search (Ctrl-R)
+#[derive(Debug, Clone, Default)]
+pub struct Search {
+    pub query: Vec<char>,
+    /// the index of the matched history entry and the char offset of the match inside of it
+    pub hit: Option<(usize, usize)>,
+    /// whether the last query change or search step found no match
+    pub failed: bool,
+    // the Input prompt from before the search started
+    prompt: Option<String>,
+}
+
+impl Search {
+    /// Creates a new empty Search
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a search, replacing the Input prompt with the `(reverse-i-search)` prompt
+    ///
+    /// the original prompt is restored on [`Search::accept`] or [`Search::cancel`]
+    pub fn start(i: &mut Input) -> Self {
+        let search = Self {
+            prompt: Some(i.prompt.clone()),
+            ..Self::default()
+        };
+        search.update_prompt(i);
+
+        search
+    }
+
+    /// Overwrites the Input prompt with the search prompt reflecting the current query
+    pub fn update_prompt(&self, i: &mut Input) {
+        i.overwrite_prompt(&format!(
+            "({}reverse-i-search)`{}': ",
+            if self.failed { "failed " } else { "" },
+            self.query.iter().collect::<String>()
+        ));
+    }
+
+    /// Appends a char to the search query then looks for a match starting from the current one
+    ///
+    /// returns false if no history entry matches the new query
+    pub fn push(&mut self, c: char, h: &History) -> bool {
+        self.query.push(c);
+        let from = self.hit.map_or(h.values.len(), |(idx, _)| idx + 1);
+
+        self.find(h, from)
+    }
+
+    /// Removes the last char of the search query then looks for a match starting from the newest
+    /// history entry
+    pub fn pop(&mut self, h: &History) -> bool {
+        if self.query.pop().is_none() {
+            return false;
+        }
+        if self.query.is_empty() {
+            self.hit = None;
+            self.failed = false;
+            return true;
+        }
+
+        self.find(h, h.values.len())
+    }
+
+    /// Looks for an older match of the current query
+    pub fn next(&mut self, h: &History) -> bool {
+        let from = self.hit.map_or(h.values.len(), |(idx, _)| idx);
+
+        self.find(h, from)
+    }
+
+    /// Returns the matched history entry
+    pub fn entry<'a>(&self, h: &'a History) -> Option<&'a [char]> {
+        self.hit.map(|(idx, _)| h.values[idx].as_slice())
+    }
+
+    /// Ends the search, binding the matched history entry (if any) to the Input values
+    pub fn accept(self, i: &mut Input, h: &History) {
+        if let Some(entry) = self.entry(h) {
+            i.values.set(entry.iter().copied());
+            i.cursor = i.values.len();
+            i.mark_clean();
+        }
+        self.cancel(i);
+    }
+
+    /// Ends the search leaving the Input values untouched
+    pub fn cancel(self, i: &mut Input) {
+        if let Some(prompt) = self.prompt {
+            i.overwrite_prompt(&prompt);
+        }
+    }
+
+    // keeps the old hit when the query has no match so that the preview doesn't go blank
+    fn find(&mut self, h: &History, from: usize) -> bool {
+        match h.search_back(&self.query, from) {
+            Some(hit) => {
+                self.hit = Some(hit);
+                self.failed = false;
+            }
+            None => self.failed = true,
+        }
+
+        !self.failed
+    }
+}
+
+impl History {
+    /// Looks through the history entries before the `before` index, newest first, for one
+    /// containing query
+    ///
+    /// returns the index of the matched entry and the char offset of the match inside of it
+    pub fn search_back(&self, query: &[char], before: usize) -> Option<(usize, usize)> {
+        if query.is_empty() {
+            return None;
+        }
+
+        self.values[..before.min(self.values.len())]
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(idx, entry)| {
+                entry
+                    .windows(query.len())
+                    .position(|w| w == query)
+                    .map(|offset| (idx, offset))
+            })
+    }
+}
+
+impl Input {
+    /// Renders the Input prompt followed by the history entry matched by the search, with the
+    /// matched part of the entry highlighted, then puts the cursor at the start of the match
+    pub fn write_search(&self, sol: &mut dyn TerminalWriter, search: &Search, h: &History) {
+        _ = sol.write(b"\x1b[2K");
+        _ = sol.write(&[13]);
+        _ = sol.write(self.prompt.as_bytes());
+
+        let Some((_, offset)) = search.hit else {
+            _ = sol.flush();
+            return;
+        };
+        let entry = search.entry(h).unwrap();
+        let end = (offset + search.query.len()).min(entry.len());
+
+        _ = sol.write(entry[..offset].iter().collect::<String>().as_bytes());
+        _ = sol.write(b"\x1b[7m");
+        _ = sol.write(entry[offset..end].iter().collect::<String>().as_bytes());
+        _ = sol.write(b"\x1b[27m");
+        _ = sol.write(entry[end..].iter().collect::<String>().as_bytes());
+        _ = sol.write(format!("\x1b[{}G", self.prompt.chars().count() + offset + 1).as_bytes());
+        _ = sol.flush();
+    }
+}
+
+#[cfg(test)]
+mod test_search {
+    use super::Search;
+    use crate::history::History;
+    use crate::input::Input;
+
+    fn history() -> History {
+        let mut h = History::new();
+        ["cargo build", "ls", "cargo test", "git status"]
+            .into_iter()
+            .for_each(|e| h.push(e.chars().collect()));
+
+        h
+    }
+
+    #[test]
+    fn test_search() {
+        let h = history();
+        let mut s = Search::new();
+
+        "car".chars().for_each(|c| assert!(s.push(c, &h)));
+        assert_eq!(s.hit, Some((2, 0)));
+
+        assert!(s.next(&h));
+        assert_eq!(s.hit, Some((0, 0)));
+        assert!(!s.next(&h));
+
+        // no match keeps the previous hit
+        assert!(!s.push('x', &h));
+        assert_eq!(s.hit, Some((0, 0)));
+        assert!(s.pop(&h));
+        assert_eq!(s.hit, Some((2, 0)));
+    }
+
+    #[test]
+    fn test_accept() {
+        let h = history();
+        let mut i = Input::new("testing input> ", false);
+        let mut s = Search::start(&mut i);
+        assert_eq!(i.prompt, "(reverse-i-search)`': ");
+
+        "stat".chars().for_each(|c| _ = s.push(c, &h));
+        s.update_prompt(&mut i);
+        assert_eq!(s.hit, Some((3, 4)));
+        assert_eq!(i.prompt, "(reverse-i-search)`stat': ");
+
+        s.push('z', &h);
+        s.update_prompt(&mut i);
+        assert_eq!(i.prompt, "(failed reverse-i-search)`statz': ");
+
+        s.accept(&mut i, &h);
+        assert_eq!(i.values.iter().collect::<String>(), "git status");
+        assert_eq!(i.cursor, 10);
+        assert_eq!(i.prompt, "testing input> ");
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+type Provider = Arc<dyn Fn() -> String + Send + Sync>;
+
+// a segment provider and the value it last computed
+struct Segment {
+    provider: Provider,
+    placeholder: String,
+    value: Arc<Mutex<Option<String>>>,
+    // bumped on every refresh so that the results of outdated computations get dropped
+    generation: Arc<AtomicUsize>,
+}
+
+/// A prompt template whose `{name}` placeholders (e.g. `{git}` or `{kube}`) get filled in by
+/// providers running on background threads, so that slow providers never block typing
+///
+/// until a provider is done, its segment is rendered with its placeholder text
+#[derive(Default)]
+pub struct PromptSegments {
+    pub template: String,
+    segments: HashMap<String, Segment>,
+    // set by the providers when they are done
+    ready: Arc<AtomicBool>,
+}
+
+impl std::fmt::Debug for PromptSegments {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PromptSegments")
+            .field("template", &self.template)
+            .field("segments", &self.segments.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl PromptSegments {
+    /// Creates new PromptSegments from a prompt template, e.g. `"[{git}] > "`
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: template.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Registers the provider computing the `{name}` segment, which is rendered as placeholder
+    /// until the provider is done
+    pub fn segment(
+        &mut self,
+        name: &str,
+        placeholder: &str,
+        provider: impl Fn() -> String + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.segments.insert(
+            name.to_string(),
+            Segment {
+                provider: Arc::new(provider),
+                placeholder: placeholder.to_string(),
+                value: Arc::new(Mutex::new(None)),
+                generation: Arc::new(AtomicUsize::new(0)),
+            },
+        );
+
+        self
+    }
+
+    /// Runs every provider again on its own thread, each keeps showing its last value until its
+    /// new one is ready
+    pub fn refresh(&self) {
+        self.segments.values().for_each(|segment| {
+            let generation = segment.generation.fetch_add(1, Ordering::SeqCst) + 1;
+            let (provider, value, current, ready) = (
+                segment.provider.clone(),
+                segment.value.clone(),
+                segment.generation.clone(),
+                self.ready.clone(),
+            );
+            std::thread::spawn(move || {
+                let computed = provider();
+                if current.load(Ordering::SeqCst) == generation {
+                    *value.lock().unwrap() = Some(computed);
+                    ready.store(true, Ordering::SeqCst);
+                }
+            });
+        });
+    }
+
+    /// Returns true if any segment got a new value since the last call,
+    /// meaning the prompt should be rendered again
+    pub fn take_ready(&self) -> bool {
+        self.ready.swap(false, Ordering::SeqCst)
+    }
+
+    /// Returns the prompt with the segment placeholders replaced by the segment values
+    pub fn render(&self) -> String {
+        self.segments
+            .iter()
+            .fold(self.template.clone(), |prompt, (name, segment)| {
+                let value = segment.value.lock().unwrap();
+                prompt.replace(
+                    &format!("{{{}}}", name),
+                    value.as_deref().unwrap_or(&segment.placeholder),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod test_segments {
+    use super::PromptSegments;
+    use std::sync::mpsc::channel;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_segments() {
+        let (tx, rx) = channel::<()>();
+        let rx = Mutex::new(rx);
+        let mut p = PromptSegments::new("[{git}|{user}] > ");
+        p.segment("git", "…", move || {
+            // blocks until the test lets it through
+            _ = rx.lock().unwrap().recv();
+            "main".to_string()
+        })
+        .segment("user", "?", || "ash".to_string());
+
+        p.refresh();
+        let start = Instant::now();
+        while p.render() != "[…|ash] > " {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::yield_now();
+        }
+        assert!(p.take_ready());
+
+        tx.send(()).unwrap();
+        while p.render() != "[main|ash] > " {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::yield_now();
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::OnceLock;
+
+use crate::term::{cooked_termios, handle_signal, restore_dying};
+
+/// The signals [`install_cleanup`] restores the terminal on
+pub const CLEANUP_SIGNALS: [libc::c_int; 4] =
+    [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT];
+
+// the termios the handlers put back, set once before any handler is installed
+static COOKED: OnceLock<libc::termios> = OnceLock::new();
+
+// restores the terminal then lets the signal kill the process as it would have without the
+// handler, only calling async signal safe functions
+extern "C" fn on_signal(signal: libc::c_int) {
+    // COOKED is only read once set, which is an atomic load
+    restore_dying(COOKED.get());
+    // SAFETY: signal and raise are async signal safe
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+/// Installs handlers for the [`CLEANUP_SIGNALS`] that put the terminal back in cooked mode,
+/// leave the alternate screen and turn the modes [`crate::init`] enabled off before the
+/// process dies of the signal, so that a Ctrl-C raising SIGINT (see [`crate::RawConfig`]) or
+/// a `kill` never leaves the terminal in raw mode
+///
+/// called by [`crate::init`] with the `signals` feature, before entering the raw mode since
+/// the termios put back are the ones found by the first call; the handlers replace whichever were
+/// installed before, and the termination watch of [`crate::Editor::autosave_on_termination`]
+/// replaces them in turn for SIGTERM and SIGHUP, its watcher thread restoring the terminal the
+/// same way
+///
+/// # Errors
+/// Returns the error of sigaction, or of tcgetattr when the terminal was not in raw mode yet
+/// and its termios could not be read
+pub fn install_cleanup() -> std::io::Result<()> {
+    if COOKED.get().is_none() {
+        _ = COOKED.set(cooked_termios()?);
+    }
+    for signal in CLEANUP_SIGNALS {
+        handle_signal(signal, on_signal, 0)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_signals {
+    use super::install_cleanup;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    #[test]
+    fn test_cleanup() {
+        // the signaled process is this test run again on its own
+        if std::env::var_os("RAGOUT_TEST_CLEANUP").is_some() {
+            crate::state::ALT_SCREEN.store(true, std::sync::atomic::Ordering::SeqCst);
+            // without a terminal to read the termios of, the handler gets installed on its own
+            if install_cleanup().is_err() {
+                super::handle_signal(libc::SIGINT, super::on_signal, 0).unwrap();
+            }
+            // SAFETY: raise takes any signal number
+            unsafe { libc::raise(libc::SIGINT) };
+            return;
+        }
+
+        let out = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "signals::test_signals::test_cleanup",
+                "--exact",
+                "--nocapture",
+            ])
+            .env("RAGOUT_TEST_CLEANUP", "1")
+            .stdin(std::process::Stdio::null())
+            .output()
+            .unwrap();
+        assert_eq!(out.status.signal(), Some(libc::SIGINT));
+        let escapes = b"\x1b[?1049l\x1b[?2004l\x1b[?1002l\x1b[?1006l\x1b[?25h\x1b[0 q";
+        assert!(out.stdout.windows(escapes.len()).any(|w| w == escapes));
+    }
+}
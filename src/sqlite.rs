@@ -0,0 +1,197 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+
+use crate::store::HistoryStore;
+
+/// A HistoryStore on SQLite, scaling to hundreds of thousands of entries
+///
+/// every namespace (e.g. one per application or per context) gets its own table, with the
+/// submission timestamps of the entries and a trigram full text index for substring searches
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Connection,
+    table: String,
+    /// the number of entries kept, the oldest ones get pruned on append when it is exceeded
+    pub max_entries: Option<usize>,
+}
+
+impl SqliteStore {
+    /// Opens (or creates) the database at path and the table of the namespace,
+    /// namespaces being made of ascii alphanumerics and underscores
+    pub fn open(path: impl AsRef<Path>, namespace: &str) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(path)?, namespace)
+    }
+
+    /// Same as [`SqliteStore::open`] but on a database living in memory
+    pub fn in_memory(namespace: &str) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?, namespace)
+    }
+
+    fn with_connection(conn: Connection, namespace: &str) -> rusqlite::Result<Self> {
+        if namespace.is_empty()
+            || !namespace
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(rusqlite::Error::InvalidParameterName(namespace.to_string()));
+        }
+        let table = format!("history_{}", namespace);
+        conn.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {t} (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                entry TEXT NOT NULL,
+                at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS {t}_at ON {t} (at);
+            CREATE VIRTUAL TABLE IF NOT EXISTS {t}_fts
+                USING fts5(entry, content='{t}', content_rowid='id', tokenize='trigram');
+            CREATE TRIGGER IF NOT EXISTS {t}_insert AFTER INSERT ON {t} BEGIN
+                INSERT INTO {t}_fts (rowid, entry) VALUES (new.id, new.entry);
+            END;
+            CREATE TRIGGER IF NOT EXISTS {t}_delete AFTER DELETE ON {t} BEGIN
+                INSERT INTO {t}_fts ({t}_fts, rowid, entry) VALUES ('delete', old.id, old.entry);
+            END;",
+            t = table
+        ))?;
+
+        Ok(Self {
+            conn,
+            table,
+            max_entries: None,
+        })
+    }
+
+    /// Returns the number of stored entries
+    pub fn len(&self) -> rusqlite::Result<usize> {
+        self.conn
+            .query_row(&format!("SELECT count(*) FROM {}", self.table), [], |row| {
+                row.get(0)
+            })
+    }
+
+    /// Returns true if no entry is stored
+    pub fn is_empty(&self) -> rusqlite::Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Returns up to limit entries submitted at or after the given unix timestamp (in seconds),
+    /// newest first, along with their timestamps
+    pub fn since(&self, at: u64, limit: usize) -> rusqlite::Result<Vec<(String, u64)>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT entry, at FROM {} WHERE at >= ?1 ORDER BY id DESC LIMIT ?2",
+            self.table
+        ))?;
+        let rows = stmt.query_map(params![at, limit], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        rows.collect()
+    }
+}
+
+fn io(e: rusqlite::Error) -> std::io::Error {
+    std::io::Error::other(e)
+}
+
+impl HistoryStore for SqliteStore {
+    fn load(&mut self) -> std::io::Result<Vec<Vec<char>>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT entry FROM {} ORDER BY id", self.table))
+            .map_err(io)?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(io)?;
+
+        rows.map(|entry| entry.map(|e| e.chars().collect()).map_err(io))
+            .collect()
+    }
+
+    fn append(&mut self, entry: &[char]) -> std::io::Result<()> {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.conn
+            .execute(
+                &format!("INSERT INTO {} (entry, at) VALUES (?1, ?2)", self.table),
+                params![entry.iter().collect::<String>(), at],
+            )
+            .map_err(io)?;
+        match self.max_entries {
+            Some(keep) => self.prune(keep),
+            None => Ok(()),
+        }
+    }
+
+    fn search(&mut self, query: &[char], limit: usize) -> std::io::Result<Vec<Vec<char>>> {
+        let query = query.iter().collect::<String>();
+        // the trigram index can only look up queries of at least 3 chars
+        let sql = match query.chars().count() >= 3 {
+            true => format!(
+                "SELECT entry FROM {t}_fts WHERE entry MATCH ?1 ORDER BY rowid DESC LIMIT ?2",
+                t = self.table
+            ),
+            false => format!(
+                "SELECT entry FROM {} WHERE instr(entry, ?1) > 0 ORDER BY id DESC LIMIT ?2",
+                self.table
+            ),
+        };
+        let pattern = match query.chars().count() >= 3 {
+            // a quoted fts string matches the query as is
+            true => format!("\"{}\"", query.replace('"', "\"\"")),
+            false => query,
+        };
+        let mut stmt = self.conn.prepare(&sql).map_err(io)?;
+        let rows = stmt
+            .query_map(params![pattern, limit], |row| row.get::<_, String>(0))
+            .map_err(io)?;
+
+        rows.map(|entry| entry.map(|e| e.chars().collect()).map_err(io))
+            .collect()
+    }
+
+    fn prune(&mut self, keep: usize) -> std::io::Result<()> {
+        self.conn
+            .execute(
+                &format!(
+                    "DELETE FROM {t} WHERE id <= (SELECT max(id) FROM {t}) - ?1",
+                    t = self.table
+                ),
+                params![keep],
+            )
+            .map_err(io)
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test_sqlite {
+    use super::SqliteStore;
+    use crate::store::HistoryStore;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_sqlite_store() {
+        let mut store = SqliteStore::in_memory("test").unwrap();
+        ["ls", "cargo build", "git status", "cargo test"]
+            .into_iter()
+            .for_each(|e| store.append(&chars(e)).unwrap());
+
+        assert_eq!(store.len().unwrap(), 4);
+        assert_eq!(
+            store.search(&chars("cargo"), 5).unwrap(),
+            [chars("cargo test"), chars("cargo build")]
+        );
+        assert_eq!(store.search(&chars("ls"), 5).unwrap(), [chars("ls")]);
+        assert_eq!(store.since(0, 1).unwrap()[0].0, "cargo test");
+
+        store.max_entries = Some(2);
+        store.append(&chars("exit")).unwrap();
+        assert_eq!(store.load().unwrap(), [chars("cargo test"), chars("exit")]);
+        assert!(store.search(&chars("build"), 5).unwrap().is_empty());
+        assert!(SqliteStore::in_memory("drop table;").is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::input::CursorShape;
+
+// whether the terminal is in the alternate screen, which the signal handlers leave
+pub(crate) static ALT_SCREEN: AtomicBool = AtomicBool::new(false);
+
+// switches the terminal to or from the alternate screen, keeping track of which one it is in
+pub(crate) fn set_alt_screen(w: &mut impl Write, on: bool) -> std::io::Result<()> {
+    ALT_SCREEN.store(on, Ordering::SeqCst);
+    w.write_all(match on {
+        true => b"\x1b[?1049h",
+        false => b"\x1b[?1049l",
+    })
+}
+
+/// The terminal modes toggled through escape sequences, which the terminal can't be asked about
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modes {
+    pub alt_screen: bool,
+    /// mouse button and drag reporting with SGR encoding
+    pub mouse: bool,
+    pub bracketed_paste: bool,
+    pub cursor_shape: CursorShape,
+}
+
+impl Modes {
+    // the escapes switching the terminal from the from modes to these
+    fn escapes(&self, from: &Modes) -> String {
+        let mut escapes = String::new();
+        let toggle = |on: bool, code: &str| format!("\x1b[?{}{}", code, if on { 'h' } else { 'l' });
+        if self.alt_screen != from.alt_screen {
+            escapes.push_str(&toggle(self.alt_screen, "1049"));
+        }
+        if self.mouse != from.mouse {
+            escapes.push_str(&toggle(self.mouse, "1002"));
+            escapes.push_str(&toggle(self.mouse, "1006"));
+        }
+        if self.bracketed_paste != from.bracketed_paste {
+            escapes.push_str(&toggle(self.bracketed_paste, "2004"));
+        }
+        if self.cursor_shape != from.cursor_shape {
+            escapes.push_str(self.cursor_shape.escape());
+        }
+
+        escapes
+    }
+}
+
+// a saved terminal configuration
+#[derive(Clone, Copy)]
+struct State {
+    modes: Modes,
+    #[cfg(unix)]
+    termios: Option<libc::termios>,
+}
+
+/// Keeps track of the terminal modes and saves and restores the whole terminal configuration
+/// (termios flags and modes), so that nested components and child processes can't leak modes
+#[derive(Default)]
+pub struct TermStack {
+    modes: Modes,
+    saved: Vec<State>,
+}
+
+impl std::fmt::Debug for TermStack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TermStack")
+            .field("modes", &self.modes)
+            .field("depth", &self.saved.len())
+            .finish()
+    }
+}
+
+impl TermStack {
+    /// Creates a new TermStack, assuming all the modes are off
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current modes
+    pub fn modes(&self) -> Modes {
+        self.modes
+    }
+
+    /// Switches the terminal to the given modes, only writing the escapes of the changed ones
+    pub fn set_modes(&mut self, w: &mut impl Write, modes: Modes) -> std::io::Result<()> {
+        let escapes = modes.escapes(&self.modes);
+        if modes.alt_screen != self.modes.alt_screen {
+            ALT_SCREEN.store(modes.alt_screen, Ordering::SeqCst);
+        }
+        self.modes = modes;
+        w.write_all(escapes.as_bytes())?;
+
+        w.flush()
+    }
+
+    /// Returns the number of saved states
+    pub fn depth(&self) -> usize {
+        self.saved.len()
+    }
+
+    /// Saves the current terminal configuration
+    pub fn push_state(&mut self) -> std::io::Result<()> {
+        self.saved.push(State {
+            modes: self.modes,
+            #[cfg(unix)]
+            termios: termios()?,
+        });
+
+        Ok(())
+    }
+
+    /// Restores the last saved terminal configuration
+    ///
+    /// returns false if there was no saved state
+    pub fn pop_state(&mut self, w: &mut impl Write) -> std::io::Result<bool> {
+        let Some(state) = self.saved.pop() else {
+            return Ok(false);
+        };
+        #[cfg(unix)]
+        if let Some(termios) = state.termios {
+            // SAFETY: termios was filled by tcgetattr
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSADRAIN, &termios) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        self.set_modes(w, state.modes)?;
+
+        Ok(true)
+    }
+}
+
+// the termios of stdin, None if stdin is not a terminal
+#[cfg(unix)]
+pub(crate) fn termios() -> std::io::Result<Option<libc::termios>> {
+    // SAFETY: tcgetattr fills the zeroed termios, isatty only inspects the fd
+    unsafe {
+        if libc::isatty(libc::STDIN_FILENO) == 0 {
+            return Ok(None);
+        }
+        let mut termios: libc::termios = std::mem::zeroed();
+        match libc::tcgetattr(libc::STDIN_FILENO, &mut termios) {
+            0 => Ok(Some(termios)),
+            _ => Err(std::io::Error::last_os_error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_state {
+    use super::{Modes, TermStack};
+    use crate::input::CursorShape;
+
+    #[test]
+    fn test_push_pop() {
+        let mut stack = TermStack::new();
+        let mut out = Vec::new();
+        stack.push_state().unwrap();
+
+        let modes = Modes {
+            mouse: true,
+            bracketed_paste: true,
+            cursor_shape: CursorShape::Bar,
+            ..Modes::default()
+        };
+        stack.set_modes(&mut out, modes).unwrap();
+        assert_eq!(
+            String::from_utf8(std::mem::take(&mut out)).unwrap(),
+            "\x1b[?1002h\x1b[?1006h\x1b[?2004h\x1b[6 q"
+        );
+
+        assert!(stack.pop_state(&mut out).unwrap());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1b[?1002l\x1b[?1006l\x1b[?2004l\x1b[0 q"
+        );
+        assert_eq!(stack.modes(), Modes::default());
+        assert!(!stack.pop_state(&mut Vec::new()).unwrap());
+    }
+}
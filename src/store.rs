@@ -0,0 +1,171 @@
+use std::fmt::Debug;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use crate::history::History;
+
+/// Where the history entries are persisted, e.g. a flat file, a database or a remote service
+///
+/// entries are handed over as chars, in the order they were submitted
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait HistoryStore: Debug {
+    /// Returns all the stored entries, oldest first
+    fn load(&mut self) -> std::io::Result<Vec<Vec<char>>>;
+
+    /// Stores a new entry
+    fn append(&mut self, entry: &[char]) -> std::io::Result<()>;
+
+    /// Returns up to limit stored entries containing query, newest first
+    fn search(&mut self, query: &[char], limit: usize) -> std::io::Result<Vec<Vec<char>>>;
+
+    /// Drops the oldest entries so that at most keep entries remain
+    fn prune(&mut self, keep: usize) -> std::io::Result<()>;
+}
+
+/// A HistoryStore keeping the entries in a flat file, one entry per line with the backslashes
+/// and line feeds of the entries escaped, see [`History::save`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStore {
+    pub path: PathBuf,
+}
+
+impl FileStore {
+    /// Creates a new FileStore, the file is only created on the first append
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl HistoryStore for FileStore {
+    fn load(&mut self) -> std::io::Result<Vec<Vec<char>>> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        std::io::BufReader::new(file)
+            .lines()
+            .map(|line| line.map(|l| unescape(&l)))
+            .collect()
+    }
+
+    fn append(&mut self, entry: &[char]) -> std::io::Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        writeln!(file, "{}", escape(entry))
+    }
+
+    fn search(&mut self, query: &[char], limit: usize) -> std::io::Result<Vec<Vec<char>>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .rev()
+            .filter(|entry| query.is_empty() || entry.windows(query.len()).any(|w| w == query))
+            .take(limit)
+            .collect())
+    }
+
+    fn prune(&mut self, keep: usize) -> std::io::Result<()> {
+        let entries = self.load()?;
+        if entries.len() <= keep {
+            return Ok(());
+        }
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&self.path)?);
+        for entry in &entries[entries.len() - keep..] {
+            writeln!(file, "{}", escape(entry))?;
+        }
+
+        file.flush()
+    }
+}
+
+/// Escapes the backslashes and line feeds of an entry so that it fits on one line
+pub fn escape(entry: &[char]) -> String {
+    entry.iter().fold(String::new(), |mut line, c| {
+        match c {
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            c => line.push(*c),
+        }
+        line
+    })
+}
+
+/// Reverses [`escape`]
+pub fn unescape(line: &str) -> Vec<char> {
+    let mut entry = Vec::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') => entry.push('\n'),
+                Some(c) => entry.push(c),
+                None => entry.push('\\'),
+            },
+            (c, false) => entry.push(c),
+        }
+    }
+
+    entry
+}
+
+impl History {
+    /// Loads the entries of a store into the History, after the entries it already has
+    pub fn load_from(&mut self, store: &mut dyn HistoryStore) -> std::io::Result<()> {
+        store.load()?.into_iter().for_each(|entry| {
+            if !self.values.contains(&entry) {
+                self.values.push(entry);
+            }
+        });
+        self.temp = None;
+        self.cursor = self.values.len();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_store {
+    use super::{escape, unescape, FileStore, HistoryStore};
+    use crate::history::History;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_escape() {
+        let entry = chars("echo a\\\nb");
+        assert_eq!(escape(&entry), "echo a\\\\\\nb");
+        assert_eq!(unescape(&escape(&entry)), entry);
+    }
+
+    #[test]
+    fn test_file_store() {
+        let path = std::env::temp_dir().join("ragout_test_file_store");
+        _ = std::fs::remove_file(&path);
+        let mut store = FileStore::new(&path);
+        assert!(store.load().unwrap().is_empty());
+
+        ["ls", "cargo build", "multi\nline", "cargo test"]
+            .into_iter()
+            .for_each(|e| store.append(&chars(e)).unwrap());
+        assert_eq!(
+            store.search(&chars("cargo"), 5).unwrap(),
+            [chars("cargo test"), chars("cargo build")]
+        );
+
+        store.prune(2).unwrap();
+        let mut h = History::new();
+        h.load_from(&mut store).unwrap();
+        assert_eq!(h.values, [chars("multi\nline"), chars("cargo test")]);
+        assert_eq!(h.cursor, 2);
+        _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,144 @@
+/// A terminal color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    /// one of the 256 colors of the xterm palette
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    // the SGR parameters of the color, base is 30 for foregrounds and 40 for backgrounds
+    fn sgr(&self, base: u8) -> String {
+        match self {
+            Self::Ansi(n) => format!("{};5;{}", base + 8, n),
+            Self::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+            Self::Black => base.to_string(),
+            Self::Red => (base + 1).to_string(),
+            Self::Green => (base + 2).to_string(),
+            Self::Yellow => (base + 3).to_string(),
+            Self::Blue => (base + 4).to_string(),
+            Self::Magenta => (base + 5).to_string(),
+            Self::Cyan => (base + 6).to_string(),
+            Self::White => (base + 7).to_string(),
+        }
+    }
+}
+
+/// The styling of displayed text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    /// Creates a new Style with no attributes
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            bold: false,
+            dim: false,
+            italic: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+
+    /// Sets the foreground color
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Sets the background color
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// Makes the text bold
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Makes the text dim
+    pub const fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    /// Makes the text italic
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Underlines the text
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Swaps the text foreground and background colors
+    pub const fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Returns the SGR escape sequence switching from any style to this one
+    pub fn sgr(&self) -> String {
+        let mut params = vec!["0".to_string()];
+        [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.reverse, "7"),
+        ]
+        .into_iter()
+        .filter(|(on, _)| *on)
+        .for_each(|(_, p)| params.push(p.to_string()));
+        if let Some(fg) = self.fg {
+            params.push(fg.sgr(30));
+        }
+        if let Some(bg) = self.bg {
+            params.push(bg.sgr(40));
+        }
+
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+#[cfg(test)]
+mod test_style {
+    use super::{Color, Style};
+
+    #[test]
+    fn test_sgr() {
+        assert_eq!(Style::new().sgr(), "\x1b[0m");
+        assert_eq!(Style::new().bold().fg(Color::Red).sgr(), "\x1b[0;1;31m");
+        assert_eq!(
+            Style::new()
+                .dim()
+                .fg(Color::Ansi(244))
+                .bg(Color::Rgb(1, 2, 3))
+                .sgr(),
+            "\x1b[0;2;38;5;244;48;2;1;2;3m"
+        );
+    }
+}
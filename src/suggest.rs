@@ -0,0 +1,104 @@
+use crate::history::History;
+use crate::tokens::tokenize;
+
+/// Returns the Levenshtein distance between a and b, i.e. the number of single char insertions,
+/// deletions and substitutions turning a into b
+pub fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Returns the candidate closest to word, as long as it is within a third of the word length
+/// (and at least 1) of edits from it
+///
+/// returns None if word is one of the candidates
+pub fn nearest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let word = word.chars().collect::<Vec<char>>();
+    let max = (word.len() / 3).max(1);
+    let mut best: Option<(usize, &str)> = None;
+    for candidate in candidates {
+        let distance = edit_distance(&word, &candidate.chars().collect::<Vec<char>>());
+        if distance == 0 {
+            return None;
+        }
+        if distance <= max && best.is_none_or(|(d, _)| distance < d) {
+            best = Some((distance, candidate));
+        }
+    }
+
+    best.map(|(_, candidate)| candidate)
+}
+
+/// Returns the correction of a line rejected by the application:
+/// the line with its first word replaced by the nearest command,
+/// or else the nearest history entry
+pub fn did_you_mean(line: &str, commands: &[String], h: &History) -> Option<String> {
+    let chars = line.chars().collect::<Vec<char>>();
+    if let Some(first) = tokenize(&chars).first() {
+        if let Some(command) = nearest(&first.text, commands.iter().map(String::as_str)) {
+            return Some(format!(
+                "{}{}{}",
+                &line[..first.bytes.start],
+                command,
+                &line[first.bytes.end..]
+            ));
+        }
+    }
+
+    let entries = h
+        .values
+        .iter()
+        .map(|e| e.iter().collect::<String>())
+        .collect::<Vec<String>>();
+
+    nearest(line, entries.iter().map(String::as_str)).map(str::to_string)
+}
+
+#[cfg(test)]
+mod test_suggest {
+    use super::{did_you_mean, edit_distance, nearest};
+    use crate::history::History;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("ls")), 2);
+        assert_eq!(
+            nearest("stauts", ["status", "stash", "log"]),
+            Some("status")
+        );
+        assert_eq!(nearest("status", ["status", "stash"]), None);
+        assert_eq!(nearest("xyz", ["status", "stash"]), None);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let mut h = History::new();
+        h.push(chars("git status"));
+        let commands = vec!["commit".to_string(), "checkout".into()];
+
+        assert_eq!(
+            did_you_mean("comit -m 'x'", &commands, &h).as_deref(),
+            Some("commit -m 'x'")
+        );
+        assert_eq!(
+            did_you_mean("git stauts", &commands, &h).as_deref(),
+            Some("git status")
+        );
+        assert_eq!(did_you_mean("ls", &commands, &h), None);
+    }
+}
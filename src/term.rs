@@ -0,0 +1,503 @@
+use std::fmt::Debug;
+#[cfg(not(unix))]
+use std::io::ErrorKind;
+use std::io::{Error, Result};
+#[cfg(unix)]
+use std::sync::atomic::{AtomicI32, Ordering};
+#[cfg(unix)]
+use std::sync::Mutex;
+
+use crate::winsize::window_size;
+
+/// The platform specific terminal operations the editor relies on.
+///
+/// Porting the crate to a new platform (or to a custom console) only requires implementing this
+/// trait, then handing it to [`crate::init_with`] or [`crate::Editor::with_backend`]
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait TermBackend: Debug {
+    /// Puts the terminal in raw mode
+    fn enable_raw_mode(&mut self) -> Result<()>;
+
+    /// Puts the terminal back in cooked mode
+    fn disable_raw_mode(&mut self) -> Result<()>;
+
+    /// Returns the terminal size as (columns, rows)
+    fn size(&self) -> Result<(u16, u16)>;
+
+    /// Starts watching for terminal resizes
+    fn watch_resize(&mut self) -> Result<()>;
+
+    /// Returns true if the terminal was resized since the last call
+    fn take_resized(&mut self) -> bool;
+
+    /// Starts watching for the signals asking the process to terminate (SIGTERM and SIGHUP),
+    /// which a backend may handle on its own: [`Native`] restores the terminal and lets the
+    /// signal kill the process from a watcher thread, wherever the program is at
+    fn watch_termination(&mut self) -> Result<()>;
+
+    /// Returns the termination signal received since the last call, if any, for the Editor to
+    /// restore the terminal and die of it; always None for a backend handling them on its own
+    fn take_termination(&mut self) -> Option<i32>;
+}
+
+/// The backend of the platforms supported out of the box: Linux, macOS and the BSDs
+/// (anything with termios and SIGWINCH) as well as windows
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Native;
+
+// the write end of the pipe the termination handler hands the signals over to the watcher
+// thread through, -1 until the thread is started
+#[cfg(unix)]
+static TERMINATION_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+#[cfg(unix)]
+extern "C" fn on_termination(signal: libc::c_int) {
+    let byte = signal as u8;
+    // SAFETY: write is async signal safe, byte outlives the call
+    unsafe {
+        libc::write(
+            TERMINATION_PIPE.load(Ordering::SeqCst),
+            (&byte as *const u8).cast(),
+            1,
+        )
+    };
+}
+
+// starts the thread waiting for the signals written to the termination pipe, once
+#[cfg(unix)]
+fn start_termination_watcher() -> Result<()> {
+    if TERMINATION_PIPE.load(Ordering::SeqCst) >= 0 {
+        return Ok(());
+    }
+    let mut fds = [0; 2];
+    // SAFETY: fds has room for the 2 fds of the pipe
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(Error::last_os_error());
+    }
+    let [read_end, write_end] = fds;
+    std::thread::Builder::new()
+        .name("ragout termination".to_string())
+        .spawn(move || loop {
+            let mut byte = 0u8;
+            // SAFETY: byte has room for the byte read
+            match unsafe { libc::read(read_end, (&mut byte as *mut u8).cast(), 1) } {
+                1 => die_of(byte as libc::c_int),
+                -1 if Error::last_os_error().kind() == std::io::ErrorKind::Interrupted => (),
+                _ => return,
+            }
+        })?;
+    TERMINATION_PIPE.store(write_end, Ordering::SeqCst);
+
+    Ok(())
+}
+
+// restores the terminal then lets the signal kill the process as it would have without the
+// handler, the history being autosaved already
+#[cfg(unix)]
+fn die_of(signal: libc::c_int) -> ! {
+    restore_dying(cooked_termios().ok().as_ref());
+    // SAFETY: signal and raise take any signal number
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+
+    std::process::exit(128 + signal)
+}
+
+// what a process dying of a signal writes to the terminal: no bracketed paste nor mouse
+// reporting, a visible cursor in the shape the user configured
+#[cfg(unix)]
+const DYING_ESCAPES: &[u8] = b"\x1b[?2004l\x1b[?1002l\x1b[?1006l\x1b[?25h\x1b[0 q";
+
+// puts the terminal back for a process dying of a signal: leaves the alternate screen, turns
+// the modes off and puts back the cooked termios; only calls async signal safe functions,
+// signal handlers call it too
+#[cfg(unix)]
+pub(crate) fn restore_dying(cooked: Option<&libc::termios>) {
+    // SAFETY: write, isatty, open and tcsetattr are async signal safe, the buffers and termios
+    // outlive the calls
+    unsafe {
+        if crate::state::ALT_SCREEN.load(Ordering::SeqCst) {
+            let leave = b"\x1b[?1049l";
+            libc::write(libc::STDOUT_FILENO, leave.as_ptr().cast(), leave.len());
+        }
+        libc::write(
+            libc::STDOUT_FILENO,
+            DYING_ESCAPES.as_ptr().cast(),
+            DYING_ESCAPES.len(),
+        );
+        if let Some(termios) = cooked {
+            let fd = match libc::isatty(libc::STDIN_FILENO) {
+                1 => libc::STDIN_FILENO,
+                _ => libc::open(c"/dev/tty".as_ptr(), libc::O_RDWR),
+            };
+            libc::tcsetattr(fd, libc::TCSANOW, termios);
+        }
+    }
+}
+
+// installs an async signal safe handler for the given signal
+#[cfg(unix)]
+pub(crate) fn handle_signal(
+    signal: libc::c_int,
+    handler: extern "C" fn(libc::c_int),
+    flags: libc::c_int,
+) -> Result<()> {
+    // SAFETY: the handlers only load atomics and call async signal safe functions: write,
+    // isatty, open, tcsetattr, signal and raise
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as *const () as libc::sighandler_t;
+        action.sa_flags = flags;
+        libc::sigemptyset(&mut action.sa_mask);
+        match libc::sigaction(signal, &action, std::ptr::null_mut()) {
+            0 => Ok(()),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+}
+
+// the termios found by the first enable_raw_mode, put back by disable_raw_mode
+#[cfg(unix)]
+static ORIGINAL: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+/// Puts the terminal in the full raw mode of [`RawConfig::default`] through termios, the
+/// termios found get stored for [`disable_raw_mode`] to put back
+///
+/// the terminal is stdin, or else `/dev/tty` when stdin is redirected; on windows this is
+/// the console input mode without line input, echo nor Ctrl-C processing (`SetConsoleMode`)
+pub fn enable_raw_mode() -> Result<()> {
+    #[cfg(unix)]
+    return with_tty(|fd| {
+        let mut termios = get_termios(fd)?;
+        let mut original = ORIGINAL.lock().map_err(|_| Error::other("poisoned lock"))?;
+        // enabling twice must not lose the cooked termios
+        original.get_or_insert(termios);
+        RawConfig::default().apply(&mut termios);
+
+        set_termios(fd, &termios)
+    });
+    #[cfg(windows)]
+    return crate::windows::enable_raw_input();
+    #[cfg(not(any(unix, windows)))]
+    crossterm::terminal::enable_raw_mode()
+}
+
+/// Puts back the termios stored by [`enable_raw_mode`], does nothing if the raw mode was not
+/// enabled
+pub fn disable_raw_mode() -> Result<()> {
+    #[cfg(unix)]
+    {
+        let mut original = ORIGINAL.lock().map_err(|_| Error::other("poisoned lock"))?;
+        let Some(termios) = original.take() else {
+            return Ok(());
+        };
+
+        with_tty(|fd| set_termios(fd, &termios))
+    }
+    #[cfg(windows)]
+    return crate::windows::restore_input_mode();
+    #[cfg(not(any(unix, windows)))]
+    crossterm::terminal::disable_raw_mode()
+}
+
+// the termios of the terminal in cooked mode: the ones stored by enable_raw_mode, or else the
+// current ones
+#[cfg(unix)]
+pub(crate) fn cooked_termios() -> Result<libc::termios> {
+    let stored = *ORIGINAL.lock().map_err(|_| Error::other("poisoned lock"))?;
+    match stored {
+        Some(termios) => Ok(termios),
+        None => with_tty(get_termios),
+    }
+}
+
+// runs f with the fd of the terminal: stdin, or else /dev/tty as crossterm does
+#[cfg(unix)]
+fn with_tty<T>(f: impl FnOnce(libc::c_int) -> Result<T>) -> Result<T> {
+    use std::io::IsTerminal;
+    use std::os::fd::AsRawFd;
+    if std::io::stdin().is_terminal() {
+        return f(libc::STDIN_FILENO);
+    }
+    // the file has to stay open for as long as its fd is used
+    let tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")?;
+
+    f(tty.as_raw_fd())
+}
+
+#[cfg(unix)]
+fn get_termios(fd: libc::c_int) -> Result<libc::termios> {
+    // SAFETY: tcgetattr fills the zeroed termios
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        match libc::tcgetattr(fd, &mut termios) {
+            0 => Ok(termios),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn set_termios(fd: libc::c_int, termios: &libc::termios) -> Result<()> {
+    // SAFETY: termios was filled by tcgetattr
+    match unsafe { libc::tcsetattr(fd, libc::TCSADRAIN, termios) } {
+        0 => Ok(()),
+        _ => Err(Error::last_os_error()),
+    }
+}
+
+impl TermBackend for Native {
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        disable_raw_mode()
+    }
+
+    fn size(&self) -> Result<(u16, u16)> {
+        window_size().map(|size| (size.cols, size.rows))
+    }
+
+    fn watch_resize(&mut self) -> Result<()> {
+        crate::winsize::watch_resize()
+    }
+
+    fn take_resized(&mut self) -> bool {
+        crate::winsize::take_resized()
+    }
+
+    #[cfg(unix)]
+    fn watch_termination(&mut self) -> Result<()> {
+        start_termination_watcher()?;
+        handle_signal(libc::SIGTERM, on_termination, 0)?;
+        handle_signal(libc::SIGHUP, on_termination, 0)
+    }
+
+    #[cfg(not(unix))]
+    fn watch_termination(&mut self) -> Result<()> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "termination signals are only watched on unix",
+        ))
+    }
+
+    // the watcher thread handles them
+    fn take_termination(&mut self) -> Option<i32> {
+        None
+    }
+}
+
+/// How much terminal processing the raw mode of a [`Termios`] backend disables
+///
+/// the default is the full raw mode that crossterm sets up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawConfig {
+    /// keeps the signal chars, Ctrl-C then raises SIGINT instead of being read as a key
+    pub isig: bool,
+    /// keeps the output processing, e.g. the `\n` to `\r\n` translation
+    pub opost: bool,
+    /// only turns off the line buffering and the echo (cbreak mode) instead of all the input
+    /// processing
+    pub cbreak: bool,
+    /// the minimum number of bytes a read waits for
+    pub vmin: u8,
+    /// how long a read waits for them, in tenths of a second, 0 waits forever
+    pub vtime: u8,
+}
+
+impl Default for RawConfig {
+    fn default() -> Self {
+        Self {
+            isig: false,
+            opost: false,
+            cbreak: false,
+            vmin: 1,
+            vtime: 0,
+        }
+    }
+}
+
+impl RawConfig {
+    /// Applies the configuration to the termios of a terminal in cooked mode
+    #[cfg(unix)]
+    pub fn apply(&self, termios: &mut libc::termios) {
+        match self.cbreak {
+            true => termios.c_lflag &= !(libc::ICANON | libc::ECHO),
+            // what cfmakeraw does
+            false => {
+                termios.c_iflag &= !(libc::IGNBRK
+                    | libc::BRKINT
+                    | libc::PARMRK
+                    | libc::ISTRIP
+                    | libc::INLCR
+                    | libc::IGNCR
+                    | libc::ICRNL
+                    | libc::IXON);
+                termios.c_oflag &= !libc::OPOST;
+                termios.c_lflag &=
+                    !(libc::ECHO | libc::ECHONL | libc::ICANON | libc::ISIG | libc::IEXTEN);
+                termios.c_cflag &= !(libc::CSIZE | libc::PARENB);
+                termios.c_cflag |= libc::CS8;
+            }
+        }
+        match self.isig {
+            true => termios.c_lflag |= libc::ISIG,
+            false => termios.c_lflag &= !libc::ISIG,
+        }
+        match self.opost {
+            true => termios.c_oflag |= libc::OPOST,
+            false => termios.c_oflag &= !libc::OPOST,
+        }
+        termios.c_cc[libc::VMIN] = self.vmin;
+        termios.c_cc[libc::VTIME] = self.vtime;
+    }
+}
+
+/// A backend entering a raw mode configured by a [`RawConfig`], through termios,
+/// see [`crate::init_with_config`]
+#[cfg(unix)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Termios {
+    pub config: RawConfig,
+    // the termios found by enable_raw_mode
+    saved: Option<libc::termios>,
+}
+
+#[cfg(unix)]
+impl Termios {
+    /// Creates a new Termios backend
+    pub fn new(config: RawConfig) -> Self {
+        Self {
+            config,
+            saved: None,
+        }
+    }
+}
+
+#[cfg(unix)]
+impl TermBackend for Termios {
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        let Some(mut termios) = crate::state::termios()? else {
+            return Err(Error::new(
+                std::io::ErrorKind::Unsupported,
+                "stdin is not a terminal",
+            ));
+        };
+        self.saved.get_or_insert(termios);
+        self.config.apply(&mut termios);
+
+        set_termios(libc::STDIN_FILENO, &termios)
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        let Some(termios) = self.saved.take() else {
+            return Ok(());
+        };
+
+        set_termios(libc::STDIN_FILENO, &termios)
+    }
+
+    fn size(&self) -> Result<(u16, u16)> {
+        Native.size()
+    }
+
+    fn watch_resize(&mut self) -> Result<()> {
+        Native.watch_resize()
+    }
+
+    fn take_resized(&mut self) -> bool {
+        Native.take_resized()
+    }
+
+    fn watch_termination(&mut self) -> Result<()> {
+        Native.watch_termination()
+    }
+
+    fn take_termination(&mut self) -> Option<i32> {
+        Native.take_termination()
+    }
+}
+
+/// Returns the backend of the current platform
+pub fn default_backend() -> Box<dyn TermBackend> {
+    Box::new(Native)
+}
+
+#[cfg(all(test, unix))]
+mod test_term {
+    use super::{disable_raw_mode, Native, RawConfig, TermBackend};
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    #[test]
+    fn test_die_of_termination() {
+        // the watching process is this test run again on its own
+        if std::env::var_os("RAGOUT_TEST_TERMINATION").is_some() {
+            Native.watch_termination().unwrap();
+            // SAFETY: raise takes any signal number
+            unsafe { libc::raise(libc::SIGTERM) };
+            std::thread::sleep(std::time::Duration::from_secs(5));
+            return;
+        }
+
+        let out = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "term::test_term::test_die_of_termination",
+                "--exact",
+                "--nocapture",
+            ])
+            .env("RAGOUT_TEST_TERMINATION", "1")
+            .output()
+            .unwrap();
+        // the process died of the signal once the terminal got restored
+        assert_eq!(out.status.signal(), Some(libc::SIGTERM));
+        assert!(out
+            .stdout
+            .windows(super::DYING_ESCAPES.len())
+            .any(|w| w == super::DYING_ESCAPES));
+    }
+
+    #[test]
+    fn test_disable_without_enable() {
+        // nothing was stored, the terminal is left alone
+        assert!(disable_raw_mode().is_ok());
+    }
+
+    #[test]
+    fn test_raw_config() {
+        // SAFETY: termios is plain data
+        let mut cooked: libc::termios = unsafe { std::mem::zeroed() };
+        cooked.c_lflag = libc::ICANON | libc::ECHO | libc::ISIG;
+        cooked.c_iflag = libc::ICRNL;
+        cooked.c_oflag = libc::OPOST;
+
+        let mut raw = cooked;
+        RawConfig::default().apply(&mut raw);
+        assert_eq!(raw.c_lflag & (libc::ICANON | libc::ECHO | libc::ISIG), 0);
+        assert_eq!(raw.c_iflag & libc::ICRNL, 0);
+        assert_eq!(raw.c_oflag & libc::OPOST, 0);
+        assert_eq!(raw.c_cc[libc::VMIN], 1);
+
+        let mut cbreak = cooked;
+        RawConfig {
+            isig: true,
+            opost: true,
+            cbreak: true,
+            vmin: 0,
+            vtime: 5,
+        }
+        .apply(&mut cbreak);
+        assert_eq!(cbreak.c_lflag, libc::ISIG);
+        assert_eq!(cbreak.c_iflag, libc::ICRNL);
+        assert_eq!(cbreak.c_oflag, libc::OPOST);
+        assert_eq!(cbreak.c_cc[libc::VTIME], 5);
+    }
+}
@@ -0,0 +1,609 @@
+use std::cell::{Cell, UnsafeCell};
+use std::ops::{Bound, Deref, DerefMut, Range, RangeBounds};
+
+use crate::undo::Edit;
+
+/// The chars of the line being edited, see [`crate::Input::values`]
+///
+/// a gap buffer: the unused cells are kept at the place of the last edit, so that the chars
+/// typed or deleted there do not move the rest of the line; reading the chars as a slice (it
+/// derefs to `[char]`) moves the gap to the end first, the edits that follow a read then move
+/// the chars after their place once
+pub struct Text {
+    // the chars with the gap at buf[gap.0..gap.1]
+    buf: UnsafeCell<Vec<char>>,
+    gap: Cell<(usize, usize)>,
+    // the changes made since Text::track, merged into one
+    tracked: Option<Change>,
+}
+
+// the changes of a tracked Text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Change {
+    // nothing changed yet
+    Nothing,
+    // the chars removed from start, now replaced by the len chars there
+    Span {
+        start: usize,
+        removed: Vec<char>,
+        len: usize,
+    },
+    // the chars got changed through a mutable slice, from these ones
+    Whole(Vec<char>),
+}
+
+impl Text {
+    /// Creates a new empty Text
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of chars
+    pub fn len(&self) -> usize {
+        let (start, end) = self.gap.get();
+        self.buf().len() - (end - start)
+    }
+
+    /// Returns true if there is no char
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the char at idx, without moving the gap
+    pub fn char_at(&self, idx: usize) -> Option<char> {
+        let (start, end) = self.gap.get();
+        match idx < start {
+            true => self.buf().get(idx).copied(),
+            false => self.buf().get(idx + end - start).copied(),
+        }
+    }
+
+    /// Returns the chars before end, moving the gap after them only if it is among them
+    pub fn head(&mut self, end: usize) -> &[char] {
+        let end = end.min(self.len());
+        let (start, gap_end) = self.gap.get();
+        if start < end && start != gap_end {
+            self.move_gap(end);
+        }
+
+        &self.buf.get_mut()[..end]
+    }
+
+    /// Inserts c at idx
+    ///
+    /// # Panics
+    /// Panics if idx is past the end, like [`Vec::insert`]
+    pub fn insert(&mut self, idx: usize, c: char) {
+        assert!(
+            idx <= self.len(),
+            "insertion index {} is out of bounds",
+            idx
+        );
+        self.replace(idx..idx, &[c]);
+    }
+
+    /// Removes and returns the char at idx
+    ///
+    /// # Panics
+    /// Panics if idx is out of bounds, like [`Vec::remove`]
+    pub fn remove(&mut self, idx: usize) -> char {
+        assert!(idx < self.len(), "removal index {} is out of bounds", idx);
+        self.replace(idx..idx + 1, &[])[0]
+    }
+
+    /// Appends c
+    pub fn push(&mut self, c: char) {
+        self.replace(self.len()..self.len(), &[c]);
+    }
+
+    /// Removes and returns the last char
+    pub fn pop(&mut self) -> Option<char> {
+        let len = self.len();
+        (len > 0).then(|| self.replace(len - 1..len, &[])[0])
+    }
+
+    /// Removes every char
+    pub fn clear(&mut self) {
+        self.replace(0..self.len(), &[]);
+    }
+
+    /// Keeps the first len chars
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.replace(len..self.len(), &[]);
+        }
+    }
+
+    /// Replaces every char with the given ones
+    pub fn set(&mut self, chars: impl IntoIterator<Item = char>) {
+        let chars = chars.into_iter().collect::<Vec<char>>();
+        self.replace(0..self.len(), &chars);
+    }
+
+    /// Removes the chars of the range and returns them
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds, like [`Vec::drain`]
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> std::vec::IntoIter<char> {
+        let range = self.range(range);
+        self.replace(range, &[]).into_iter()
+    }
+
+    /// Replaces the chars of the range with the given ones
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds, like [`Vec::splice`]
+    pub fn splice(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        chars: impl IntoIterator<Item = char>,
+    ) {
+        let range = self.range(range);
+        let chars = chars.into_iter().collect::<Vec<char>>();
+        self.replace(range, &chars);
+    }
+
+    /// Returns the chars as a Vec, the gap closed
+    pub fn into_vec(mut self) -> Vec<char> {
+        let len = self.len();
+        self.move_gap(len);
+        let mut buf = std::mem::take(self.buf.get_mut());
+        buf.truncate(len);
+
+        buf
+    }
+
+    // replaces the chars of the range with the inserted ones at the gap, returns the removed
+    // chars
+    fn replace(&mut self, range: Range<usize>, inserted: &[char]) -> Vec<char> {
+        let len = self.len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {:?} is out of bounds of {} chars",
+            range,
+            len
+        );
+        self.note(range.clone(), inserted.len());
+        self.move_gap(range.end);
+        let (_, end) = self.gap.get();
+        let removed = self.buf.get_mut()[range.clone()].to_vec();
+        // the removed chars join the gap, the inserted ones fill it from its start
+        self.gap.set((range.start, end));
+        if end - range.start < inserted.len() {
+            self.grow(inserted.len());
+        }
+        let (start, end) = self.gap.get();
+        self.buf.get_mut()[start..start + inserted.len()].copy_from_slice(inserted);
+        self.gap.set((start + inserted.len(), end));
+
+        removed
+    }
+
+    // moves the gap to idx, the chars in between go to the other side of it
+    fn move_gap(&mut self, idx: usize) {
+        let (start, end) = self.gap.get();
+        let buf = self.buf.get_mut();
+        match idx < start {
+            true => buf.copy_within(idx..start, idx + end - start),
+            false => buf.copy_within(end..end + idx - start, start),
+        }
+        self.gap.set((idx, idx + end - start));
+    }
+
+    // makes room for at least room chars in the gap, the buffer at least doubles
+    fn grow(&mut self, room: usize) {
+        let (start, end) = self.gap.get();
+        let buf = self.buf.get_mut();
+        let more = room.max(buf.len()).max(16);
+        buf.splice(end..end, std::iter::repeat_n('\0', more));
+        self.gap.set((start, end + more));
+    }
+
+    // the range of chars of bounds
+    fn range(&self, bounds: impl RangeBounds<usize>) -> Range<usize> {
+        let start = match bounds.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match bounds.end_bound() {
+            Bound::Included(end) => end + 1,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self.len(),
+        };
+
+        start..end
+    }
+
+    fn buf(&self) -> &Vec<char> {
+        // SAFETY: the buffer only gets mutated through &mut self, or by close_gap when no
+        // reference to it is alive
+        unsafe { &*self.buf.get() }
+    }
+
+    // moves the gap to the end for the chars to be contiguous
+    fn close_gap(&self) {
+        let (start, end) = self.gap.get();
+        if start == end || end == self.buf().len() {
+            return;
+        }
+        // SAFETY: a gap in the middle only gets opened through &mut self and every reference
+        // to the chars gets handed out by deref, which closes it first; a gap in the middle thus
+        // means that no reference to the buffer is alive, the Text being !Sync
+        let buf = unsafe { &mut *self.buf.get() };
+        let len = buf.len() - (end - start);
+        buf.copy_within(end.., start);
+        self.gap.set((len, buf.len()));
+    }
+
+    /// Starts merging the changes (made through any method) into a single [`Edit`], see
+    /// [`Text::untrack`]; returns the changes tracked so far, for them to be tracked again
+    /// once untracked
+    pub(crate) fn track(&mut self) -> Option<Change> {
+        self.tracked.replace(Change::Nothing)
+    }
+
+    /// Stops the tracking started by [`Text::track`] and returns the Edit the changes made,
+    /// None if they left the chars as they were; outer gets tracked again, the changes merged
+    /// into it
+    pub(crate) fn untrack(
+        &mut self,
+        outer: Option<Change>,
+        cursor_before: usize,
+        cursor_after: usize,
+    ) -> Option<Edit> {
+        let inner = std::mem::replace(&mut self.tracked, outer)?;
+        let edit = match &inner {
+            Change::Nothing => None,
+            Change::Span {
+                start,
+                removed,
+                len,
+            } => {
+                let inserted = (*start..start + len)
+                    .filter_map(|idx| self.char_at(idx))
+                    .collect();
+                Edit::new(
+                    *start,
+                    removed.clone(),
+                    inserted,
+                    cursor_before,
+                    cursor_after,
+                )
+            }
+            Change::Whole(before) => Edit::diff(before, self, cursor_before, cursor_after),
+        };
+        if let Some(outer) = self.tracked.take() {
+            self.tracked = Some(self.merged(outer, inner));
+        }
+
+        edit
+    }
+
+    // the changes of outer followed by the inner ones
+    fn merged(&self, outer: Change, inner: Change) -> Change {
+        match (outer, inner) {
+            (outer, Change::Nothing) => outer,
+            (Change::Nothing, inner) => inner,
+            (Change::Whole(before), _) => Change::Whole(before),
+            (
+                Change::Span {
+                    start,
+                    mut removed,
+                    len,
+                },
+                Change::Whole(mid),
+            ) => {
+                removed.splice(0..0, mid[..start].iter().copied());
+                removed.extend_from_slice(&mid[start + len..]);
+                Change::Whole(removed)
+            }
+            // the chars in the union of both spans were there before, but for the part the
+            // outer span replaced; the ones outside of the inner span are read where it left
+            // them
+            (
+                Change::Span {
+                    start,
+                    removed,
+                    len,
+                },
+                Change::Span {
+                    start: inner,
+                    removed: replaced,
+                    len: inserted,
+                },
+            ) => {
+                let mid = |idx: usize| match idx.checked_sub(inner) {
+                    None => self.char_at(idx),
+                    Some(off) if off < replaced.len() => Some(replaced[off]),
+                    Some(_) => self.char_at(idx - replaced.len() + inserted),
+                };
+                let union = start.min(inner)..(start + len).max(inner + replaced.len());
+                let mut before = (union.start..start).filter_map(mid).collect::<Vec<char>>();
+                before.extend(removed);
+                before.extend((start + len..union.end).filter_map(mid));
+                Change::Span {
+                    start: union.start,
+                    removed: before,
+                    len: union.len() - replaced.len() + inserted,
+                }
+            }
+        }
+    }
+
+    // merges the replacement of the chars of range with len chars into the tracked changes,
+    // before it is made
+    fn note(&mut self, range: Range<usize>, len: usize) {
+        let chars = |text: &Self, range: Range<usize>| {
+            range
+                .filter_map(|idx| text.char_at(idx))
+                .collect::<Vec<char>>()
+        };
+        let change = match self.tracked.take() {
+            None => return,
+            Some(Change::Nothing) => Change::Span {
+                start: range.start,
+                removed: chars(self, range.clone()),
+                len,
+            },
+            // the chars now in the union of both spans were there before, but for the part
+            // the tracked span replaced
+            Some(Change::Span {
+                start,
+                removed,
+                len: tracked,
+            }) => {
+                let union = range.start.min(start)..range.end.max(start + tracked);
+                let mut before = chars(self, union.start..start);
+                before.extend(removed);
+                before.extend(chars(self, start + tracked..union.end));
+                Change::Span {
+                    start: union.start,
+                    removed: before,
+                    len: union.len() - range.len() + len,
+                }
+            }
+            Some(whole) => whole,
+        };
+        self.tracked = Some(change);
+    }
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Self::from(Vec::new())
+    }
+}
+
+impl From<Vec<char>> for Text {
+    fn from(chars: Vec<char>) -> Self {
+        let len = chars.len();
+        Self {
+            buf: UnsafeCell::new(chars),
+            gap: Cell::new((len, len)),
+            tracked: None,
+        }
+    }
+}
+
+impl From<&[char]> for Text {
+    fn from(chars: &[char]) -> Self {
+        Self::from(chars.to_vec())
+    }
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Self {
+        text.chars().collect()
+    }
+}
+
+impl FromIterator<char> for Text {
+    fn from_iter<I: IntoIterator<Item = char>>(chars: I) -> Self {
+        Self::from(chars.into_iter().collect::<Vec<char>>())
+    }
+}
+
+impl Extend<char> for Text {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, chars: I) {
+        let chars = chars.into_iter().collect::<Vec<char>>();
+        self.replace(self.len()..self.len(), &chars);
+    }
+}
+
+impl<'a> Extend<&'a char> for Text {
+    fn extend<I: IntoIterator<Item = &'a char>>(&mut self, chars: I) {
+        self.extend(chars.into_iter().copied());
+    }
+}
+
+impl<'a> IntoIterator for &'a Text {
+    type Item = &'a char;
+    type IntoIter = std::slice::Iter<'a, char>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Deref for Text {
+    type Target = [char];
+
+    fn deref(&self) -> &[char] {
+        self.close_gap();
+        let len = self.len();
+
+        &self.buf()[..len]
+    }
+}
+
+impl DerefMut for Text {
+    /// Returns the chars as a mutable slice, a tracked Text then compares the whole of them
+    /// once untracked
+    fn deref_mut(&mut self) -> &mut [char] {
+        let len = self.len();
+        self.move_gap(len);
+        if let Some(Change::Nothing | Change::Span { .. }) = &self.tracked {
+            let now = self.buf.get_mut()[..len].to_vec();
+            let before = match self.tracked.take() {
+                Some(Change::Span {
+                    start,
+                    removed,
+                    len: tracked,
+                }) => {
+                    let mut before = now[..start].to_vec();
+                    before.extend(removed);
+                    before.extend_from_slice(&now[start + tracked..]);
+                    before
+                }
+                _ => now,
+            };
+            self.tracked = Some(Change::Whole(before));
+        }
+
+        &mut self.buf.get_mut()[..len]
+    }
+}
+
+impl Clone for Text {
+    fn clone(&self) -> Self {
+        Self {
+            tracked: self.tracked.clone(),
+            ..Self::from(self.to_vec())
+        }
+    }
+}
+
+impl std::fmt::Debug for Text {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for Text {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Text {}
+
+impl PartialEq<[char]> for Text {
+    fn eq(&self, other: &[char]) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<&[char]> for Text {
+    fn eq(&self, other: &&[char]) -> bool {
+        **self == **other
+    }
+}
+
+impl<const N: usize> PartialEq<[char; N]> for Text {
+    fn eq(&self, other: &[char; N]) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<Vec<char>> for Text {
+    fn eq(&self, other: &Vec<char>) -> bool {
+        **self == **other
+    }
+}
+
+impl PartialEq<Text> for Vec<char> {
+    fn eq(&self, other: &Text) -> bool {
+        **self == **other
+    }
+}
+
+impl PartialEq<Text> for [char] {
+    fn eq(&self, other: &Text) -> bool {
+        *self == **other
+    }
+}
+
+#[cfg(test)]
+mod test_text {
+    use super::Text;
+
+    #[test]
+    fn test_gap_edits() {
+        let mut text = Text::from("pichu");
+        text.insert(2, 'k');
+        text.insert(3, 'a');
+        // the chars before the gap are read without moving it
+        assert_eq!(text.head(4), ['p', 'i', 'k', 'a']);
+        assert_eq!((text.len(), text.char_at(4)), (7, Some('c')));
+        assert_eq!(text.remove(3), 'a');
+        (0..40).for_each(|_| text.insert(3, '-'));
+        assert_eq!(
+            text.iter().collect::<String>(),
+            format!("pik{}chu", "-".repeat(40))
+        );
+
+        text.splice(1..43, "ea".chars());
+        assert_eq!(text, ['p', 'e', 'a', 'c', 'h', 'u']);
+        assert_eq!(text.drain(3..).collect::<String>(), "chu");
+        text[0] = 'q';
+        text.push('!');
+        assert_eq!(text.pop(), Some('!'));
+        assert_eq!(text.clone().into_vec(), ['q', 'e', 'a']);
+        text.clear();
+        assert!(text.is_empty() && text.pop().is_none());
+    }
+
+    #[test]
+    fn test_tracked_edits() {
+        let mut text = Text::from("cargo test");
+        let outer = text.track();
+        "build "
+            .chars()
+            .enumerate()
+            .for_each(|(idx, c)| text.insert(6 + idx, c));
+        text.remove(6);
+        text.insert(6, 'B');
+        let edit = text.untrack(outer, 6, 12).unwrap();
+        assert_eq!((edit.start, edit.removed.len()), (6, 0));
+        assert_eq!(edit.inserted.iter().collect::<String>(), "Build ");
+
+        // the edits far apart make a single edit covering both
+        let outer = text.track();
+        text.remove(0);
+        text.push('s');
+        text.insert(0, 'c');
+        let edit = text.untrack(outer, 0, 0).unwrap();
+        assert_eq!(edit.start, 16);
+        assert_eq!((edit.removed, edit.inserted), (vec![], vec!['s']));
+
+        let outer = text.track();
+        text[0] = 'C';
+        text.push('!');
+        let edit = text.untrack(outer, 0, 0).unwrap();
+        assert_eq!(edit.removed.iter().collect::<String>(), "cargo Build tests");
+        assert_eq!(
+            edit.inserted.iter().collect::<String>(),
+            "Cargo Build tests!"
+        );
+
+        // the changes undone before untracking make no edit
+        let outer = text.track();
+        text.insert(3, 'x');
+        text.remove(3);
+        assert_eq!(text.untrack(outer, 0, 0), None);
+
+        // the changes tracked within another tracking belong to both
+        let mut text = Text::from("git log");
+        let outer = text.track();
+        text.insert(0, '#');
+        let inner = text.track();
+        text.splice(5..8, "push".chars());
+        let edit = text.untrack(inner, 0, 0).unwrap();
+        assert_eq!((edit.start, edit.inserted.len()), (5, 4));
+        text.push('!');
+        let edit = text.untrack(outer, 0, 0).unwrap();
+        assert_eq!(edit.removed.iter().collect::<String>(), "git log");
+        assert_eq!(edit.inserted.iter().collect::<String>(), "#git push!");
+    }
+}
@@ -0,0 +1,115 @@
+use std::ops::Range;
+
+use crate::input::Input;
+
+/// A shell like token of an input line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// the token value, with its quotes and escapes resolved
+    pub text: String,
+    /// the span of the raw token in the line chars
+    pub chars: Range<usize>,
+    /// the span of the raw token in the utf-8 bytes of the line
+    pub bytes: Range<usize>,
+    /// the quote char still open at the end of the line, if the token is an unterminated quote
+    pub open_quote: Option<char>,
+}
+
+/// Splits a line into shell like tokens separated by unquoted whitespace.
+///
+/// Single quotes keep everything literal, double quotes only allow escaping `"`, `\` and `$`,
+/// anywhere else a backslash escapes the char that follows it
+pub fn tokenize(line: &[char]) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut token: Option<Token> = None;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut byte = 0;
+
+    for (idx, c) in line.iter().enumerate() {
+        let len = c.len_utf8();
+        if token.is_none() && !c.is_whitespace() {
+            token = Some(Token {
+                text: String::new(),
+                chars: idx..idx,
+                bytes: byte..byte,
+                open_quote: None,
+            });
+        }
+
+        if let Some(t) = token.as_mut() {
+            match (quote, escaped, *c) {
+                (_, true, c) => {
+                    // inside double quotes a backslash only escapes a few chars
+                    if quote == Some('"') && !['"', '\\', '$'].contains(&c) {
+                        t.text.push('\\');
+                    }
+                    t.text.push(c);
+                    escaped = false;
+                }
+                (Some('\''), _, '\'') | (Some('"'), _, '"') => quote = None,
+                (Some('\''), _, c) => t.text.push(c),
+                (_, _, '\\') => escaped = true,
+                (None, _, '\'' | '"') => quote = Some(*c),
+                (None, _, c) if c.is_whitespace() => {
+                    tokens.push(token.take().unwrap());
+                    byte += len;
+                    continue;
+                }
+                (_, _, c) => t.text.push(c),
+            }
+            t.chars.end = idx + 1;
+            t.bytes.end = byte + len;
+        }
+        byte += len;
+    }
+
+    if let Some(mut t) = token {
+        t.open_quote = quote;
+        if escaped {
+            t.text.push('\\');
+        }
+        tokens.push(t);
+    }
+
+    tokens
+}
+
+impl Input {
+    /// Splits the Input values into shell like tokens, see [`tokenize`]
+    pub fn tokens(&self) -> Vec<Token> {
+        tokenize(&self.values)
+    }
+}
+
+#[cfg(test)]
+mod test_tokens {
+    use super::tokenize;
+
+    fn texts(line: &str) -> Vec<String> {
+        tokenize(&line.chars().collect::<Vec<char>>())
+            .into_iter()
+            .map(|t| t.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(texts("  ls -la  src "), ["ls", "-la", "src"]);
+        assert_eq!(texts(r#"echo "a b" 'c d'e"#), ["echo", "a b", "c de"]);
+        assert_eq!(texts(r#"a\ b "\"\n" '\'"#), ["a b", "\"\\n", "\\"]);
+    }
+
+    #[test]
+    fn test_spans() {
+        let line = "é 'ça va".chars().collect::<Vec<char>>();
+        let tokens = tokenize(&line);
+
+        assert_eq!(tokens[0].chars, 0..1);
+        assert_eq!(tokens[0].bytes, 0..2);
+        assert_eq!(tokens[1].chars, 2..8);
+        assert_eq!(tokens[1].bytes, 3..10);
+        assert_eq!(tokens[1].text, "ça va");
+        assert_eq!(tokens[1].open_quote, Some('\''));
+    }
+}
@@ -0,0 +1,139 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::input::Input;
+
+/// Renders an Input (prompt, values and an optional hint) as a single line ratatui widget,
+/// scrolling the line horizontally to keep the cursor in view
+#[derive(Debug, Clone)]
+pub struct InputWidget<'a> {
+    input: &'a Input,
+    hint: Option<&'a str>,
+    style: Style,
+    prompt_style: Style,
+    hint_style: Style,
+}
+
+impl<'a> InputWidget<'a> {
+    /// Creates a new InputWidget for the given Input
+    pub fn new(input: &'a Input) -> Self {
+        Self {
+            input,
+            hint: None,
+            style: Style::default(),
+            prompt_style: Style::default(),
+            hint_style: Style::default().add_modifier(Modifier::DIM),
+        }
+    }
+
+    /// Sets the hint text rendered after the Input values
+    pub fn hint(mut self, hint: &'a str) -> Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    /// Sets the style of the Input values
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the style of the prompt
+    pub fn prompt_style(mut self, style: Style) -> Self {
+        self.prompt_style = style;
+        self
+    }
+
+    /// Sets the style of the hint, dimmed by default
+    pub fn hint_style(mut self, style: Style) -> Self {
+        self.hint_style = style;
+        self
+    }
+
+    /// Returns where the terminal cursor goes when the widget is rendered in area,
+    /// to be handed to `Frame::set_cursor_position`
+    pub fn cursor_position(&self, area: Rect) -> Position {
+        let column = self.input.prompt.chars().count() + self.input.cursor - self.scroll(area);
+
+        Position::new(
+            area.x + (column as u16).min(area.width.saturating_sub(1)),
+            area.y,
+        )
+    }
+
+    // the number of leading cells scrolled out of view so that the cursor stays visible
+    fn scroll(&self, area: Rect) -> usize {
+        let column = self.input.prompt.chars().count() + self.input.cursor;
+
+        (column + 1).saturating_sub(area.width as usize)
+    }
+}
+
+impl Widget for InputWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        let values = self.input.values.iter().collect::<String>();
+        let segments = [
+            (self.input.prompt.as_str(), self.prompt_style),
+            (values.as_str(), self.style),
+            (self.hint.unwrap_or(""), self.hint_style),
+        ];
+        let mut skip = self.scroll(area);
+        let mut x = area.x;
+        for (text, style) in segments {
+            let count = text.chars().count();
+            let visible = text.chars().skip(skip).collect::<String>();
+            skip = skip.saturating_sub(count);
+
+            let width = area.right().saturating_sub(x) as usize;
+            x = buf.set_stringn(x, area.y, visible, width, style).0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_tui {
+    use super::InputWidget;
+    use crate::input::Input;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::{Position, Rect};
+    use ratatui::style::Modifier;
+    use ratatui::widgets::Widget;
+
+    #[test]
+    fn test_render() {
+        let mut i = Input::new("> ", false);
+        i.set_text("pika");
+        let area = Rect::new(0, 0, 12, 1);
+        let mut buf = Buffer::empty(area);
+
+        InputWidget::new(&i).hint("chu").render(area, &mut buf);
+        let mut expected = Buffer::with_lines(["> pikachu   "]);
+        expected.set_style(Rect::new(6, 0, 3, 1), Modifier::DIM);
+        assert_eq!(buf, expected);
+        assert_eq!(
+            InputWidget::new(&i).cursor_position(area),
+            Position::new(6, 0)
+        );
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut i = Input::new("> ", false);
+        i.set_text("pikachu");
+        let area = Rect::new(0, 0, 6, 1);
+        let mut buf = Buffer::empty(area);
+
+        InputWidget::new(&i).render(area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(["kachu "]));
+        assert_eq!(
+            InputWidget::new(&i).cursor_position(area),
+            Position::new(5, 0)
+        );
+    }
+}
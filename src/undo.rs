@@ -0,0 +1,221 @@
+use crate::input::Input;
+
+/// A change of the Input values: the chars removed from start and the ones inserted in their
+/// place, along with the cursor before and after the change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub start: usize,
+    pub removed: Vec<char>,
+    pub inserted: Vec<char>,
+    pub cursor_before: usize,
+    pub cursor_after: usize,
+}
+
+impl Edit {
+    /// Creates the Edit replacing removed with inserted at start, trimmed of the chars they
+    /// begin or end with alike; None if they are equal
+    pub fn new(
+        start: usize,
+        removed: Vec<char>,
+        inserted: Vec<char>,
+        cursor_before: usize,
+        cursor_after: usize,
+    ) -> Option<Self> {
+        let mut edit = Self::diff(&removed, &inserted, cursor_before, cursor_after)?;
+        edit.start += start;
+
+        Some(edit)
+    }
+
+    /// Returns the Edit turning the before values into the after ones, None if they are equal
+    pub fn diff(
+        before: &[char],
+        after: &[char],
+        cursor_before: usize,
+        cursor_after: usize,
+    ) -> Option<Self> {
+        if before == after {
+            return None;
+        }
+        let prefix = before.iter().zip(after).take_while(|(a, b)| a == b).count();
+        let suffix = before[prefix..]
+            .iter()
+            .rev()
+            .zip(after[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        Some(Self {
+            start: prefix,
+            removed: before[prefix..before.len() - suffix].to_vec(),
+            inserted: after[prefix..after.len() - suffix].to_vec(),
+            cursor_before,
+            cursor_after,
+        })
+    }
+
+    // whether the edit only typed a char right after the chars typed by the previous one,
+    // the typing gets undone a word at a time
+    fn continues(&self, previous: &Edit) -> bool {
+        self.removed.is_empty()
+            && previous.removed.is_empty()
+            && self.inserted.len() == 1
+            && previous.start + previous.inserted.len() == self.start
+            && previous.inserted.last().is_some_and(|c| !c.is_whitespace())
+    }
+}
+
+/// The edits of the line being edited, undone newest first then redone in the order they
+/// were undone, see [`Input::undo`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoStack {
+    undos: Vec<Edit>,
+    redos: Vec<Edit>,
+    /// the number of edits kept, the oldest ones get dropped first
+    pub max: usize,
+    // bumped by every change of the stack, tells whether an edit was already recorded
+    pub(crate) generation: usize,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+impl UndoStack {
+    /// Creates a new empty UndoStack keeping up to max edits
+    pub fn new(max: usize) -> Self {
+        Self {
+            undos: Vec::new(),
+            redos: Vec::new(),
+            max,
+            generation: 0,
+        }
+    }
+
+    /// Records an edit, the undone edits can no longer be redone
+    pub fn push(&mut self, edit: Edit) {
+        self.generation += 1;
+        self.redos.clear();
+        if self.max == 0 {
+            return;
+        }
+        if let Some(last) = self.undos.last_mut() {
+            if edit.continues(last) {
+                last.inserted.extend(edit.inserted);
+                last.cursor_after = edit.cursor_after;
+                return;
+            }
+        }
+        if self.undos.len() == self.max {
+            self.undos.remove(0);
+        }
+        self.undos.push(edit);
+    }
+
+    /// Drops all the edits
+    pub fn clear(&mut self) {
+        self.generation += 1;
+        self.undos.clear();
+        self.redos.clear();
+    }
+
+    /// Returns true if there is an edit to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undos.is_empty()
+    }
+
+    /// Returns true if there is an undone edit to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redos.is_empty()
+    }
+}
+
+impl Input {
+    /// Reverts the newest edit of the line
+    ///
+    /// returns false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.undo.undos.pop() else {
+            return false;
+        };
+        let end = (edit.start + edit.inserted.len()).min(self.values.len());
+        self.values
+            .splice(edit.start.min(end)..end, edit.removed.iter().copied());
+        self.cursor = edit.cursor_before.min(self.values.len());
+        self.changes += 1;
+        self.undo.generation += 1;
+        self.undo.redos.push(edit);
+
+        true
+    }
+
+    /// Applies the newest undone edit again
+    ///
+    /// returns false if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.undo.redos.pop() else {
+            return false;
+        };
+        let end = (edit.start + edit.removed.len()).min(self.values.len());
+        self.values
+            .splice(edit.start.min(end)..end, edit.inserted.iter().copied());
+        self.cursor = edit.cursor_after.min(self.values.len());
+        self.changes += 1;
+        self.undo.generation += 1;
+        self.undo.undos.push(edit);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test_undo {
+    use super::{Edit, UndoStack};
+    use crate::history::History;
+    use crate::input::Input;
+    use crate::ops::InputOp;
+
+    #[test]
+    fn test_diff() {
+        let before = "cargo test".chars().collect::<Vec<char>>();
+        let after = "cargo build test".chars().collect::<Vec<char>>();
+        let edit = Edit::diff(&before, &after, 6, 12).unwrap();
+
+        assert_eq!((edit.start, edit.removed.len()), (6, 0));
+        assert_eq!(edit.inserted.iter().collect::<String>(), "build ");
+        assert_eq!(Edit::diff(&before, &before, 0, 3), None);
+
+        let mut stack = UndoStack::new(1);
+        stack.push(edit.clone());
+        stack.push(Edit::diff(&after, &before, 12, 6).unwrap());
+        assert!(stack.can_undo());
+        assert_eq!(stack.undos.len(), 1);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut i = Input::new("> ", false);
+        let mut h = History::new();
+        "cargo test"
+            .chars()
+            .for_each(|c| _ = i.apply(&InputOp::InsertChar(c), &mut h));
+        assert!(i.apply(&InputOp::ClearLeft, &mut h));
+
+        assert!(i.apply(&InputOp::Undo, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "cargo test");
+        assert!(i.apply(&InputOp::Undo, &mut h));
+        assert_eq!(i.values.iter().collect::<String>(), "cargo ");
+        assert!(i.apply(&InputOp::Redo, &mut h));
+        assert_eq!((i.values.len(), i.cursor), (10, 10));
+
+        assert!(i.apply(&InputOp::Backspace, &mut h));
+        assert!(!i.apply(&InputOp::Redo, &mut h));
+        assert!(i.apply(&InputOp::Undo, &mut h));
+        assert!(i.apply(&InputOp::Undo, &mut h));
+        assert!(i.apply(&InputOp::Undo, &mut h));
+        assert!(i.values.is_empty());
+        assert!(!i.apply(&InputOp::Undo, &mut h));
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt::Debug;
+
+/// What a [`Validator`] makes of the line Enter was pressed on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Validation {
+    /// the line gets submitted
+    Valid,
+    /// the line goes on, on a new line of a multiline Input or else on a continuation line
+    Incomplete,
+    /// the line stays in place and editable, with the message rendered under it
+    Invalid(String),
+}
+
+/// Checks the line before Enter submits it, see [`crate::Editor::validator`]
+/// # Object Safety
+///
+/// This trait is object safe
+pub trait Validator: Debug {
+    /// Returns what to do with the line, the continuation lines typed so far included
+    fn validate(&mut self, line: &str) -> Validation;
+}
+
+/// A Validator calling a closure
+pub struct FnValidator<F>(pub F);
+
+impl<F> Debug for FnValidator<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnValidator").finish_non_exhaustive()
+    }
+}
+
+impl<F: FnMut(&str) -> Validation> Validator for FnValidator<F> {
+    fn validate(&mut self, line: &str) -> Validation {
+        (self.0)(line)
+    }
+}
+
+/// A Validator finding the lines with unclosed brackets or quotes incomplete, and those
+/// closing a bracket that was never opened invalid
+///
+/// the brackets in quotes don't count, a backslash escapes the char after it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Brackets;
+
+impl Validator for Brackets {
+    fn validate(&mut self, line: &str) -> Validation {
+        let mut open = Vec::new();
+        let mut quote = None;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (_, '\\') => _ = chars.next(),
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => (),
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '(' | '[' | '{') => open.push(c),
+                (None, ')' | ']' | '}') => {
+                    let opener = match c {
+                        ')' => '(',
+                        ']' => '[',
+                        _ => '{',
+                    };
+                    if open.pop() != Some(opener) {
+                        return Validation::Invalid(format!("unmatched `{}`", c));
+                    }
+                }
+                (None, _) => (),
+            }
+        }
+
+        match open.is_empty() && quote.is_none() {
+            true => Validation::Valid,
+            false => Validation::Incomplete,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_validate {
+    use super::{Brackets, Validation, Validator};
+
+    #[test]
+    fn test_brackets() {
+        let mut v = Brackets;
+        assert_eq!(v.validate("f(x, [1, 2])"), Validation::Valid);
+        assert_eq!(v.validate("f(x, [1,\n2"), Validation::Incomplete);
+        assert_eq!(v.validate("print(\"(\")"), Validation::Valid);
+        assert_eq!(v.validate("'it\\'s"), Validation::Incomplete);
+        assert_eq!(
+            v.validate("f(x]"),
+            Validation::Invalid("unmatched `]`".to_string())
+        );
+    }
+}
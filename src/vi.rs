@@ -0,0 +1,423 @@
+use std::ops::Range;
+
+use crate::history::History;
+use crate::input::{next_boundary, prev_boundary, CharSearch, Input, Mode};
+use crate::ops::InputOp;
+
+/// The vi command typed so far in the normal mode, see [`Input::vi_command`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ViPending {
+    // the repeat count, 0 when none was typed
+    count: usize,
+    // the operator waiting for its motion: d, c or y
+    operator: Option<char>,
+    // the f, F, t, T or r waiting for its char
+    awaiting: Option<char>,
+    // the i or a of the text object waiting for its w or W
+    object: Option<char>,
+}
+
+// the class of a char for the word motions: 0 for the whitespaces, 1 for the word chars and
+// 2 for the punctuation, the big words (W, B, E) only tell the whitespaces apart
+fn class(c: char, big: bool) -> u8 {
+    match (c.is_whitespace(), big || c.is_alphanumeric() || c == '_') {
+        (true, _) => 0,
+        (false, true) => 1,
+        (false, false) => 2,
+    }
+}
+
+// the start of the word after idx, vi's w
+fn word_start(v: &[char], idx: usize, big: bool) -> usize {
+    let mut idx = idx;
+    if let Some(cl) = v.get(idx).map(|c| class(*c, big)).filter(|cl| *cl != 0) {
+        while idx < v.len() && class(v[idx], big) == cl {
+            idx += 1;
+        }
+    }
+    while idx < v.len() && class(v[idx], big) == 0 {
+        idx += 1;
+    }
+
+    idx
+}
+
+// the start of the word before idx, vi's b
+fn word_back(v: &[char], idx: usize, big: bool) -> usize {
+    let mut idx = idx;
+    while idx > 0 && class(v[idx - 1], big) == 0 {
+        idx -= 1;
+    }
+    if let Some(cl) = idx.checked_sub(1).map(|prev| class(v[prev], big)) {
+        while idx > 0 && class(v[idx - 1], big) == cl {
+            idx -= 1;
+        }
+    }
+
+    idx
+}
+
+// the last char of the word after idx, vi's e
+fn word_end(v: &[char], idx: usize, big: bool) -> usize {
+    let mut idx = idx + 1;
+    while idx < v.len() && class(v[idx], big) == 0 {
+        idx += 1;
+    }
+    if idx >= v.len() {
+        return v.len().saturating_sub(1);
+    }
+    let cl = class(v[idx], big);
+    while idx + 1 < v.len() && class(v[idx + 1], big) == cl {
+        idx += 1;
+    }
+
+    idx
+}
+
+impl Input {
+    /// Leaves the insert mode for the vi normal mode, the cursor steps back onto the last
+    /// inserted char
+    pub fn vi_normal(&mut self) {
+        if self.mode != Mode::Normal && self.cursor > 0 {
+            self.cursor = prev_boundary(&self.values, self.cursor);
+        }
+        self.set_mode(Mode::Normal);
+        self.vi_pending = ViPending::default();
+    }
+
+    /// Interprets a char typed in the vi normal mode, see [`Input::vi`]
+    ///
+    /// the supported commands are the motions `h l 0 ^ $ w b e W B E f F t T ; ,`, the
+    /// operators `d c y` followed by a motion, by themselves for the whole line or by the text
+    /// objects `iw aw iW aW`, along with `x X D C s S r i a I A p P u j k` and the repeat
+    /// counts; the deleted and yanked texts go to the kill ring
+    ///
+    /// returns false if the command failed, e.g. a motion going nowhere
+    pub fn vi_command(&mut self, c: char, history: &mut History) -> bool {
+        let mut pending = std::mem::take(&mut self.vi_pending);
+        let count = pending.count.max(1);
+        let done = match (pending.awaiting.take(), pending.object.take()) {
+            (Some('r'), _) => self.vi_replace(c, count),
+            (Some(cmd), _) => {
+                let search = CharSearch {
+                    c,
+                    forward: matches!(cmd, 'f' | 't'),
+                    till: matches!(cmd, 't' | 'T'),
+                };
+                self.vi_motion(pending.operator, |i| i.vi_find(Some(search), count))
+            }
+            (None, Some(object)) => match (c, pending.operator) {
+                ('w' | 'W', Some(op)) => {
+                    let range = self.vi_word_object(object == 'a', c == 'W');
+                    self.vi_operate(op, range)
+                }
+                _ => false,
+            },
+            (None, None) => match (c, pending.operator) {
+                ('1'..='9', _) | ('0', _) if c != '0' || pending.count > 0 => {
+                    pending.count = pending.count * 10 + c.to_digit(10).unwrap_or(0) as usize;
+                    self.vi_pending = pending;
+                    return true;
+                }
+                ('d' | 'c' | 'y', Some(op)) if op == c => self.vi_operate(op, 0..self.values.len()),
+                ('d' | 'c' | 'y', None) | ('i' | 'a', Some(_)) | ('f' | 'F' | 't' | 'T', _) => {
+                    match c {
+                        'd' | 'c' | 'y' => pending.operator = Some(c),
+                        'i' | 'a' => pending.object = Some(c),
+                        _ => pending.awaiting = Some(c),
+                    }
+                    self.vi_pending = pending;
+                    return true;
+                }
+                ('r', None) => {
+                    pending.awaiting = Some(c);
+                    self.vi_pending = pending;
+                    return true;
+                }
+                // cw on a word changes up to its end, like ce
+                ('w' | 'W', Some('c'))
+                    if self
+                        .values
+                        .get(self.cursor)
+                        .is_some_and(|c| !c.is_whitespace()) =>
+                {
+                    let big = c == 'W';
+                    self.vi_motion(Some('c'), |i| {
+                        Some((i.vi_repeat(count, |v, idx| word_end(v, idx, big)), true))
+                    })
+                }
+                (_, Some(op)) => match self.vi_target(c, count) {
+                    Some(target) => self.vi_motion(Some(op), |_| Some(target)),
+                    None => false,
+                },
+                (_, None) => self.vi_edit(c, count, history),
+            },
+        };
+        self.vi_clamp();
+
+        done
+    }
+
+    // the normal mode commands that are not operators nor motions, or the motion of c
+    fn vi_edit(&mut self, c: char, count: usize, history: &mut History) -> bool {
+        let len = self.values.len();
+        match c {
+            'x' | 's' if self.cursor < len => {
+                let end = (0..count).fold(self.cursor, |idx, _| next_boundary(&self.values, idx));
+                let op = if c == 'x' { 'd' } else { 'c' };
+                self.vi_operate(op, self.cursor..end)
+            }
+            'X' if self.cursor > 0 => {
+                let start = (0..count).fold(self.cursor, |idx, _| prev_boundary(&self.values, idx));
+                self.vi_operate('d', start..self.cursor)
+            }
+            'D' => self.vi_operate('d', self.cursor..len),
+            'C' => self.vi_operate('c', self.cursor..len),
+            'S' => self.vi_operate('c', 0..len),
+            'i' | 'a' | 'I' | 'A' => {
+                self.cursor = match c {
+                    'a' if self.cursor < len => next_boundary(&self.values, self.cursor),
+                    'I' => self.values.iter().take_while(|c| c.is_whitespace()).count(),
+                    'A' => len,
+                    _ => self.cursor,
+                };
+                self.set_mode(Mode::Insert);
+                true
+            }
+            'p' | 'P' => {
+                if c == 'p' && self.cursor < len {
+                    self.cursor = next_boundary(&self.values, self.cursor);
+                }
+                (0..count).all(|_| self.yank())
+            }
+            'u' => (0..count).all(|_| self.undo()),
+            'j' => self.apply(&InputOp::HistoryNext, history),
+            'k' => self.apply(&InputOp::HistoryPrev, history),
+            _ => match self.vi_target(c, count) {
+                Some(target) if target.0 != self.cursor => {
+                    self.cursor = target.0;
+                    true
+                }
+                _ => false,
+            },
+        }
+    }
+
+    // the index a motion goes to and whether the char there is included when an operator
+    // applies to the motion
+    fn vi_target(&mut self, c: char, count: usize) -> Option<(usize, bool)> {
+        let v = &self.values;
+        Some(match c {
+            'h' => (
+                (0..count).fold(self.cursor, |idx, _| prev_boundary(v, idx)),
+                false,
+            ),
+            'l' | ' ' => (
+                (0..count).fold(self.cursor, |idx, _| next_boundary(v, idx)),
+                false,
+            ),
+            '0' => (0, false),
+            '^' => (v.iter().take_while(|c| c.is_whitespace()).count(), false),
+            '$' => (v.len(), false),
+            'w' | 'W' => (
+                self.vi_repeat(count, |v, idx| word_start(v, idx, c == 'W')),
+                false,
+            ),
+            'b' | 'B' => (
+                self.vi_repeat(count, |v, idx| word_back(v, idx, c == 'B')),
+                false,
+            ),
+            'e' | 'E' => (
+                self.vi_repeat(count, |v, idx| word_end(v, idx, c == 'E')),
+                true,
+            ),
+            ';' | ',' => {
+                let mut search = self.last_find?;
+                search.forward ^= c == ',';
+                let target = self.vi_find(Some(search), count);
+                // the repeated jump is remembered in its original direction
+                self.last_find = self.last_find.map(|last| CharSearch {
+                    forward: last.forward ^ (c == ','),
+                    ..last
+                });
+                target?
+            }
+            _ => return None,
+        })
+    }
+
+    // applies a word motion count times from the cursor
+    fn vi_repeat(&self, count: usize, motion: impl Fn(&[char], usize) -> usize) -> usize {
+        (0..count).fold(self.cursor, |idx, _| motion(&self.values, idx))
+    }
+
+    // the index of the count-th char jump from the cursor, the cursor stays in place
+    fn vi_find(&mut self, search: Option<CharSearch>, count: usize) -> Option<(usize, bool)> {
+        let search = search?;
+        let cursor = self.cursor;
+        let found = (0..count).all(|_| self.find_char(search));
+        let target = std::mem::replace(&mut self.cursor, cursor);
+
+        found.then_some((target, search.forward))
+    }
+
+    // moves the cursor to the target of a motion, or applies the operator to the chars
+    // between the cursor and the target
+    fn vi_motion(
+        &mut self,
+        operator: Option<char>,
+        motion: impl FnOnce(&mut Self) -> Option<(usize, bool)>,
+    ) -> bool {
+        let Some((target, inclusive)) = motion(self) else {
+            return false;
+        };
+        match operator {
+            Some(op) => {
+                let range = match target < self.cursor {
+                    true => target..self.cursor,
+                    false if inclusive && target < self.values.len() => {
+                        self.cursor..next_boundary(&self.values, target)
+                    }
+                    false => self.cursor..target,
+                };
+                self.vi_operate(op, range)
+            }
+            None => {
+                self.cursor = target;
+                true
+            }
+        }
+    }
+
+    // deletes (d), changes (c) or yanks (y) the chars of range
+    fn vi_operate(&mut self, op: char, range: Range<usize>) -> bool {
+        if range.is_empty() && op != 'c' {
+            return false;
+        }
+        let text = self.values[range.clone()].iter().collect::<String>();
+        // the secrets typed in a sensitive line must not outlive it
+        if !self.sensitive {
+            self.kill_ring.push(text);
+        }
+        self.cursor = range.start;
+        if op != 'y' {
+            self.values.drain(range);
+            self.changes += 1;
+        }
+        if op == 'c' {
+            self.set_mode(Mode::Insert);
+        }
+
+        true
+    }
+
+    // replaces count chars from the cursor with c, the cursor ending on the last one
+    fn vi_replace(&mut self, c: char, count: usize) -> bool {
+        let end = (0..count).fold(self.cursor, |idx, _| next_boundary(&self.values, idx));
+        if self.cursor == self.values.len() || end - self.cursor < count {
+            return false;
+        }
+        self.values
+            .splice(self.cursor..end, std::iter::repeat_n(c, count));
+        self.cursor += count - 1;
+        self.changes += 1;
+
+        true
+    }
+
+    // the word (iw) or the word and the whitespaces around it (aw) under the cursor
+    fn vi_word_object(&self, around: bool, big: bool) -> Range<usize> {
+        let v = &self.values;
+        let Some(cl) = v.get(self.cursor).map(|c| class(*c, big)) else {
+            return self.cursor..self.cursor;
+        };
+        let (mut start, mut end) = (self.cursor, self.cursor);
+        while start > 0 && class(v[start - 1], big) == cl {
+            start -= 1;
+        }
+        while end < v.len() && class(v[end], big) == cl {
+            end += 1;
+        }
+        if around {
+            let trailing = end;
+            while end < v.len() && (class(v[end], big) == 0) != (cl == 0) {
+                end += 1;
+            }
+            // a word without trailing whitespaces takes the ones before it instead
+            if end == trailing && cl != 0 {
+                while start > 0 && class(v[start - 1], big) == 0 {
+                    start -= 1;
+                }
+            }
+        }
+
+        start..end
+    }
+
+    // keeps the cursor on a char in the normal mode, it can not sit after the last one
+    fn vi_clamp(&mut self) {
+        if self.mode == Mode::Normal && self.cursor == self.values.len() && self.cursor > 0 {
+            self.cursor = prev_boundary(&self.values, self.cursor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_vi {
+    use crate::history::History;
+    use crate::input::{Input, Mode};
+
+    fn typed(i: &mut Input, h: &mut History, commands: &str) {
+        commands.chars().for_each(|c| _ = i.vi_command(c, h));
+    }
+
+    #[test]
+    fn test_motions() {
+        let mut i = Input::new("> ", false);
+        let mut h = History::new();
+        i.set_text("cargo test --release");
+        i.vi_normal();
+        assert_eq!(i.cursor, 19);
+
+        typed(&mut i, &mut h, "0w");
+        assert_eq!(i.cursor, 6);
+        typed(&mut i, &mut h, "2w");
+        assert_eq!(i.cursor, 13);
+        typed(&mut i, &mut h, "BE");
+        assert_eq!(i.cursor, 19);
+        typed(&mut i, &mut h, "Fe");
+        assert_eq!(i.cursor, 16);
+        typed(&mut i, &mut h, ";");
+        assert_eq!(i.cursor, 14);
+        typed(&mut i, &mut h, "$b^");
+        assert_eq!(i.cursor, 0);
+        assert!(!i.vi_command('h', &mut h));
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut i = Input::new("> ", false);
+        let mut h = History::new();
+        i.set_text("cargo test --release");
+        i.vi_normal();
+
+        typed(&mut i, &mut h, "0dw");
+        assert_eq!(i.values.iter().collect::<String>(), "test --release");
+        typed(&mut i, &mut h, "2wciw");
+        assert_eq!(i.mode, Mode::Insert);
+        assert_eq!(i.values.iter().collect::<String>(), "test --");
+        "build".chars().for_each(|c| i.put_char(c));
+        i.vi_normal();
+
+        typed(&mut i, &mut h, "0xp");
+        assert_eq!(i.values.iter().collect::<String>(), "etst --build");
+        typed(&mut i, &mut h, "$daw");
+        assert_eq!(i.values.iter().collect::<String>(), "etst --");
+        typed(&mut i, &mut h, "0r3");
+        assert_eq!(i.values.iter().collect::<String>(), "3tst --");
+        typed(&mut i, &mut h, "dd");
+        assert!(i.values.is_empty());
+        typed(&mut i, &mut h, "P");
+        assert_eq!(i.values.iter().collect::<String>(), "3tst --");
+    }
+}
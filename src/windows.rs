@@ -0,0 +1,166 @@
+// windows console configuration, the ANSI escapes written by the renderer only get interpreted
+// by the console once virtual terminal processing is enabled on its output handle, and the
+// raw mode is the console input mode without line input, echo nor Ctrl-C processing
+
+use std::ffi::c_void;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+type Handle = *mut c_void;
+
+const STD_INPUT_HANDLE: u32 = -10i32 as u32;
+const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+const ENABLE_PROCESSED_INPUT: u32 = 0x0001;
+const ENABLE_LINE_INPUT: u32 = 0x0002;
+const ENABLE_ECHO_INPUT: u32 = 0x0004;
+const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+const DISABLE_NEWLINE_AUTO_RETURN: u32 = 0x0008;
+// no console mode has every bit set, so this marks the absence of a saved mode
+const UNSET: u32 = u32::MAX;
+
+#[repr(C)]
+#[derive(Default)]
+struct Coord {
+    x: i16,
+    y: i16,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct SmallRect {
+    left: i16,
+    top: i16,
+    right: i16,
+    bottom: i16,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct ScreenBufferInfo {
+    size: Coord,
+    cursor_position: Coord,
+    attributes: u16,
+    window: SmallRect,
+    maximum_window_size: Coord,
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetStdHandle(std_handle: u32) -> Handle;
+    fn GetConsoleMode(console: Handle, mode: *mut u32) -> i32;
+    fn SetConsoleMode(console: Handle, mode: u32) -> i32;
+    fn GetConsoleScreenBufferInfo(console: Handle, info: *mut ScreenBufferInfo) -> i32;
+}
+
+// the console output mode from before enable_vt_processing was called
+static ORIGINAL_OUTPUT_MODE: AtomicU32 = AtomicU32::new(UNSET);
+// the console input mode from before enable_raw_input was called
+static ORIGINAL_INPUT_MODE: AtomicU32 = AtomicU32::new(UNSET);
+
+// the handle and mode of the console of a standard handle, None if it is no console
+fn console_mode(std_handle: u32) -> Option<(Handle, u32)> {
+    let mut mode = 0;
+    // SAFETY: GetStdHandle has no preconditions and GetConsoleMode only writes to mode
+    unsafe {
+        let handle = GetStdHandle(std_handle);
+        match GetConsoleMode(handle, &mut mode) {
+            0 => None,
+            _ => Some((handle, mode)),
+        }
+    }
+}
+
+/// Enables virtual terminal processing on the console output, saving the previous mode
+///
+/// the automatic carriage return after a line feed goes too where supported, so that the
+/// line feeds and the wrapping at the last column behave as in a unix terminal in raw mode
+///
+/// returns false if stdout is not a console or the console doesn't support it
+pub(crate) fn enable_vt_processing() -> bool {
+    let Some((handle, mode)) = console_mode(STD_OUTPUT_HANDLE) else {
+        return false;
+    };
+    _ = ORIGINAL_OUTPUT_MODE.compare_exchange(UNSET, mode, Ordering::SeqCst, Ordering::SeqCst);
+    let vt = mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING;
+
+    // SAFETY: handle was validated by GetConsoleMode
+    unsafe {
+        SetConsoleMode(handle, vt | DISABLE_NEWLINE_AUTO_RETURN) != 0
+            || SetConsoleMode(handle, vt) != 0
+    }
+}
+
+/// Restores the console output mode saved by enable_vt_processing
+pub(crate) fn restore_output_mode() {
+    let mode = ORIGINAL_OUTPUT_MODE.swap(UNSET, Ordering::SeqCst);
+    if mode == UNSET {
+        return;
+    }
+    if let Some((handle, _)) = console_mode(STD_OUTPUT_HANDLE) {
+        // SAFETY: handle was validated by GetConsoleMode
+        unsafe {
+            SetConsoleMode(handle, mode);
+        }
+    }
+}
+
+/// Puts the console input in raw mode: no line input, echo nor Ctrl-C processing, which then
+/// comes in as a key; the previous mode is saved for restore_input_mode
+///
+/// # Errors
+/// Returns an [`ErrorKind::Unsupported`] error if stdin is not a console, or the error of
+/// SetConsoleMode
+pub(crate) fn enable_raw_input() -> Result<()> {
+    let Some((handle, mode)) = console_mode(STD_INPUT_HANDLE) else {
+        return Err(Error::new(ErrorKind::Unsupported, "stdin is not a console"));
+    };
+    // enabling twice must not lose the cooked mode
+    _ = ORIGINAL_INPUT_MODE.compare_exchange(UNSET, mode, Ordering::SeqCst, Ordering::SeqCst);
+    let raw = mode & !(ENABLE_PROCESSED_INPUT | ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT);
+
+    // SAFETY: handle was validated by GetConsoleMode
+    match unsafe { SetConsoleMode(handle, raw) } {
+        0 => Err(Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Restores the console input mode saved by enable_raw_input, does nothing if the raw mode
+/// was not enabled
+///
+/// # Errors
+/// Returns the error of SetConsoleMode
+pub(crate) fn restore_input_mode() -> Result<()> {
+    let mode = ORIGINAL_INPUT_MODE.swap(UNSET, Ordering::SeqCst);
+    if mode == UNSET {
+        return Ok(());
+    }
+    let Some((handle, _)) = console_mode(STD_INPUT_HANDLE) else {
+        return Ok(());
+    };
+
+    // SAFETY: handle was validated by GetConsoleMode
+    match unsafe { SetConsoleMode(handle, mode) } {
+        0 => Err(Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the (columns, rows) of the visible window of the console screen buffer
+///
+/// # Errors
+/// Returns the error of GetConsoleScreenBufferInfo, e.g. when stdout is not a console
+pub(crate) fn console_size() -> Result<(u16, u16)> {
+    let mut info = ScreenBufferInfo::default();
+    // SAFETY: GetConsoleScreenBufferInfo only writes to info
+    let ok = unsafe { GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), &mut info) };
+    if ok == 0 {
+        return Err(Error::last_os_error());
+    }
+    let window = info.window;
+
+    Ok((
+        (window.right - window.left + 1) as u16,
+        (window.bottom - window.top + 1) as u16,
+    ))
+}
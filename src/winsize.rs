@@ -0,0 +1,101 @@
+use std::io::Result;
+#[cfg(unix)]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The size of the terminal window, in cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WinSize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+#[cfg(unix)]
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn on_sigwinch(_: libc::c_int) {
+    RESIZED.store(true, Ordering::SeqCst);
+}
+
+/// Returns the size of the terminal, asked through `ioctl(TIOCGWINSZ)` on stdout, stdin then
+/// `/dev/tty`, the first of them being a terminal; through the console API on windows
+///
+/// # Errors
+/// Returns the error of the last ioctl if none of them is a terminal, or if the terminal
+/// reports a zero size
+pub fn window_size() -> Result<WinSize> {
+    #[cfg(unix)]
+    {
+        let ioctl = |fd: libc::c_int| {
+            // SAFETY: TIOCGWINSZ fills the zeroed winsize
+            let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+            match unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) } {
+                0 if size.ws_col > 0 && size.ws_row > 0 => Ok(WinSize {
+                    cols: size.ws_col,
+                    rows: size.ws_row,
+                }),
+                0 => Err(std::io::Error::other("the terminal reports a zero size")),
+                _ => Err(std::io::Error::last_os_error()),
+            }
+        };
+
+        ioctl(libc::STDOUT_FILENO)
+            .or_else(|_| ioctl(libc::STDIN_FILENO))
+            .or_else(|_| {
+                use std::os::fd::AsRawFd;
+                let tty = std::fs::File::open("/dev/tty")?;
+                ioctl(tty.as_raw_fd())
+            })
+    }
+    #[cfg(windows)]
+    return crate::windows::console_size().map(|(cols, rows)| WinSize { cols, rows });
+    #[cfg(not(any(unix, windows)))]
+    crossterm::terminal::size().map(|(cols, rows)| WinSize { cols, rows })
+}
+
+/// Starts watching for the terminal resizes by handling SIGWINCH, see [`take_resized`]
+///
+/// the handler replaces the one of crossterm, which then no longer reports the resizes; only
+/// meant for the reads that don't go through crossterm, e.g. those of [`crate::Decoder`]
+///
+/// # Errors
+/// Returns the error of sigaction, or [`std::io::ErrorKind::Unsupported`] on windows, where
+/// the resizes are console input events
+pub fn watch_resize() -> Result<()> {
+    #[cfg(unix)]
+    return crate::term::handle_signal(libc::SIGWINCH, on_sigwinch, libc::SA_RESTART);
+    #[cfg(not(unix))]
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "resizes are reported as console input events",
+    ))
+}
+
+/// Returns true if the terminal was resized since the last call, once [`watch_resize`] was
+/// called
+pub fn take_resized() -> bool {
+    #[cfg(unix)]
+    return RESIZED.swap(false, Ordering::SeqCst);
+    #[cfg(not(unix))]
+    false
+}
+
+#[cfg(all(test, unix))]
+mod test_winsize {
+    use super::{take_resized, watch_resize, window_size};
+
+    #[test]
+    fn test_resize_notification() {
+        // the test harness may or may not run in a terminal
+        if let Ok(size) = window_size() {
+            assert!(size.cols > 0 && size.rows > 0);
+        }
+
+        watch_resize().unwrap();
+        assert!(!take_resized());
+        // SAFETY: the SIGWINCH handler only stores to an atomic
+        unsafe { libc::raise(libc::SIGWINCH) };
+        assert!(take_resized());
+        assert!(!take_resized());
+    }
+}